@@ -1,11 +1,34 @@
 use crate::modules::ModuleManager;
-use clap::App;
+use clap::{App, AppSettings, Arg};
 
 pub fn build_app<'a, 'b>() -> (App<'a, 'b>, ModuleManager<'a, 'b>) {
 	let mut app = App::new(env!("CARGO_PKG_NAME"))
 		.version(env!("CARGO_PKG_VERSION"))
 		.author(env!("CARGO_PKG_AUTHORS"))
-		.about(env!("CARGO_PKG_DESCRIPTION"));
+		.about(env!("CARGO_PKG_DESCRIPTION"))
+		.args(&global_args())
+		// Not global, -v is the version of uuid_gen, so it goes before the sub command
+		.arg(
+			Arg::with_name("verbose_short")
+				.short("v")
+				.help("Same as --verbose, before the sub command")
+				.multiple(true),
+		)
+		// Not global either, -n is the namespace of uuid_gen
+		.arg(
+			Arg::with_name("no_newline_short")
+				.short("n")
+				.help("Same as --no-newline, before the sub command"),
+		)
+		// Not global either, -o is the octal of ns
+		.arg(
+			Arg::with_name("output_short")
+				.short("o")
+				.help("Same as --output, before the sub command")
+				.takes_value(true),
+		)
+		// The last occurrence wins, so the command line overrides the config file
+		.global_setting(AppSettings::AllArgsOverrideSelf);
 
 	let module_manager = ModuleManager::new();
 	let subcommands = module_manager.apps();
@@ -16,3 +39,130 @@ pub fn build_app<'a, 'b>() -> (App<'a, 'b>, ModuleManager<'a, 'b>) {
 
 	(app, module_manager)
 }
+
+/// Args handled by the dispatcher, available to every sub command
+pub fn global_args<'a, 'b>() -> Vec<Arg<'a, 'b>> {
+	vec![
+		Arg::with_name("json")
+			.long("json")
+			.help("Output as JSON")
+			.global(true),
+		Arg::with_name("json_style")
+			.long("json-style")
+			.help("Layout of the JSON output [default: pretty on a terminal, compact otherwise]")
+			.takes_value(true)
+			.possible_values(&["pretty", "compact"])
+			.global(true),
+		Arg::with_name("separator")
+			.long("separator")
+			.help("Separator of the output lines")
+			.takes_value(true)
+			.possible_values(&["newline", "comma", "tab", "null", "space"])
+			.default_value("newline")
+			.global(true),
+		Arg::with_name("color")
+			.long("color")
+			.help("Colorize the output, auto: only on a terminal unless NO_COLOR is set")
+			.takes_value(true)
+			.possible_values(&["auto", "always", "never"])
+			.default_value("auto")
+			.global(true),
+		Arg::with_name("output")
+			.long("output")
+			.help("Write output to file, - for stdout")
+			.takes_value(true)
+			.global(true),
+		Arg::with_name("force")
+			.long("force")
+			.help("Overwrite the file of --output or the key files of jwt_keygen if they exist, or go past the --max-memory of scrypt")
+			.global(true),
+		Arg::with_name("each")
+			.long("each")
+			.help("Run the sub command once for each line of stdin (or --file) as INPUT")
+			.global(true),
+		Arg::with_name("prefix")
+			.long("prefix")
+			.help("Prefix of the output lines with --each")
+			.takes_value(true)
+			.possible_values(&["input", "line", "none"])
+			.default_value("line")
+			.global(true),
+		Arg::with_name("jobs")
+			.long("jobs")
+			.short("j")
+			.help("Number of threads running the lines of --each, the order of the output is kept [default: number of logical CPUs]")
+			.takes_value(true)
+			.global(true),
+		Arg::with_name("fail_fast")
+			.long("fail-fast")
+			.help("Stop at the first failed line with --each")
+			.global(true),
+		Arg::with_name("null")
+			.long("null")
+			.short("0")
+			.help("Split the input of --each on NUL and terminate output records with NUL")
+			.global(true),
+		Arg::with_name("time")
+			.long("time")
+			.help("Print the elapsed time (and peak RSS on Linux) to stderr")
+			.global(true),
+		Arg::with_name("time_json")
+			.long("time-json")
+			.help("Add elapsed_ms (and peak_rss_kb on Linux) to the --json output")
+			.requires("json")
+			.global(true),
+		Arg::with_name("repeat")
+			.long("repeat")
+			.help("Run the sub command that many times and print the min, median, mean and max elapsed time to stderr, the output is the one of the first run")
+			.takes_value(true)
+			.conflicts_with("each")
+			.global(true),
+		Arg::with_name("encoding")
+			.long("encoding")
+			.help("Encoding of the text input of --file or stdin, e.g. latin1, shift_jis, utf-16le [default: the encoding of a UTF-8 or UTF-16 BOM, or else UTF-8]")
+			.takes_value(true)
+			.global(true),
+		Arg::with_name("max_input_size")
+			.long("max-input-size")
+			.visible_alias("max-input-bytes")
+			.help("Maximum size of the input read from stdin or --file, in bytes or with a K, M or G suffix [default: 16M]")
+			.takes_value(true)
+			.global(true),
+		Arg::with_name("no_stdin_wait")
+			.long("no-stdin-wait")
+			.help("Fail instead of waiting for stdin when INPUT is missing and stdin is a terminal")
+			.global(true),
+		Arg::with_name("no_config")
+			.long("no-config")
+			.help("Do not read the default args of the config file")
+			.global(true),
+		// No -n, it is --no-newline before the sub command and --namespace of uuid_gen
+		Arg::with_name("dry_run")
+			.long("dry-run")
+			.help("Validate the arguments of s2qr, jwt_encode and uuid_gen without producing the output")
+			.global(true),
+		Arg::with_name("no_newline")
+			.long("no-newline")
+			.help("Do not print the trailing newline of the output")
+			.global(true),
+		Arg::with_name("error_json")
+			.long("error-json")
+			.help("Print errors to stderr as a JSON object with the kind, message and exit code")
+			.global(true),
+		Arg::with_name("raw")
+			.long("raw")
+			.help("Write the output byte exact, without the trailing newline or any messages on stderr, aes_* and chacha_* write and read the raw cipher rather than Base64")
+			.conflicts_with("json")
+			.global(true),
+		Arg::with_name("quiet")
+			.long("quiet")
+			.short("q")
+			.help("Print nothing but errors to stderr")
+			.global(true),
+		Arg::with_name("verbose")
+			.long("verbose")
+			.help("Print the parameters chosen and the elapsed time to stderr, twice for every step tried")
+			.multiple(true)
+			.global(true),
+	]
+}
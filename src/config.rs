@@ -0,0 +1,328 @@
+use crate::app;
+use crate::modules::Output;
+use clap::{App, ArgMatches, ErrorKind, SubCommand};
+use linked_hash_map::LinkedHashMap;
+use serde_json::{json, Map, Value};
+use std::env;
+use std::ffi::OsString;
+use std::fs;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+/// Default args per sub command: long name and value, None for flags
+pub type Settings = LinkedHashMap<String, Vec<(String, Option<String>)>>;
+
+/// Global args taking a value, skipped when looking for the sub command
+const GLOBAL_VALUE_ARGS: &[&str] = &[
+	"-o",
+	"--output",
+	"--json-style",
+	"--separator",
+	"--prefix",
+	"--color",
+	"-j",
+	"--jobs",
+	"--repeat",
+	"--encoding",
+	"--max-input-size",
+	"--max-input-bytes",
+];
+
+pub fn app<'a, 'b>() -> App<'a, 'b> {
+	SubCommand::with_name("config")
+		.about("Show the config file path and the default args it gives to the sub commands")
+}
+
+pub fn run(matches: &ArgMatches) -> Result<Output, String> {
+	let path = path().ok_or("Can not resolve the config file path")?;
+	let settings = if matches.is_present("no_config") {
+		Settings::new()
+	} else {
+		let (app, module_manager) = app::build_app();
+		let names = module_manager
+			.apps()
+			.iter()
+			.map(|x| x.get_name().to_string())
+			.collect::<Vec<_>>();
+		load(&path, &app, &names, &mut io::stderr())?
+	};
+
+	if matches.is_present("json") {
+		let settings = settings
+			.iter()
+			.map(|(name, args)| {
+				let args = args
+					.iter()
+					.map(|(key, value)| {
+						(key.clone(), value.clone().map_or(json!(true), Value::from))
+					})
+					.collect::<Map<_, _>>();
+				(name.clone(), Value::Object(args))
+			})
+			.collect::<Map<_, _>>();
+		return Ok(Output::Structured(json!({
+			"path": path.to_string_lossy(),
+			"exists": path.exists(),
+			"settings": settings,
+		})));
+	}
+
+	let mut result = vec![format!(
+		"Path: {}{}",
+		path.display(),
+		if path.exists() { "" } else { " (not found)" }
+	)];
+	for (name, args) in settings {
+		result.push(format!("[{}]", name));
+		result.extend(args.into_iter().map(|(key, value)| match value {
+			Some(value) => format!("{} = {}", key, value),
+			None => format!("{} = true", key),
+		}));
+	}
+
+	Ok(Output::Lines(result))
+}
+
+/// The command line args with the settings of the config file injected,
+/// unless --no-config is given
+pub fn command_line(app: &App, names: &[String]) -> Result<Vec<OsString>, String> {
+	let args = env::args_os().collect::<Vec<_>>();
+	if args.iter().any(|x| x == "--no-config") {
+		return Ok(args);
+	}
+	// Leave args which are not valid UTF-8 to clap
+	let args = match args.iter().map(|x| x.clone().into_string()).collect() {
+		Ok(args) => args,
+		Err(_) => return Ok(args),
+	};
+
+	let settings = match path() {
+		Some(path) => load(&path, app, names, &mut io::stderr())?,
+		None => Settings::new(),
+	};
+
+	Ok(inject_args(args, &settings)
+		.into_iter()
+		.map(OsString::from)
+		.collect())
+}
+
+/// The config file, `DTOOL_CONFIG` overrides `$XDG_CONFIG_HOME/dtool/config.toml`
+pub fn path() -> Option<PathBuf> {
+	if let Some(path) = env::var_os("DTOOL_CONFIG") {
+		return Some(PathBuf::from(path));
+	}
+	let config_home = env::var_os("XDG_CONFIG_HOME")
+		.filter(|x| !x.is_empty())
+		.map(PathBuf::from)
+		.or_else(|| env::var_os("HOME").map(|x| PathBuf::from(x).join(".config")))?;
+	Some(config_home.join("dtool").join("config.toml"))
+}
+
+/// Read the settings of the sub commands in `names`, unknown sections and keys
+/// are skipped with a warning
+pub fn load<W: Write>(
+	path: &Path,
+	app: &App,
+	names: &[String],
+	warn: &mut W,
+) -> Result<Settings, String> {
+	let mut settings = Settings::new();
+	if !path.exists() {
+		return Ok(settings);
+	}
+
+	let content = fs::read_to_string(path)
+		.map_err(|e| format!("Failed to read file {}: {}", path.display(), e))?;
+	let table = content
+		.parse::<toml::Table>()
+		.map_err(|e| format!("Invalid config file {}: {}", path.display(), e))?;
+
+	let mut warning = |message: String| {
+		writeln!(warn, "Warning: {} in {}", message, path.display()).map_err(|e| e.to_string())
+	};
+
+	for (name, entries) in table {
+		let entries = match entries {
+			toml::Value::Table(entries) if names.contains(&name) => entries,
+			_ => {
+				warning(format!("unknown section [{}]", name))?;
+				continue;
+			}
+		};
+
+		let mut args = vec![];
+		for (key, value) in entries {
+			let value = match value {
+				toml::Value::String(value) => Some(expand_tilde(&value)),
+				toml::Value::Integer(value) => Some(value.to_string()),
+				toml::Value::Float(value) => Some(value.to_string()),
+				toml::Value::Boolean(true) => None,
+				toml::Value::Boolean(false) => continue,
+				_ => {
+					warning(format!("invalid value of {} in [{}]", key, name))?;
+					continue;
+				}
+			};
+
+			// Let clap tell whether the sub command knows the arg
+			let long = format!("--{}", key);
+			let trial = vec!["dtool", name.as_str(), long.as_str()]
+				.into_iter()
+				.chain(value.as_deref());
+			if let Err(e) = app.clone().get_matches_from_safe(trial) {
+				if e.kind == ErrorKind::UnknownArgument {
+					warning(format!("unknown key {} in [{}]", key, name))?;
+					continue;
+				}
+			}
+
+			args.push((key, value));
+		}
+		settings.insert(name, args);
+	}
+
+	Ok(settings)
+}
+
+/// Insert the settings of the sub command right after its name, so the args
+/// given on the command line come later and win
+pub fn inject_args(args: Vec<String>, settings: &Settings) -> Vec<String> {
+	let mut index = 1;
+	while index < args.len() {
+		let arg = args[index].as_str();
+		if settings.contains_key(arg) {
+			break;
+		}
+		// Skip the value too
+		if GLOBAL_VALUE_ARGS.contains(&arg) {
+			index += 1;
+		}
+		index += 1;
+	}
+	let name = match args.get(index) {
+		Some(name) => name,
+		None => return args,
+	};
+
+	let given = &args[index + 1..];
+	let defaults = settings[name]
+		.iter()
+		.filter(|(key, _)| {
+			let long = format!("--{}", key);
+			!given
+				.iter()
+				.any(|x| *x == long || x.starts_with(&format!("{}=", long)))
+		})
+		.flat_map(|(key, value)| Some(format!("--{}", key)).into_iter().chain(value.clone()));
+
+	args[..=index]
+		.iter()
+		.cloned()
+		.chain(defaults)
+		.chain(given.iter().cloned())
+		.collect()
+}
+
+fn expand_tilde(value: &str) -> String {
+	match (value.strip_prefix("~/"), env::var("HOME")) {
+		(Some(rest), Ok(home)) => Path::new(&home).join(rest).to_string_lossy().to_string(),
+		_ => value.to_string(),
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn args(args: &str) -> Vec<String> {
+		args.split(' ').map(str::to_string).collect()
+	}
+
+	#[test]
+	fn test_load() {
+		let path = env::temp_dir().join(format!("dtool_config_{}.toml", std::process::id()));
+		fs::write(
+			&path,
+			r#"
+[jwt_verify]
+key-file = "~/.keys/hs.pem"
+unknown = "x"
+
+[s2qr]
+dpi = 300
+json = true
+
+[unknown]
+a = 1
+"#,
+		)
+		.unwrap();
+
+		let (app, module_manager) = app::build_app();
+		let names = module_manager
+			.apps()
+			.iter()
+			.map(|x| x.get_name().to_string())
+			.collect::<Vec<_>>();
+		let mut warn = vec![];
+		let settings = load(&path, &app, &names, &mut warn).unwrap();
+		fs::remove_file(&path).unwrap();
+
+		let key_file = Path::new(&env::var("HOME").unwrap()).join(".keys/hs.pem");
+		assert_eq!(
+			settings["jwt_verify"],
+			vec![(
+				"key-file".to_string(),
+				Some(key_file.to_string_lossy().to_string())
+			)]
+		);
+		assert_eq!(
+			settings["s2qr"],
+			vec![
+				("dpi".to_string(), Some("300".to_string())),
+				("json".to_string(), None)
+			]
+		);
+		assert!(settings.get("unknown").is_none());
+
+		let warn = String::from_utf8(warn).unwrap();
+		assert!(warn.contains("Warning: unknown key unknown in [jwt_verify]"));
+		assert!(warn.contains("Warning: unknown section [unknown]"));
+	}
+
+	#[test]
+	fn test_inject_args() {
+		let mut settings = Settings::new();
+		settings.insert(
+			"s2qr".to_string(),
+			vec![
+				("dpi".to_string(), Some("300".to_string())),
+				("json".to_string(), None),
+			],
+		);
+
+		assert_eq!(
+			inject_args(args("dtool -o s2qr s2qr hello"), &settings),
+			args("dtool -o s2qr s2qr --dpi 300 --json hello")
+		);
+		// A per sub command limit, the global value is not taken for the sub command
+		settings.insert(
+			"h2s".to_string(),
+			vec![("max-input-size".to_string(), Some("1K".to_string()))],
+		);
+		assert_eq!(
+			inject_args(args("dtool --max-input-size h2s h2s 0x61"), &settings),
+			args("dtool --max-input-size h2s h2s --max-input-size 1K 0x61")
+		);
+		// Args given on the command line win
+		assert_eq!(
+			inject_args(args("dtool s2qr --dpi=72 hello"), &settings),
+			args("dtool s2qr --json --dpi=72 hello")
+		);
+		assert_eq!(
+			inject_args(args("dtool s2h hello"), &settings),
+			args("dtool s2h hello")
+		);
+	}
+}
@@ -0,0 +1,77 @@
+//! dtool as a library: the core functions of the QR, JWT and UUID modules, usable
+//! without building any command line args
+
+use clap::ErrorKind;
+
+mod app;
+mod config;
+mod log;
+mod modules;
+mod plugin;
+
+pub use modules::{jwt, qr, uuid, Error};
+
+/// Run the dtool command line with the args of the process
+pub fn run() {
+	let (app, module_manager) = app::build_app();
+
+	let mut app_clone = app.clone();
+
+	let names = module_manager
+		.apps()
+		.iter()
+		.map(|x| x.get_name().to_string())
+		.collect::<Vec<_>>();
+	// Like git and cargo, an unknown sub command runs the plugin dtool-<name> in PATH
+	let args_os = std::env::args_os().collect::<Vec<_>>();
+	if let Some(name) = args_os.get(1).and_then(|x| x.to_str()) {
+		if !name.starts_with('-') && name != "help" && !names.iter().any(|x| x == name) {
+			if let Some(path) = plugin::find(name) {
+				plugin::exec(&path, &args_os[2..]);
+			}
+		}
+	}
+
+	let args = match config::command_line(&app, &names) {
+		Ok(args) => args,
+		Err(e) => {
+			log::error(&e);
+			std::process::exit(modules::exit_code::USAGE);
+		}
+	};
+
+	let matches = match app.get_matches_from_safe(args) {
+		Ok(matches) => matches,
+		Err(e) if e.kind == ErrorKind::HelpDisplayed || e.kind == ErrorKind::VersionDisplayed => {
+			e.exit()
+		}
+		Err(e) => {
+			// The args did not parse, so --error-json is looked up by hand
+			if args_os.iter().any(|x| x == "--error-json") {
+				let error = modules::Error::Usage(e.message);
+				log::error(&error.to_json().to_string());
+			} else {
+				log::error(&e.message);
+			}
+			std::process::exit(modules::exit_code::USAGE);
+		}
+	};
+
+	let (name, sub_matches) = matches.subcommand();
+
+	if let Some(sub_matches) = sub_matches {
+		let quiet = sub_matches.is_present("quiet") || sub_matches.is_present("raw");
+		let verbose =
+			sub_matches.occurrences_of("verbose") + matches.occurrences_of("verbose_short");
+		log::set_level(log::Level::from_args(quiet, verbose));
+		module_manager.run(
+			name,
+			sub_matches,
+			matches.is_present("no_newline_short"),
+			matches.value_of("output_short"),
+		);
+	} else {
+		app_clone.print_help().unwrap_or(());
+		println!();
+	}
+}
@@ -0,0 +1,155 @@
+//! Messages on stderr which are not part of the output, filtered by --quiet and -v
+
+use std::io::{self, IsTerminal, Write};
+use std::sync::atomic::{AtomicU8, Ordering};
+use std::sync::Mutex;
+
+/// How much goes to stderr, each level includes the ones before it
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Level {
+	/// Errors only, with --quiet or --raw
+	Error,
+	/// Notes like the file written by --output, the default
+	Info,
+	/// Decisions of the commands, with -v
+	Debug,
+	/// Every step tried, with -vv
+	Trace,
+}
+
+impl Level {
+	/// --quiet wins over any count of -v
+	pub fn from_args(quiet: bool, verbose: u64) -> Self {
+		match (quiet, verbose) {
+			(true, _) => Level::Error,
+			(false, 0) => Level::Info,
+			(false, 1) => Level::Debug,
+			(false, _) => Level::Trace,
+		}
+	}
+}
+
+/// Set by the dispatcher, shared by the threads of --jobs
+static LEVEL: AtomicU8 = AtomicU8::new(Level::Info as u8);
+
+/// Where the messages go, stderr unless replaced
+static WRITER: Mutex<Option<Box<dyn Write + Send>>> = Mutex::new(None);
+
+pub fn set_level(level: Level) {
+	LEVEL.store(level as u8, Ordering::SeqCst);
+}
+
+/// Send the messages to the writer instead of stderr, None to restore stderr
+#[cfg(test)]
+pub fn set_writer(writer: Option<Box<dyn Write + Send>>) {
+	*WRITER.lock().expect("no panics while writing") = writer;
+}
+
+pub fn enabled(level: Level) -> bool {
+	level as u8 <= LEVEL.load(Ordering::SeqCst)
+}
+
+pub fn log(level: Level, message: &str) {
+	if !enabled(level) {
+		return;
+	}
+	let prefix = match level {
+		Level::Error | Level::Info => "",
+		Level::Debug => "debug: ",
+		Level::Trace => "trace: ",
+	};
+	let mut writer = WRITER.lock().expect("no panics while writing");
+	// Nowhere left to report a failed write to stderr
+	let _ = match writer.as_mut() {
+		Some(writer) => writeln!(writer, "{}{}", prefix, message),
+		None => writeln!(io::stderr(), "{}{}", prefix, message),
+	};
+}
+
+pub fn error(message: &str) {
+	log(Level::Error, message);
+}
+
+pub fn info(message: &str) {
+	log(Level::Info, message);
+}
+
+pub fn debug(message: &str) {
+	log(Level::Debug, message);
+}
+
+pub fn trace(message: &str) {
+	log(Level::Trace, message);
+}
+
+/// A progress line on a terminal stderr, overwritten by the next one, None clears it;
+/// skipped with --quiet and when the messages do not go to stderr
+pub fn progress(message: Option<&str>) {
+	let to_stderr = WRITER.lock().expect("no panics while writing").is_none();
+	if !enabled(Level::Info) || !to_stderr || !io::stderr().is_terminal() {
+		return;
+	}
+	let mut stderr = io::stderr();
+	let _ = match message {
+		Some(message) => write!(stderr, "\r\x1b[K{}", message),
+		None => write!(stderr, "\r\x1b[K"),
+	};
+	let _ = stderr.flush();
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use std::sync::Arc;
+
+	#[derive(Clone, Default)]
+	struct Buffer(Arc<Mutex<Vec<u8>>>);
+
+	impl Write for Buffer {
+		fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+			self.0.lock().unwrap().write(buf)
+		}
+
+		fn flush(&mut self) -> io::Result<()> {
+			Ok(())
+		}
+	}
+
+	#[test]
+	fn test_levels() {
+		let buffer = Buffer::default();
+		set_writer(Some(Box::new(buffer.clone())));
+
+		let mut expected = vec![];
+		for (quiet, verbose) in [(true, 2), (false, 0), (false, 1), (false, 2)] {
+			let level = Level::from_args(quiet, verbose);
+			set_level(level);
+			let tag = format!("test_levels {} {}", quiet, verbose);
+			error(&tag);
+			info(&tag);
+			debug(&tag);
+			trace(&tag);
+
+			expected.push(tag.clone());
+			if level >= Level::Info {
+				expected.push(tag.clone());
+			}
+			if level >= Level::Debug {
+				expected.push(format!("debug: {}", tag));
+			}
+			if level >= Level::Trace {
+				expected.push(format!("trace: {}", tag));
+			}
+		}
+		set_level(Level::Info);
+		set_writer(None);
+
+		// Other tests may log meanwhile, only the lines of this one count
+		let written = String::from_utf8(buffer.0.lock().unwrap().clone()).unwrap();
+		let written = written
+			.lines()
+			.filter(|x| x.contains("test_levels"))
+			.collect::<Vec<_>>();
+		assert_eq!(written, expected);
+	}
+}
@@ -1,6 +1,6 @@
 use self::Mode::{CBC, CTR, ECB};
 use crate::modules::base::Hex;
-use crate::modules::{base, Command, Module};
+use crate::modules::{base, Command, Error, Module, Output};
 use clap::{Arg, ArgMatches, SubCommand};
 use aes::{Aes128, Aes192, Aes256};
 use aes::cipher::{BlockSizeUser, consts::U16};
@@ -9,6 +9,15 @@ use cbc::{Decryptor as CbcDecryptor, Encryptor as CbcEncryptor};
 use ctr::Ctr128BE;
 use ecb::{Decryptor as EcbDecryptor, Encryptor as EcbEncryptor};
 use cipher::block_padding::Pkcs7;
+use argon2::Argon2;
+use base64::{engine::general_purpose, Engine as _};
+use rand::rngs::OsRng;
+use rand::RngCore;
+use ring::aead::{self, Aad, LessSafeKey, Nonce, UnboundKey};
+use ring::pbkdf2;
+use std::convert::TryFrom;
+use std::fs;
+use std::num::NonZeroU32;
 
 pub fn module<'a, 'b>() -> Module<'a, 'b> {
 	Module {
@@ -29,6 +38,7 @@ pub fn commands<'a, 'b>() -> Vec<Command<'a, 'b>> {
 						.required(false)
 						.index(1),
 				)
+				.arg(base::file_arg())
 				.arg(
 					Arg::with_name("MODE")
 						.long("mode")
@@ -55,6 +65,8 @@ pub fn commands<'a, 'b>() -> Vec<Command<'a, 'b>> {
 						.required(false),
 				),
 			f: aes_enc,
+			serial: false,
+			pure: true,
 		},
 		Command {
 			app: SubCommand::with_name("aes_dec")
@@ -65,6 +77,7 @@ pub fn commands<'a, 'b>() -> Vec<Command<'a, 'b>> {
 						.required(false)
 						.index(1),
 				)
+				.arg(base::file_arg())
 				.arg(
 					Arg::with_name("MODE")
 						.long("mode")
@@ -91,10 +104,98 @@ pub fn commands<'a, 'b>() -> Vec<Command<'a, 'b>> {
 						.required(false),
 				),
 			f: aes_dec,
+			serial: false,
+			pure: true,
+		},
+		Command {
+			app: SubCommand::with_name("aes_encrypt")
+				.about("AES-GCM encrypt, the random nonce is prefixed to the ciphertext")
+				.arg(
+					Arg::with_name("INPUT")
+						.help("Plain")
+						.required(false)
+						.index(1),
+				)
+				.arg(base::file_arg())
+				.args(&key_args())
+				.arg(kdf_arg())
+				.arg(nonce_arg()),
+			f: aes_encrypt,
+			serial: false,
+			pure: true,
+		},
+		Command {
+			app: SubCommand::with_name("aes_decrypt")
+				.about("AES-GCM decrypt (exit code: 4 authentication failed)")
+				.arg(
+					Arg::with_name("INPUT")
+						.help("Cipher (Base64) of aes_encrypt")
+						.required(false)
+						.index(1),
+				)
+				.arg(base::file_arg())
+				.args(&key_args()),
+			f: aes_decrypt,
+			serial: false,
+			pure: true,
 		},
 	]
 }
 
+/// The key of the authenticated encryption commands, given or derived from a password,
+/// and their associated data
+pub(crate) fn key_args<'a, 'b>() -> Vec<Arg<'a, 'b>> {
+	vec![
+		Arg::with_name("KEY_HEX")
+			.long("key-hex")
+			.help("Key (Hex), 16 or 32 bytes for AES-128 or AES-256, 32 for ChaCha20")
+			.takes_value(true)
+			.required_unless_one(&["KEY_B64", "KEY_FILE", "PASSWORD"])
+			.conflicts_with_all(&["KEY_B64", "KEY_FILE", "PASSWORD"]),
+		Arg::with_name("KEY_B64")
+			.long("key-b64")
+			.help("Key (Base64)")
+			.takes_value(true)
+			.conflicts_with_all(&["KEY_FILE", "PASSWORD"]),
+		Arg::with_name("KEY_FILE")
+			.long("key-file")
+			.help("Key file of raw bytes, e.g. of rand_bytes --format raw")
+			.takes_value(true)
+			.conflicts_with("PASSWORD"),
+		Arg::with_name("PASSWORD")
+			.long("key-from-password")
+			.value_name("PASSWORD")
+			.help("Derive a 256-bit key from a password, its salt is stored in a header before the nonce")
+			.takes_value(true),
+		Arg::with_name("AAD")
+			.long("aad")
+			.help("Associated data, authenticated but not encrypted, the same is needed to decrypt")
+			.takes_value(true),
+		Arg::with_name("AAD_HEX")
+			.long("aad-hex")
+			.help("Associated data (Hex)")
+			.takes_value(true)
+			.conflicts_with("AAD"),
+	]
+}
+
+pub(crate) fn kdf_arg<'a, 'b>() -> Arg<'a, 'b> {
+	Arg::with_name("KDF")
+		.long("kdf")
+		.help("Key derivation of --key-from-password")
+		.takes_value(true)
+		.possible_values(&["argon2", "pbkdf2"])
+		.default_value("argon2")
+}
+
+/// A fixed nonce is only safe for test vectors
+pub(crate) fn nonce_arg<'a, 'b>() -> Arg<'a, 'b> {
+	Arg::with_name("NONCE_HEX")
+		.long("nonce-hex")
+		.takes_value(true)
+		.hidden(true)
+}
+
 enum Mode {
 	ECB,
 	CBC { iv: Vec<u8> },
@@ -107,7 +208,7 @@ enum KeySize {
 	KeySize256,
 }
 
-fn aes_enc(matches: &ArgMatches) -> Result<Vec<String>, String> {
+fn aes_enc(matches: &ArgMatches) -> Result<Output, Error> {
 	let (key_size, key, mode, input) = get_common_arg(matches)?;
 
 	// cipher
@@ -118,10 +219,10 @@ fn aes_enc(matches: &ArgMatches) -> Result<Vec<String>, String> {
 	}?;
 	let result = Hex::from(result).into();
 
-	Ok(vec![result])
+	Ok(Output::Lines(vec![result]))
 }
 
-fn aes_dec(matches: &ArgMatches) -> Result<Vec<String>, String> {
+fn aes_dec(matches: &ArgMatches) -> Result<Output, Error> {
 	let (key_size, key, mode, input) = get_common_arg(matches)?;
 
 	// plain
@@ -132,7 +233,7 @@ fn aes_dec(matches: &ArgMatches) -> Result<Vec<String>, String> {
 	}?;
 	let result = Hex::from(result).into();
 
-	Ok(vec![result])
+	Ok(Output::Lines(vec![result]))
 }
 
 fn get_common_arg(matches: &ArgMatches) -> Result<(KeySize, Vec<u8>, Mode, Vec<u8>), String> {
@@ -290,6 +391,225 @@ fn aes_dec_ctr(key_size: KeySize, key: &[u8], input: &[u8], iv: &[u8]) -> Result
 	aes_enc_ctr(key_size, key, input, iv)
 }
 
+const NONCE_LEN: usize = 12;
+const TAG_LEN: usize = 16;
+const SALT_LEN: usize = 16;
+const PBKDF2_ITERATIONS: u32 = 600_000;
+
+/// The key derivation of --key-from-password, the first byte of the header
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Kdf {
+	/// Argon2id with the defaults of the argon2 crate
+	Argon2 = 1,
+	/// PBKDF2-HMAC-SHA256 with PBKDF2_ITERATIONS
+	Pbkdf2 = 2,
+}
+
+/// Output: nonce | ciphertext | tag, after kdf | salt with --key-from-password
+fn aes_encrypt(matches: &ArgMatches) -> Result<Output, Error> {
+	let input = base::input_bytes(matches)?;
+	let nonce = nonce(matches)?;
+	let (header, key) = sealing_key(matches)?;
+	let result = header
+		.into_iter()
+		.chain(seal(&key, nonce, &aad(matches)?, &input)?)
+		.collect();
+
+	Ok(cipher_output(matches, result))
+}
+
+fn aes_decrypt(matches: &ArgMatches) -> Result<Output, Error> {
+	let input = cipher_input(matches)?;
+	let (key, sealed) = opening_key(matches, &input)?;
+
+	Ok(Output::Bytes(open(&key, &aad(matches)?, sealed)?))
+}
+
+/// The nonce of --nonce-hex, random otherwise
+pub(crate) fn nonce<const N: usize>(matches: &ArgMatches) -> Result<[u8; N], Error> {
+	match matches.value_of("NONCE_HEX") {
+		Some(nonce) => nonce
+			.parse::<Hex>()
+			.map(Vec::<u8>::from)
+			.ok()
+			.and_then(|x| <[u8; N]>::try_from(x).ok())
+			.ok_or_else(|| Error::Usage(format!("Invalid nonce, should be {} bytes", N))),
+		None => Ok(random()),
+	}
+}
+
+pub(crate) fn aad(matches: &ArgMatches) -> Result<Vec<u8>, Error> {
+	match matches.value_of("AAD_HEX") {
+		Some(aad) => Ok(aad
+			.parse::<Hex>()
+			.map_err(|_| Error::invalid_input("Invalid associated data".to_string()))?
+			.into()),
+		None => Ok(matches
+			.value_of("AAD")
+			.unwrap_or_default()
+			.as_bytes()
+			.to_vec()),
+	}
+}
+
+/// The cipher in base64, or the raw bytes with --raw
+pub(crate) fn cipher_input(matches: &ArgMatches) -> Result<Vec<u8>, Error> {
+	match matches.is_present("raw") {
+		true => Ok(base::input_bytes(matches)?),
+		false => {
+			let input = base::input_string(matches)?;
+			let input = input.split_whitespace().collect::<String>();
+			general_purpose::STANDARD
+				.decode(input)
+				.map_err(|e| Error::invalid_input(format!("Invalid base64: {}", e)))
+		}
+	}
+}
+
+pub(crate) fn cipher_output(matches: &ArgMatches, cipher: Vec<u8>) -> Output {
+	match matches.is_present("raw") {
+		true => Output::Bytes(cipher),
+		false => Output::Lines(vec![general_purpose::STANDARD.encode(cipher)]),
+	}
+}
+
+/// The header to prefix to the cipher, kdf | salt with --key-from-password, and the key
+pub(crate) fn sealing_key(matches: &ArgMatches) -> Result<(Vec<u8>, Vec<u8>), Error> {
+	let password = match matches.value_of("PASSWORD") {
+		Some(password) => password,
+		None => return Ok((vec![], given_key(matches)?)),
+	};
+	let kdf = match matches.value_of("KDF") {
+		Some("pbkdf2") => Kdf::Pbkdf2,
+		_ => Kdf::Argon2,
+	};
+	let salt: [u8; SALT_LEN] = random();
+	let key = derive_key(kdf, password, &salt)?;
+	let header = Some(kdf as u8).into_iter().chain(salt).collect();
+
+	Ok((header, key))
+}
+
+/// The key, derived from the password and the header with --key-from-password, and the
+/// cipher after the header
+pub(crate) fn opening_key<'i>(
+	matches: &ArgMatches,
+	input: &'i [u8],
+) -> Result<(Vec<u8>, &'i [u8]), Error> {
+	let password = match matches.value_of("PASSWORD") {
+		Some(password) => password,
+		None => return Ok((given_key(matches)?, input)),
+	};
+
+	if input.len() < 1 + SALT_LEN {
+		return Err(Error::invalid_input(
+			"The cipher is too short for the header of --key-from-password".to_string(),
+		));
+	}
+	let kdf = match input[0] {
+		1 => Kdf::Argon2,
+		2 => Kdf::Pbkdf2,
+		x => {
+			return Err(
+				Error::invalid_input(format!("Unknown key derivation {} in the header", x))
+					.with_hint("was it encrypted with --key-from-password?"),
+			)
+		}
+	};
+	let key = derive_key(kdf, password, &input[1..1 + SALT_LEN])?;
+
+	Ok((key, &input[1 + SALT_LEN..]))
+}
+
+/// The key of --key-hex, --key-b64 or --key-file, its size is checked by the cipher
+fn given_key(matches: &ArgMatches) -> Result<Vec<u8>, Error> {
+	match (
+		matches.value_of("KEY_HEX"),
+		matches.value_of("KEY_B64"),
+		matches.value_of("KEY_FILE"),
+	) {
+		(Some(key), ..) => Ok(key
+			.parse::<Hex>()
+			.map_err(|_| Error::invalid_input("Invalid key".to_string()))?
+			.into()),
+		(_, Some(key), _) => general_purpose::STANDARD
+			.decode(key)
+			.map_err(|e| Error::invalid_input(format!("Invalid key: {}", e))),
+		(_, _, Some(path)) => {
+			fs::read(path).map_err(|e| Error::io(&format!("Failed to read file {}", path), e))
+		}
+		_ => Err(Error::Usage("Key is required".to_string())),
+	}
+}
+
+fn derive_key(kdf: Kdf, password: &str, salt: &[u8]) -> Result<Vec<u8>, Error> {
+	let mut key = vec![0u8; 32];
+	match kdf {
+		Kdf::Argon2 => Argon2::default()
+			.hash_password_into(password.as_bytes(), salt, &mut key)
+			.map_err(|e| Error::Other(format!("Failed to derive the key: {}", e)))?,
+		Kdf::Pbkdf2 => pbkdf2::derive(
+			pbkdf2::PBKDF2_HMAC_SHA256,
+			NonZeroU32::new(PBKDF2_ITERATIONS).unwrap(),
+			salt,
+			password.as_bytes(),
+			&mut key,
+		),
+	}
+	Ok(key)
+}
+
+fn less_safe_key(key: &[u8]) -> Result<LessSafeKey, Error> {
+	let algorithm = match key.len() {
+		16 => &aead::AES_128_GCM,
+		_ => &aead::AES_256_GCM,
+	};
+	let key = UnboundKey::new(algorithm, key)
+		.map_err(|_| Error::Usage("Invalid key size (should be 128/256)".to_string()))?;
+	Ok(LessSafeKey::new(key))
+}
+
+/// nonce | ciphertext | tag
+fn seal(key: &[u8], nonce: [u8; NONCE_LEN], aad: &[u8], plain: &[u8]) -> Result<Vec<u8>, Error> {
+	let mut in_out = plain.to_vec();
+	less_safe_key(key)?
+		.seal_in_place_append_tag(
+			Nonce::assume_unique_for_key(nonce),
+			Aad::from(aad),
+			&mut in_out,
+		)
+		.map_err(|_| Error::Other("Failed to encrypt".to_string()))?;
+	Ok(nonce.iter().copied().chain(in_out).collect())
+}
+
+fn open(key: &[u8], aad: &[u8], sealed: &[u8]) -> Result<Vec<u8>, Error> {
+	if sealed.len() < NONCE_LEN + TAG_LEN {
+		return Err(Error::invalid_input(format!(
+			"The cipher is too short, it needs the {}-byte nonce and the {}-byte tag",
+			NONCE_LEN, TAG_LEN
+		)));
+	}
+	let (nonce, cipher) = sealed.split_at(NONCE_LEN);
+	let nonce = Nonce::try_assume_unique_for_key(nonce)
+		.map_err(|_| Error::invalid_input("Invalid nonce".to_string()))?;
+	let mut in_out = cipher.to_vec();
+	let plain = less_safe_key(key)?
+		.open_in_place(nonce, Aad::from(aad), &mut in_out)
+		.map_err(|_| authentication_failed())?;
+	Ok(plain.to_vec())
+}
+
+pub(crate) fn authentication_failed() -> Error {
+	Error::ValidationFailed("Authentication failed".to_string())
+		.with_hint("wrong key or --aad, or the cipher was modified")
+}
+
+fn random<const N: usize>() -> [u8; N] {
+	let mut bytes = [0u8; N];
+	OsRng.fill_bytes(&mut bytes);
+	bytes
+}
+
 mod cases {
 	use crate::modules::Case;
 	use linked_hash_map::LinkedHashMap;
@@ -956,6 +1276,186 @@ mod cases {
 					},
 				],
 			),
+			(
+				"aes_encrypt",
+				vec![
+					Case {
+						desc: "AES-128-GCM, the nonce is random".to_string(),
+						input: vec![
+							"--key-hex",
+							"feffe9928665731c6d6a8f9467308308",
+							"'hello world'",
+						]
+						.into_iter()
+						.map(Into::into)
+						.collect(),
+						output: vec![
+							"yv66vvrO263eyviI89dAi7bTBa6cR0x37bq6Zlk1YNwIT+/UxROA",
+						]
+						.into_iter()
+						.map(Into::into)
+						.collect(),
+						is_example: true,
+						is_test: false, // Random nonce
+						since: "0.17.0".to_string(),
+					},
+					Case {
+						desc: "AES-128-GCM with a fixed nonce".to_string(),
+						input: vec![
+							"--key-hex",
+							"feffe9928665731c6d6a8f9467308308",
+							"--nonce-hex",
+							"cafebabefacedbaddecaf888",
+							"'hello world'",
+						]
+						.into_iter()
+						.map(Into::into)
+						.collect(),
+						output: vec![
+							"yv66vvrO263eyviI89dAi7bTBa6cR0x37bq6Zlk1YNwIT+/UxROA",
+						]
+						.into_iter()
+						.map(Into::into)
+						.collect(),
+						is_example: false,
+						is_test: true,
+						since: "0.17.0".to_string(),
+					},
+					Case {
+						desc: "AES-256-GCM with associated data and a fixed nonce".to_string(),
+						input: vec![
+							"--key-hex",
+							"feffe9928665731c6d6a8f9467308308feffe9928665731c6d6a8f9467308308",
+							"--aad",
+							"v1",
+							"--nonce-hex",
+							"cafebabefacedbaddecaf888",
+							"'hello world'",
+						]
+						.into_iter()
+						.map(Into::into)
+						.collect(),
+						output: vec![
+							"yv66vvrO263eyviI43mfuQ7yDI0jSlqudfhkjIU2wlXaSMS8Gvoh",
+						]
+						.into_iter()
+						.map(Into::into)
+						.collect(),
+						is_example: false,
+						is_test: true,
+						since: "0.17.0".to_string(),
+					},
+					Case {
+						desc: "Key derived from a password".to_string(),
+						input: vec![
+							"--key-from-password",
+							"'correct horse'",
+							"'hello world'",
+						]
+						.into_iter()
+						.map(Into::into)
+						.collect(),
+						output: vec![
+							"AcPFkFPIilwidhNGNigf3bCCaz5zhzZA2Jqqu8JGjyz72yqvDDnpDmohVdiCGsV1arbwFBaWDzM=",
+						]
+						.into_iter()
+						.map(Into::into)
+						.collect(),
+						is_example: true,
+						is_test: false, // Random salt and nonce
+						since: "0.17.0".to_string(),
+					},
+				],
+			),
+			(
+				"aes_decrypt",
+				vec![
+					Case {
+						desc: "AES-128-GCM".to_string(),
+						input: vec![
+							"--key-hex",
+							"feffe9928665731c6d6a8f9467308308",
+							"yv66vvrO263eyviI89dAi7bTBa6cR0x37bq6Zlk1YNwIT+/UxROA",
+						]
+						.into_iter()
+						.map(Into::into)
+						.collect(),
+						output: vec![
+							"hello world",
+						]
+						.into_iter()
+						.map(Into::into)
+						.collect(),
+						is_example: true,
+						is_test: true,
+						since: "0.17.0".to_string(),
+					},
+					Case {
+						desc: "AES-256-GCM with associated data".to_string(),
+						input: vec![
+							"--key-hex",
+							"feffe9928665731c6d6a8f9467308308feffe9928665731c6d6a8f9467308308",
+							"--aad",
+							"v1",
+							"yv66vvrO263eyviI43mfuQ7yDI0jSlqudfhkjIU2wlXaSMS8Gvoh",
+						]
+						.into_iter()
+						.map(Into::into)
+						.collect(),
+						output: vec![
+							"hello world",
+						]
+						.into_iter()
+						.map(Into::into)
+						.collect(),
+						is_example: true,
+						is_test: true,
+						since: "0.17.0".to_string(),
+					},
+					Case {
+						desc: "NIST GCM test case 3 (AES-128)".to_string(),
+						input: vec![
+							"--key-hex",
+							"feffe9928665731c6d6a8f9467308308",
+							"--json",
+							"yv66vvrO263eyviIQoMewiF3dCRLciG3hNDUnOOqIS8sAqTgNcF+IymsoS4h1RSyVGaTHH2PalqshKoFG6MLOWoKrJc9WOCRRz9ZhU1cKvMnzWSmLPNavSum+rQ=",
+						]
+						.into_iter()
+						.map(Into::into)
+						.collect(),
+						output: vec![
+							"{\n  \"base64\": \"2TEyJfiEBuWlWQnFr/UmmoanqVMVNPfaLkwwPYoxinIcPAyVlWgJUy/PDiRJprUlsWrt9aoN5le6Y3s5Gq/SVQ==\"\n}",
+						]
+						.into_iter()
+						.map(Into::into)
+						.collect(),
+						is_example: false,
+						is_test: true,
+						since: "0.17.0".to_string(),
+					},
+					Case {
+						desc: "NIST GCM test case 15 (AES-256)".to_string(),
+						input: vec![
+							"--key-hex",
+							"feffe9928665731c6d6a8f9467308308feffe9928665731c6d6a8f9467308308",
+							"--json",
+							"yv66vvrO263eyviIUi3B8JlWfQf0fzejKoRCfWQ6jNy/5cDJdZiivSVV0aqMsI5IWQ27PaewixBWgog4xfYeY5O6egq8yfZiiYAVrbCU2sXZNHG97BpQInDjzGw=",
+						]
+						.into_iter()
+						.map(Into::into)
+						.collect(),
+						output: vec![
+							"{\n  \"base64\": \"2TEyJfiEBuWlWQnFr/UmmoanqVMVNPfaLkwwPYoxinIcPAyVlWgJUy/PDiRJprUlsWrt9aoN5le6Y3s5Gq/SVQ==\"\n}",
+						]
+						.into_iter()
+						.map(Into::into)
+						.collect(),
+						is_example: false,
+						is_test: true,
+						since: "0.17.0".to_string(),
+					},
+				],
+			),
 		]
 		.into_iter()
 		.collect()
@@ -966,9 +1466,162 @@ mod cases {
 mod tests {
 	use super::*;
 	use crate::modules::base::test::test_module;
+	use crate::modules::exit_code;
 
 	#[test]
 	fn test_cases() {
 		test_module(module());
 	}
+
+	#[test]
+	fn test_gcm_nist_vectors() {
+		let hex = |x: &str| -> Vec<u8> { x.parse::<Hex>().unwrap().into() };
+		// Test case 2 of the GCM spec
+		let sealed = seal(&[0; 16], [0; NONCE_LEN], b"", &[0; 16]).unwrap();
+		assert_eq!(
+			sealed[NONCE_LEN..],
+			hex("0388dace60b6a392f328c2b971b2fe78ab6e47d42cec13bdf53a67b21257bddf")[..]
+		);
+		assert_eq!(open(&[0; 16], b"", &sealed).unwrap(), vec![0; 16]);
+
+		// Test case 4, with associated data
+		let key = hex("feffe9928665731c6d6a8f9467308308");
+		let nonce = hex("cafebabefacedbaddecaf888");
+		let aad = hex("feedfacedeadbeeffeedfacedeadbeefabaddad2");
+		let plain = hex("d9313225f88406e5a55909c5aff5269a86a7a9531534f7da2e4c303d8a318a721c3c0c95956809532fcf0e2449a6b525b16aedf5aa0de657ba637b39");
+		let sealed = seal(
+			&key,
+			<[u8; NONCE_LEN]>::try_from(nonce).unwrap(),
+			&aad,
+			&plain,
+		)
+		.unwrap();
+		assert_eq!(
+			sealed[NONCE_LEN..],
+			hex("42831ec2217774244b7221b784d0d49ce3aa212f2c02a4e035c17e2329aca12e21d514b25466931c7d8f6a5aac84aa051ba30b396a0aac973d58e0915bc94fbc3221a5db94fae95ae7121a47")[..]
+		);
+		assert_eq!(open(&key, &aad, &sealed).unwrap(), plain);
+	}
+
+	fn run(name: &str, args: Vec<&str>) -> Result<Output, Error> {
+		let index = match name {
+			"aes_encrypt" => 2,
+			_ => 3,
+		};
+		let command = &commands()[index];
+		let matches = command
+			.app
+			.clone()
+			.args(&crate::app::global_args())
+			.get_matches_from(Some(name).into_iter().chain(args));
+		(command.f)(&matches)
+	}
+
+	fn text(output: Output) -> String {
+		output.render(false).unwrap().remove(0)
+	}
+
+	#[test]
+	fn test_gcm_round_trip() {
+		let key = "000102030405060708090a0b0c0d0e0f101112131415161718191a1b1c1d1e1f";
+		let cipher =
+			text(run("aes_encrypt", vec!["--key-hex", key, "--aad", "v1", "abc"]).unwrap());
+		// A random nonce each time
+		assert_ne!(
+			cipher,
+			text(run("aes_encrypt", vec!["--key-hex", key, "--aad", "v1", "abc"]).unwrap())
+		);
+		assert_eq!(
+			run(
+				"aes_decrypt",
+				vec!["--key-hex", key, "--aad", "v1", &cipher]
+			),
+			Ok(Output::Bytes(b"abc".to_vec()))
+		);
+
+		let path = std::env::temp_dir().join(format!("dtool_aes_key_{}", std::process::id()));
+		fs::write(&path, hex::decode(key).unwrap()).unwrap();
+		let path = path.to_string_lossy().to_string();
+		let key_b64 = general_purpose::STANDARD.encode(hex::decode(key).unwrap());
+		let sealed = match run("aes_encrypt", vec!["--key-file", &path, "--raw", "abc"]).unwrap() {
+			Output::Bytes(bytes) => bytes,
+			_ => unreachable!(),
+		};
+		assert_eq!(sealed.len(), NONCE_LEN + 3 + TAG_LEN);
+		assert_eq!(
+			open(&hex::decode(key).unwrap(), b"", &sealed),
+			Ok(b"abc".to_vec())
+		);
+		let cipher = general_purpose::STANDARD.encode(&sealed);
+		assert_eq!(
+			run("aes_decrypt", vec!["--key-b64", &key_b64, &cipher]),
+			Ok(Output::Bytes(b"abc".to_vec()))
+		);
+		fs::remove_file(&path).unwrap();
+	}
+
+	#[test]
+	fn test_gcm_password() {
+		for kdf in &["argon2", "pbkdf2"] {
+			let cipher = text(
+				run(
+					"aes_encrypt",
+					vec!["--key-from-password", "secret", "--kdf", kdf, "abc"],
+				)
+				.unwrap(),
+			);
+			let sealed = general_purpose::STANDARD.decode(&cipher).unwrap();
+			assert_eq!(sealed.len(), 1 + SALT_LEN + NONCE_LEN + 3 + TAG_LEN);
+			assert_eq!(
+				run(
+					"aes_decrypt",
+					vec!["--key-from-password", "secret", &cipher]
+				),
+				Ok(Output::Bytes(b"abc".to_vec()))
+			);
+			assert_eq!(
+				run("aes_decrypt", vec!["--key-from-password", "wrong", &cipher])
+					.map_err(|e| e.exit_code()),
+				Err(exit_code::INVALID)
+			);
+		}
+	}
+
+	#[test]
+	fn test_gcm_authentication_failed() {
+		let key = "feffe9928665731c6d6a8f9467308308";
+		let cipher = "yv66vvrO263eyviI89dAi7bTBa6cR0x37bq6Zlk1YNwIT+/UxROA";
+		let mut sealed = general_purpose::STANDARD.decode(cipher).unwrap();
+		sealed[NONCE_LEN] ^= 1;
+		let tampered = general_purpose::STANDARD.encode(&sealed);
+		for args in [
+			vec!["--key-hex", key, tampered.as_str()],
+			vec!["--key-hex", key, "--aad", "v2", cipher],
+			vec!["--key-hex", "00000000000000000000000000000000", cipher],
+		] {
+			let result = run("aes_decrypt", args);
+			assert_eq!(
+				result.clone().map_err(|e| e.exit_code()),
+				Err(exit_code::INVALID)
+			);
+			assert_eq!(
+				result.map_err(String::from),
+				Err("Authentication failed".to_string())
+			);
+		}
+
+		assert_eq!(
+			run("aes_decrypt", vec!["--key-hex", key, "AAAA"]),
+			Err(Error::invalid_input(
+				"The cipher is too short, it needs the 12-byte nonce and the 16-byte tag"
+					.to_string()
+			))
+		);
+		assert_eq!(
+			run("aes_encrypt", vec!["--key-hex", "0011", "abc"]),
+			Err(Error::Usage(
+				"Invalid key size (should be 128/256)".to_string()
+			))
+		);
+	}
 }
@@ -1,28 +1,398 @@
-use clap::ArgMatches;
+use crate::log;
+use crate::modules::Output;
+use base64::{engine::general_purpose, Engine as _};
+use clap::{Arg, ArgMatches};
+use encoding_rs::{DecoderResult, Encoding, UTF_8};
+use std::cell::{Cell, RefCell};
+use std::fs;
 use std::io;
-use std::io::{BufRead, Read};
+use std::io::{BufRead, IsTerminal, Read, Write};
+use std::path::Path;
 use std::str::FromStr;
 
+/// Stdin and --file are read into memory up to this size unless --max-input-size is given
+const DEFAULT_MAX_INPUT_SIZE: u64 = 16 * 1024 * 1024;
+
+/// The error of a command given an empty input
+pub const NO_INPUT: &str = "No input provided (pass an argument, use --file, or pipe via stdin)";
+
+/// `-f/--file` arg to read the input from a file instead of `INPUT` or stdin
+pub fn file_arg<'a, 'b>() -> Arg<'a, 'b> {
+	long_file_arg().short("f")
+}
+
+/// `file_arg` without the `-f` short, for sub commands which already use it
+pub fn long_file_arg<'a, 'b>() -> Arg<'a, 'b> {
+	Arg::with_name("FILE")
+		.long("file")
+		.help("Read input from file")
+		.takes_value(true)
+		.conflicts_with("INPUT")
+}
+
+/// Whether the stdin to read is a terminal, a trait so the check can be mocked
+pub trait Terminal {
+	fn is_terminal(&self) -> bool;
+}
+
+impl Terminal for io::Stdin {
+	fn is_terminal(&self) -> bool {
+		IsTerminal::is_terminal(self)
+	}
+}
+
+impl Terminal for io::StdinLock<'_> {
+	fn is_terminal(&self) -> bool {
+		IsTerminal::is_terminal(self)
+	}
+}
+
 pub fn input_string(matches: &ArgMatches) -> Result<String, String> {
-	match matches.value_of("INPUT") {
-		Some(input) => Ok(input.to_string()),
-		None => io::stdin()
-			.lock()
-			.lines()
-			.collect::<Result<Vec<String>, io::Error>>()
-			.map(|x| x.join("\n"))
-			.map_err(|_| "Invalid input".to_string()),
+	read_input_string(matches, io::stdin().lock()).and_then(non_empty)
+}
+
+/// A secret like a password: `INPUT`, `--file` or stdin as `input_string`, but prompted
+/// for with no echo when stdin is a terminal
+pub fn secret_input(matches: &ArgMatches, prompt: &str) -> Result<String, String> {
+	secret_input_or_empty(matches, prompt, false)
+}
+
+/// `secret_input` which may be empty with `allow_empty`, e.g. the empty password of the
+/// scrypt test vectors
+pub fn secret_input_or_empty(
+	matches: &ArgMatches,
+	prompt: &str,
+	allow_empty: bool,
+) -> Result<String, String> {
+	let prompt_needed = input_override().is_none()
+		&& matches.value_of("FILE").is_none()
+		&& matches.value_of("INPUT").is_none()
+		&& !matches.is_present("no_stdin_wait")
+		&& Terminal::is_terminal(&io::stdin());
+	if !prompt_needed {
+		let input = read_input_string(matches, io::stdin().lock())?;
+		return match allow_empty {
+			true => Ok(input),
+			false => non_empty(input),
+		};
 	}
+	rpassword::prompt_password(prompt).map_err(|e| format!("Failed to read the prompt: {}", e))
 }
 
 pub fn input_bytes(matches: &ArgMatches) -> Result<Vec<u8>, String> {
-	match matches.value_of("INPUT") {
-		Some(input) => Ok(input.bytes().collect::<Vec<u8>>()),
-		None => io::stdin()
-			.bytes()
-			.collect::<Result<Vec<u8>, io::Error>>()
-			.map_err(|_| "Invalid input".to_string()),
+	input_bytes_or_empty(matches, false)
+}
+
+/// `input_bytes` which may be empty with `allow_empty`, for the commands where an empty
+/// input means something, e.g. `s2qr --allow-empty`
+pub fn input_bytes_or_empty(matches: &ArgMatches, allow_empty: bool) -> Result<Vec<u8>, String> {
+	let input = read_input_bytes(matches, io::stdin())?;
+	match allow_empty {
+		true => Ok(input),
+		false => non_empty(input),
+	}
+}
+
+/// An empty input, e.g. `uuid_parse ""` or an empty pipe, fails the same way for every
+/// command rather than with a confusing error of its own
+fn non_empty<T: AsRef<[u8]>>(input: T) -> Result<T, String> {
+	match input.as_ref().is_empty() {
+		true => Err(NO_INPUT.to_string()),
+		false => Ok(input),
+	}
+}
+
+/// Streaming input for the commands which can process it incrementally: the bytes of
+/// `INPUT`, `--file` or stdin, without the size limit of `input_bytes`
+pub fn input_reader(matches: &ArgMatches) -> Result<Box<dyn Read>, String> {
+	open_input(matches, io::stdin())
+}
+
+fn open_input<'r, R: Read + Terminal + 'r>(
+	matches: &ArgMatches,
+	stdin: R,
+) -> Result<Box<dyn Read + 'r>, String> {
+	if let Some(input) = input_override() {
+		return Ok(Box::new(io::Cursor::new(input)));
+	}
+	match (matches.value_of("FILE"), matches.value_of("INPUT")) {
+		(Some(path), _) => fs::File::open(path)
+			.map(|file| Box::new(io::BufReader::new(file)) as Box<dyn Read + 'r>)
+			.map_err(|e| format!("Failed to read file {}: {}", path, e)),
+		(None, Some(input)) => Ok(Box::new(io::Cursor::new(input.as_bytes().to_vec()))),
+		(None, None) => {
+			check_stdin(matches, &stdin)?;
+			Ok(Box::new(stdin))
+		}
+	}
+}
+
+/// The limit of --max-input-size
+fn max_input_size(matches: &ArgMatches) -> Result<u64, String> {
+	match matches.value_of("max_input_size") {
+		Some(size) => parse_size(size).ok_or_else(|| format!("Invalid max input size: {}", size)),
+		None => Ok(DEFAULT_MAX_INPUT_SIZE),
+	}
+}
+
+/// A size in bytes with an optional K, M or G unit, e.g. 1048576, 512K or 64M
+pub fn parse_size(size: &str) -> Option<u64> {
+	let (digits, unit) = match size.char_indices().last() {
+		Some((i, c)) if c.is_ascii_alphabetic() => (&size[..i], c.to_ascii_uppercase()),
+		_ => (size, 'B'),
+	};
+	let shift = match unit {
+		'B' => 0,
+		'K' => 10,
+		'M' => 20,
+		'G' => 30,
+		_ => return None,
+	};
+	digits
+		.parse::<u64>()
+		.ok()
+		.and_then(|x| x.checked_mul(1 << shift))
+}
+
+/// Read to the end, failing as soon as more than `limit` bytes come instead of growing
+/// the buffer past it
+fn read_limited<R: Read>(reader: R, limit: u64) -> Result<Vec<u8>, String> {
+	let mut bytes = vec![];
+	reader
+		.take(limit.saturating_add(1))
+		.read_to_end(&mut bytes)
+		.map_err(|_| "Invalid input".to_string())?;
+	if bytes.len() as u64 > limit {
+		return Err(format!(
+			"Input exceeds maximum size of {} bytes, use --max-input-size",
+			limit
+		));
+	}
+	Ok(bytes)
+}
+
+/// Reading a terminal waits for the user, so say so on stderr, or fail right away
+/// with --no-stdin-wait
+fn check_stdin<T: Terminal>(matches: &ArgMatches, stdin: &T) -> Result<(), String> {
+	if !stdin.is_terminal() {
+		return Ok(());
+	}
+	if matches.is_present("no_stdin_wait") {
+		return Err(format!(
+			"No INPUT given and stdin is a terminal\n\n{}",
+			matches.usage()
+		));
+	}
+	log::info("reading from stdin, press Ctrl-D to finish, or pass INPUT as an argument");
+	Ok(())
+}
+
+fn read_input_string<R: BufRead + Terminal>(
+	matches: &ArgMatches,
+	mut stdin: R,
+) -> Result<String, String> {
+	if let Some(input) = input_override() {
+		return String::from_utf8(input).map_err(|_| "Invalid UTF-8 input".to_string());
+	}
+	let encoding = matches.value_of("encoding");
+	let text = match (matches.value_of("FILE"), matches.value_of("INPUT")) {
+		(Some(path), _) => decode_text(&read_file(path, max_input_size(matches)?)?, encoding)
+			.map_err(|e| format!("{} in file: {}", e, path))?,
+		(None, Some(input)) => return Ok(input.to_string()),
+		(None, None) => {
+			check_stdin(matches, &stdin)?;
+			let bytes = read_limited(&mut stdin, max_input_size(matches)?)?;
+			decode_text(&bytes, encoding)?
+		}
+	};
+	Ok(text.lines().collect::<Vec<_>>().join("\n"))
+}
+
+/// Decode text input with the encoding of --encoding, by default with the encoding of
+/// its UTF-8 or UTF-16 BOM, or else UTF-8; the BOM is stripped
+pub fn decode_text(bytes: &[u8], encoding: Option<&str>) -> Result<String, String> {
+	let bom = Encoding::for_bom(bytes);
+	let (encoding, bom_len) = match (encoding, bom) {
+		(Some(label), _) => {
+			let encoding = Encoding::for_label(label.as_bytes())
+				.ok_or_else(|| format!("Unknown encoding: {}", label))?;
+			// Only the BOM of the encoding itself is stripped, another one is text
+			match bom {
+				Some((x, len)) if x == encoding => (encoding, len),
+				_ => (encoding, 0),
+			}
+		}
+		(None, Some((encoding, len))) => (encoding, len),
+		(None, None) => (UTF_8, 0),
+	};
+	log::debug(&format!(
+		"Input decoded as {}{}",
+		encoding.name(),
+		if bom_len > 0 { " (BOM)" } else { "" }
+	));
+	if bom_len > 0 && bom_len == bytes.len() {
+		return Err(format!(
+			"Invalid {} input: no text after the BOM at byte {}",
+			encoding.name(),
+			bom_len
+		));
+	}
+
+	let input = &bytes[bom_len..];
+	let mut decoder = encoding.new_decoder_without_bom_handling();
+	let capacity = decoder
+		.max_utf8_buffer_length_without_replacement(input.len())
+		.ok_or("Input too large")?;
+	let mut text = String::with_capacity(capacity);
+	match decoder.decode_to_string_without_replacement(input, &mut text, true) {
+		(DecoderResult::InputEmpty, _) => Ok(text),
+		// read counts the malformed sequence and the bytes after it already consumed
+		(DecoderResult::Malformed(len, consumed), read) => Err(format!(
+			"Invalid {} input at byte {}",
+			encoding.name(),
+			bom_len + read - len as usize - consumed as usize
+		)),
+		(DecoderResult::OutputFull, _) => Err("Input too large".to_string()),
+	}
+}
+
+fn read_input_bytes<R: Read + Terminal>(matches: &ArgMatches, stdin: R) -> Result<Vec<u8>, String> {
+	if let Some(input) = input_override() {
+		return Ok(input);
+	}
+	match (matches.value_of("FILE"), matches.value_of("INPUT")) {
+		(Some(path), _) => read_file(path, max_input_size(matches)?),
+		(None, Some(input)) => Ok(input.bytes().collect::<Vec<u8>>()),
+		(None, None) => {
+			check_stdin(matches, &stdin)?;
+			read_limited(stdin, max_input_size(matches)?)
+		}
+	}
+}
+
+/// Write a file through a temporary file renamed in place, so readers never see a partial file
+pub fn write_file_atomic(path: &str, content: &[u8], force: bool) -> io::Result<()> {
+	write_atomic(path, content, force, false)
+}
+
+/// `write_file_atomic` of a private key or another secret, only the owner can read the
+/// file (mode 0600 on unix)
+pub fn write_secret_file_atomic(path: &str, content: &[u8], force: bool) -> io::Result<()> {
+	write_atomic(path, content, force, true)
+}
+
+fn write_atomic(path: &str, content: &[u8], force: bool, secret: bool) -> io::Result<()> {
+	let target = Path::new(path);
+	if target.exists() && !force {
+		return Err(io::Error::new(
+			io::ErrorKind::AlreadyExists,
+			format!("File exists: {}, use --force to overwrite", path),
+		));
+	}
+
+	let file_name = target.file_name().ok_or_else(|| {
+		io::Error::new(
+			io::ErrorKind::InvalidInput,
+			format!("Invalid file path: {}", path),
+		)
+	})?;
+	let temp = target.with_file_name(format!(
+		".{}.{}.tmp",
+		file_name.to_string_lossy(),
+		std::process::id()
+	));
+
+	// The mode is set on creation, the secret is never readable by others, and
+	// create_new does not follow a link left in place of the temporary file
+	let _ = fs::remove_file(&temp);
+	let mut options = fs::OpenOptions::new();
+	options.write(true).create_new(true);
+	#[cfg(unix)]
+	if secret {
+		use std::os::unix::fs::OpenOptionsExt;
+		options.mode(0o600);
+	}
+	#[cfg(not(unix))]
+	let _ = secret;
+
+	options
+		.open(&temp)
+		.and_then(|mut file| file.write_all(content))
+		.and_then(|_| fs::rename(&temp, target))
+		.map_err(|e| {
+			let _ = fs::remove_file(&temp);
+			io::Error::new(e.kind(), format!("Failed to write file {}: {}", path, e))
+		})
+}
+
+/// Split a command line into args the way a shell does, respecting quotes and escapes
+pub fn split_args(line: &str) -> Result<Vec<String>, String> {
+	shlex::split(line).ok_or_else(|| "Invalid quoting".to_string())
+}
+
+/// The DER of the first PEM block of the label, e.g. CERTIFICATE
+pub fn pem_body(pem: &str, label: &str) -> Option<Vec<u8>> {
+	let begin = format!("-----BEGIN {}-----", label);
+	let end = format!("-----END {}-----", label);
+	let start = pem.find(&begin)? + begin.len();
+	let body = &pem[start..start + pem[start..].find(&end)?];
+	let body = body.split_whitespace().collect::<String>();
+
+	general_purpose::STANDARD.decode(body).ok()
+}
+
+/// With --dry-run, the summary of the output to return once the arguments are validated,
+/// in place of producing the output
+pub fn dry_run<F: FnOnce() -> String>(matches: &ArgMatches, summary: F) -> Option<Output> {
+	if !matches.is_present("dry_run") {
+		return None;
 	}
+	let summary = format!("OK: would produce {}", summary());
+	Some(Output::Lines(vec![summary]))
+}
+
+/// Peak resident set size of the process, only available on Linux
+pub fn peak_rss_kb() -> Option<u64> {
+	fs::read_to_string("/proc/self/status")
+		.ok()
+		.and_then(|status| parse_vm_hwm(&status))
+}
+
+fn parse_vm_hwm(status: &str) -> Option<u64> {
+	status
+		.lines()
+		.find_map(|x| x.strip_prefix("VmHWM:"))
+		.and_then(|x| x.trim().trim_end_matches("kB").trim().parse().ok())
+}
+
+fn read_file(path: &str, limit: u64) -> Result<Vec<u8>, String> {
+	let file = fs::File::open(path).map_err(|e| format!("Failed to read file {}: {}", path, e))?;
+	read_limited(file, limit)
+}
+
+thread_local! {
+	static EXIT_CODE: Cell<i32> = const { Cell::new(0) };
+	static INPUT_OVERRIDE: RefCell<Option<Vec<u8>>> = const { RefCell::new(None) };
+}
+
+/// Set the input returned by `input_string` and `input_bytes` in place of `INPUT`, `--file`
+/// and stdin, used by the dispatcher to run a command once per line with --each
+/// and to feed the stages of `pipe`
+pub fn set_input_override(input: Option<Vec<u8>>) {
+	INPUT_OVERRIDE.with(|x| *x.borrow_mut() = input);
+}
+
+fn input_override() -> Option<Vec<u8>> {
+	INPUT_OVERRIDE.with(|x| x.borrow().clone())
+}
+
+/// Set the process exit code used after the command output is printed
+pub fn set_exit_code(code: i32) {
+	EXIT_CODE.with(|x| x.set(code));
+}
+
+pub fn exit_code() -> i32 {
+	EXIT_CODE.with(|x| x.get())
 }
 
 pub struct Hex(Vec<u8>);
@@ -55,13 +425,14 @@ impl From<Hex> for Vec<u8> {
 
 #[cfg(test)]
 pub mod test {
-	use crate::modules::Module;
+	use crate::app;
+	use crate::modules::{JsonStyle, Module};
 
 	pub fn test_module(module: Module) {
 		let commands = module.commands;
 		let cases = (module.get_cases)();
 		for command in commands {
-			let app = &command.app;
+			let app = &command.app.clone().args(&app::global_args());
 			let cases = cases.get(app.get_name());
 
 			assert!(cases.is_some(), "{} should have cases", app.get_name());
@@ -85,7 +456,11 @@ pub mod test {
 						input.append(&mut ori_input);
 						let expected_output = Ok((&case.output).clone());
 						let matches = app.clone().get_matches_from(input.clone());
-						let output = f(&matches);
+						// As shown on a terminal, like the examples of the docs
+						let style = JsonStyle::new(&matches, true);
+						let output = f(&matches)
+							.map_err(String::from)
+							.and_then(|x| x.render_styled(matches.is_present("json"), style));
 						assert_eq!(output, expected_output, "Test: {}", input.join(" "));
 					}
 				}
@@ -93,3 +468,300 @@ pub mod test {
 		}
 	}
 }
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use clap::{App, SubCommand};
+	use std::io::Cursor;
+
+	fn app<'a, 'b>() -> App<'a, 'b> {
+		SubCommand::with_name("test")
+			.arg(Arg::with_name("INPUT").required(false).index(1))
+			.arg(file_arg())
+			.args(&crate::app::global_args())
+	}
+
+	impl<T> Terminal for Cursor<T> {
+		fn is_terminal(&self) -> bool {
+			false
+		}
+	}
+
+	/// stdin of a terminal
+	struct Tty(Cursor<&'static str>);
+
+	impl Read for Tty {
+		fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+			self.0.read(buf)
+		}
+	}
+
+	impl BufRead for Tty {
+		fn fill_buf(&mut self) -> io::Result<&[u8]> {
+			self.0.fill_buf()
+		}
+
+		fn consume(&mut self, amt: usize) {
+			self.0.consume(amt)
+		}
+	}
+
+	impl Terminal for Tty {
+		fn is_terminal(&self) -> bool {
+			true
+		}
+	}
+
+	fn temp_file(name: &str, content: &[u8]) -> String {
+		let path = std::env::temp_dir().join(format!("dtool_base_{}_{}", std::process::id(), name));
+		fs::write(&path, content).unwrap();
+		path.to_string_lossy().to_string()
+	}
+
+	#[test]
+	fn test_input_positional() {
+		let matches = app().get_matches_from(vec!["test", "abc"]);
+		assert_eq!(
+			read_input_string(&matches, Cursor::new("stdin")),
+			Ok("abc".to_string())
+		);
+		assert_eq!(
+			read_input_bytes(&matches, Cursor::new("stdin")),
+			Ok(b"abc".to_vec())
+		);
+	}
+
+	#[test]
+	fn test_input_file() {
+		let path = temp_file("input_file", b"line1\nline2\n");
+		let matches = app().get_matches_from(vec!["test", "-f", path.as_str()]);
+		assert_eq!(
+			read_input_string(&matches, Cursor::new("stdin")),
+			Ok("line1\nline2".to_string())
+		);
+		assert_eq!(
+			read_input_bytes(&matches, Cursor::new("stdin")),
+			Ok(b"line1\nline2\n".to_vec())
+		);
+		fs::remove_file(&path).unwrap();
+	}
+
+	#[test]
+	fn test_input_stdin() {
+		let matches = app().get_matches_from(vec!["test"]);
+		assert_eq!(
+			read_input_string(&matches, Cursor::new("line1\nline2\n")),
+			Ok("line1\nline2".to_string())
+		);
+		assert_eq!(
+			read_input_bytes(&matches, Cursor::new("stdin")),
+			Ok(b"stdin".to_vec())
+		);
+	}
+
+	#[test]
+	fn test_input_stdin_terminal() {
+		// Not waited for with INPUT, or when stdin is not a terminal
+		let matches = app().get_matches_from(vec!["test", "--no-stdin-wait", "abc"]);
+		assert_eq!(
+			read_input_string(&matches, Tty(Cursor::new("stdin"))),
+			Ok("abc".to_string())
+		);
+		let matches = app().get_matches_from(vec!["test", "--no-stdin-wait"]);
+		assert_eq!(
+			read_input_string(&matches, Cursor::new("stdin")),
+			Ok("stdin".to_string())
+		);
+
+		let error = read_input_string(&matches, Tty(Cursor::new("stdin"))).unwrap_err();
+		assert!(error.starts_with("No INPUT given and stdin is a terminal\n\nUSAGE:"));
+		assert!(read_input_bytes(&matches, Tty(Cursor::new("stdin"))).is_err());
+
+		// Read after the hint without --no-stdin-wait
+		let matches = app().get_matches_from(vec!["test"]);
+		assert_eq!(
+			read_input_bytes(&matches, Tty(Cursor::new("stdin"))),
+			Ok(b"stdin".to_vec())
+		);
+	}
+
+	#[test]
+	fn test_input_file_conflicts_with_positional() {
+		let result = app().get_matches_from_safe(vec!["test", "--file", "input.txt", "abc"]);
+		assert!(result.is_err());
+	}
+
+	#[test]
+	fn test_input_file_errors() {
+		let matches = app().get_matches_from(vec!["test", "--file", "/nonexistent/dtool_input"]);
+		let result = read_input_string(&matches, Cursor::new("stdin"));
+		assert!(result
+			.unwrap_err()
+			.starts_with("Failed to read file /nonexistent/dtool_input"));
+
+		let path = temp_file("invalid_utf8", &[0x61, 0xff, 0xfe]);
+		let matches = app().get_matches_from(vec!["test", "--file", path.as_str()]);
+		assert_eq!(
+			read_input_string(&matches, Cursor::new("stdin")),
+			Err(format!("Invalid UTF-8 input at byte 1 in file: {}", path))
+		);
+		assert_eq!(
+			read_input_bytes(&matches, Cursor::new("stdin")),
+			Ok(vec![0x61, 0xff, 0xfe])
+		);
+		fs::remove_file(&path).unwrap();
+	}
+
+	#[test]
+	fn test_input_size_limit() {
+		let error = "Input exceeds maximum size of 5 bytes, use --max-input-size";
+		let matches = app().get_matches_from(vec!["test", "--max-input-size", "5"]);
+		assert_eq!(
+			read_input_bytes(&matches, Cursor::new("12345")),
+			Ok(b"12345".to_vec())
+		);
+		assert_eq!(
+			read_input_bytes(&matches, Cursor::new("123456")),
+			Err(error.to_string())
+		);
+		assert_eq!(
+			read_input_string(&matches, Cursor::new("1234\n")),
+			Ok("1234".to_string())
+		);
+		assert_eq!(
+			read_input_string(&matches, Cursor::new("1234\n\n")),
+			Err(error.to_string())
+		);
+
+		// INPUT is not limited, --file is
+		let matches = app().get_matches_from(vec!["test", "--max-input-size", "5", "123456"]);
+		assert_eq!(
+			read_input_bytes(&matches, Cursor::new("")),
+			Ok(b"123456".to_vec())
+		);
+		let path = temp_file("size_limit", b"123456");
+		let matches =
+			app().get_matches_from(vec!["test", "--max-input-bytes", "5", "--file", &path]);
+		assert_eq!(
+			read_input_bytes(&matches, Cursor::new("")),
+			Err(error.to_string())
+		);
+		assert_eq!(
+			read_input_string(&matches, Cursor::new("")),
+			Err(error.to_string())
+		);
+		fs::remove_file(&path).unwrap();
+
+		// An endless stream stops at the limit rather than filling the memory
+		assert_eq!(
+			read_limited(io::repeat(b'a'), 1024),
+			Err("Input exceeds maximum size of 1024 bytes, use --max-input-size".to_string())
+		);
+
+		// The stream is not limited either
+		let matches = app().get_matches_from(vec!["test", "--max-input-size", "5"]);
+		let mut input = vec![];
+		open_input(&matches, Cursor::new("123456"))
+			.unwrap()
+			.read_to_end(&mut input)
+			.unwrap();
+		assert_eq!(input, b"123456".to_vec());
+	}
+
+	#[test]
+	fn test_max_input_size() {
+		let size = |x: &str| {
+			let matches = app().get_matches_from(vec!["test", "--max-input-size", x]);
+			max_input_size(&matches)
+		};
+		assert_eq!(size("1024"), Ok(1024));
+		assert_eq!(size("512K"), Ok(512 * 1024));
+		assert_eq!(size("16m"), Ok(DEFAULT_MAX_INPUT_SIZE));
+		assert_eq!(size("2G"), Ok(2 << 30));
+		for x in ["", "M", "1T", "1.5K", "99999999999G"] {
+			assert_eq!(size(x), Err(format!("Invalid max input size: {}", x)));
+		}
+		assert_eq!(
+			max_input_size(&app().get_matches_from(vec!["test"])),
+			Ok(DEFAULT_MAX_INPUT_SIZE)
+		);
+	}
+
+	#[test]
+	fn test_input_encoding() {
+		// UTF-16LE with a BOM, detected by default
+		let utf16 = [
+			0xff, 0xfe, 0x63, 0x00, 0x61, 0x00, 0x66, 0x00, 0xe9, 0x00, 0x0a, 0x00,
+		];
+		let path = temp_file("utf16", &utf16);
+		let matches = app().get_matches_from(vec!["test", "-f", path.as_str()]);
+		assert_eq!(
+			read_input_string(&matches, Cursor::new("stdin")),
+			Ok("café".to_string())
+		);
+		assert_eq!(
+			read_input_bytes(&matches, Cursor::new("stdin")),
+			Ok(utf16.to_vec())
+		);
+		fs::remove_file(&path).unwrap();
+
+		let matches = app().get_matches_from(vec!["test", "--encoding", "latin1"]);
+		assert_eq!(
+			read_input_string(&matches, Cursor::new(&b"caf\xe9\r\n"[..])),
+			Ok("café".to_string())
+		);
+		let matches = app().get_matches_from(vec!["test", "--encoding", "shift_jis"]);
+		assert_eq!(
+			read_input_string(&matches, Cursor::new(&b"\x82\xa0"[..])),
+			Ok("あ".to_string())
+		);
+
+		// Lone surrogate, BOM only, mislabeled and unknown encodings
+		assert_eq!(
+			decode_text(&[0xff, 0xfe, 0x61, 0x00, 0x00, 0xd8, 0x62, 0x00], None),
+			Err("Invalid UTF-16LE input at byte 4".to_string())
+		);
+		assert_eq!(
+			decode_text(&[0xfe, 0xff], None),
+			Err("Invalid UTF-16BE input: no text after the BOM at byte 2".to_string())
+		);
+		assert_eq!(
+			decode_text(b"caf\xe9", Some("utf-8")),
+			Err("Invalid UTF-8 input at byte 3".to_string())
+		);
+		assert_eq!(
+			decode_text(b"abc", Some("utf-42")),
+			Err("Unknown encoding: utf-42".to_string())
+		);
+	}
+
+	#[test]
+	fn test_parse_vm_hwm() {
+		let status =
+			"Name:\tdtool\nVmPeak:\t   10240 kB\nVmHWM:\t    5120 kB\nVmRSS:\t    4096 kB\n";
+		assert_eq!(parse_vm_hwm(status), Some(5120));
+		assert_eq!(parse_vm_hwm("Name:\tdtool\n"), None);
+	}
+
+	#[test]
+	fn test_split_args() {
+		assert_eq!(
+			split_args(r#"jwt_encode -s "my secret" '{"sub": "1"}'"#),
+			Ok(vec![
+				"jwt_encode".to_string(),
+				"-s".to_string(),
+				"my secret".to_string(),
+				r#"{"sub": "1"}"#.to_string(),
+			])
+		);
+		assert_eq!(
+			split_args(r"s2h a\ b"),
+			Ok(vec!["s2h".to_string(), "a b".to_string()])
+		);
+		assert_eq!(
+			split_args(r#"s2h "abc"#),
+			Err("Invalid quoting".to_string())
+		);
+	}
+}
@@ -1,5 +1,5 @@
 use crate::modules::base::Hex;
-use crate::modules::{base, Command, Module};
+use crate::modules::{base, Command, Error, Module, Output};
 use clap::{Arg, ArgMatches, SubCommand};
 use data_encoding::BASE32;
 
@@ -16,29 +16,35 @@ pub fn commands<'a, 'b>() -> Vec<Command<'a, 'b>> {
 		Command {
 			app: SubCommand::with_name("h2b32")
 				.about("Convert hex to base32")
-				.arg(Arg::with_name("INPUT").required(false).index(1)),
+				.arg(Arg::with_name("INPUT").required(false).index(1))
+				.arg(base::file_arg()),
 			f: h2b32,
+			serial: false,
+			pure: true,
 		},
 		Command {
 			app: SubCommand::with_name("b322h")
 				.about("Convert base32 to hex")
-				.arg(Arg::with_name("INPUT").required(false).index(1)),
+				.arg(Arg::with_name("INPUT").required(false).index(1))
+				.arg(base::file_arg()),
 			f: b322h,
+			serial: false,
+			pure: true,
 		},
 	]
 }
 
-fn h2b32(matches: &ArgMatches) -> Result<Vec<String>, String> {
+fn h2b32(matches: &ArgMatches) -> Result<Output, Error> {
 	let input = base::input_string(matches)?;
 
 	let input: Vec<u8> = input.parse::<Hex>().map_err(|_| "Convert failed")?.into();
 
 	let result = BASE32.encode(&input);
 
-	Ok(vec![result])
+	Ok(Output::Lines(vec![result]))
 }
 
-fn b322h(matches: &ArgMatches) -> Result<Vec<String>, String> {
+fn b322h(matches: &ArgMatches) -> Result<Output, Error> {
 	let input = base::input_string(matches)?;
 
 	let result = BASE32
@@ -46,7 +52,7 @@ fn b322h(matches: &ArgMatches) -> Result<Vec<String>, String> {
 		.map_err(|_| "Convert failed")?;
 	let result = Hex::from(result).into();
 
-	Ok(vec![result])
+	Ok(Output::Lines(vec![result]))
 }
 
 mod cases {
@@ -1,5 +1,5 @@
 use crate::modules::base::Hex;
-use crate::modules::{base, Command, Module};
+use crate::modules::{base, Command, Error, Module, Output};
 use clap::{Arg, ArgMatches, SubCommand};
 
 pub fn module<'a, 'b>() -> Module<'a, 'b> {
@@ -15,51 +15,63 @@ pub fn commands<'a, 'b>() -> Vec<Command<'a, 'b>> {
 		Command {
 			app: SubCommand::with_name("h2b58")
 				.about("Convert hex to base58")
-				.arg(Arg::with_name("INPUT").required(false).index(1)),
+				.arg(Arg::with_name("INPUT").required(false).index(1))
+				.arg(base::file_arg()),
 			f: h2b58,
+			serial: false,
+			pure: true,
 		},
 		Command {
 			app: SubCommand::with_name("h2b58c")
 				.about("Convert hex to base58 check")
-				.arg(Arg::with_name("INPUT").required(false).index(1)),
+				.arg(Arg::with_name("INPUT").required(false).index(1))
+				.arg(base::file_arg()),
 			f: h2b58c,
+			serial: false,
+			pure: true,
 		},
 		Command {
 			app: SubCommand::with_name("b582h")
 				.about("Convert base58 to hex")
-				.arg(Arg::with_name("INPUT").required(false).index(1)),
+				.arg(Arg::with_name("INPUT").required(false).index(1))
+				.arg(base::file_arg()),
 			f: b582h,
+			serial: false,
+			pure: true,
 		},
 		Command {
 			app: SubCommand::with_name("b58c2h")
 				.about("Convert base58 check to hex")
-				.arg(Arg::with_name("INPUT").required(false).index(1)),
+				.arg(Arg::with_name("INPUT").required(false).index(1))
+				.arg(base::file_arg()),
 			f: b58c2h,
+			serial: false,
+			pure: true,
 		},
 	]
 }
 
-fn h2b58(matches: &ArgMatches) -> Result<Vec<String>, String> {
+fn h2b58(matches: &ArgMatches) -> Result<Output, Error> {
 	let input = base::input_string(matches)?;
 
 	let input: Vec<u8> = input.parse::<Hex>().map_err(|_| "Convert failed")?.into();
 
 	let result = bs58::encode(input).into_string();
 
-	Ok(vec![result])
+	Ok(Output::Lines(vec![result]))
 }
 
-fn h2b58c(matches: &ArgMatches) -> Result<Vec<String>, String> {
+fn h2b58c(matches: &ArgMatches) -> Result<Output, Error> {
 	let input = base::input_string(matches)?;
 
 	let input: Vec<u8> = input.parse::<Hex>().map_err(|_| "Convert failed")?.into();
 
 	let result = bs58::encode(input).with_check().into_string();
 
-	Ok(vec![result])
+	Ok(Output::Lines(vec![result]))
 }
 
-fn b582h(matches: &ArgMatches) -> Result<Vec<String>, String> {
+fn b582h(matches: &ArgMatches) -> Result<Output, Error> {
 	let input = base::input_string(matches)?;
 
 	let input = bs58::decode(&input)
@@ -67,10 +79,10 @@ fn b582h(matches: &ArgMatches) -> Result<Vec<String>, String> {
 		.map_err(|_| "Convert failed")?;
 	let result = Hex::from(input).into();
 
-	Ok(vec![result])
+	Ok(Output::Lines(vec![result]))
 }
 
-fn b58c2h(matches: &ArgMatches) -> Result<Vec<String>, String> {
+fn b58c2h(matches: &ArgMatches) -> Result<Output, Error> {
 	let input = base::input_string(matches)?;
 
 	let input = bs58::decode(&input)
@@ -79,7 +91,7 @@ fn b58c2h(matches: &ArgMatches) -> Result<Vec<String>, String> {
 		.map_err(|_| "Convert failed")?;
 	let result = Hex::from(input).into();
 
-	Ok(vec![result])
+	Ok(Output::Lines(vec![result]))
 }
 
 mod cases {
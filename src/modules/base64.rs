@@ -1,5 +1,5 @@
 use crate::modules::base::Hex;
-use crate::modules::{base, Command, Module};
+use crate::modules::{base, Command, Error, Module, Output};
 use clap::{Arg, ArgMatches, SubCommand};
 
 pub fn module<'a, 'b>() -> Module<'a, 'b> {
@@ -15,31 +15,37 @@ pub fn commands<'a, 'b>() -> Vec<Command<'a, 'b>> {
 		Command {
 			app: SubCommand::with_name("h2b64")
 				.about("Convert hex to base64")
-				.arg(Arg::with_name("INPUT").required(false).index(1)),
+				.arg(Arg::with_name("INPUT").required(false).index(1))
+				.arg(base::file_arg()),
 			f: h2b64,
+			serial: false,
+			pure: true,
 		},
 		Command {
 			app: SubCommand::with_name("b642h")
 				.about("Convert base64 to hex")
-				.arg(Arg::with_name("INPUT").required(false).index(1)),
+				.arg(Arg::with_name("INPUT").required(false).index(1))
+				.arg(base::file_arg()),
 			f: b642h,
+			serial: false,
+			pure: true,
 		},
 	]
 }
 
 use base64::{engine::general_purpose, Engine as _};
 
-fn h2b64(matches: &ArgMatches) -> Result<Vec<String>, String> {
+fn h2b64(matches: &ArgMatches) -> Result<Output, Error> {
 	let input = base::input_string(matches)?;
 
 	let input: Vec<u8> = input.parse::<Hex>().map_err(|_| "Convert failed")?.into();
 
 	let result = general_purpose::STANDARD.encode(&input);
 
-	Ok(vec![result])
+	Ok(Output::Lines(vec![result]))
 }
 
-fn b642h(matches: &ArgMatches) -> Result<Vec<String>, String> {
+fn b642h(matches: &ArgMatches) -> Result<Output, Error> {
 	let input = base::input_string(matches)?;
 
 	let result = general_purpose::STANDARD
@@ -47,7 +53,7 @@ fn b642h(matches: &ArgMatches) -> Result<Vec<String>, String> {
 		.map_err(|_| "Convert failed")?;
 	let result = Hex::from(result).into();
 
-	Ok(vec![result])
+	Ok(Output::Lines(vec![result]))
 }
 
 mod cases {
@@ -0,0 +1,392 @@
+use crate::modules::base::Hex;
+use crate::modules::{base, exit_code, Command, Error, Module, Output};
+use bip39::{Language, Mnemonic};
+use clap::{Arg, ArgMatches, SubCommand};
+use rand::rngs::OsRng;
+use rand::RngCore;
+
+pub fn module<'a, 'b>() -> Module<'a, 'b> {
+	Module {
+		desc: "BIP39 mnemonic".to_string(),
+		commands: commands(),
+		get_cases: cases::cases,
+	}
+}
+
+/// Word lists of --language
+const LANGUAGES: [(&str, Language); 10] = [
+	("english", Language::English),
+	("chinese-simplified", Language::SimplifiedChinese),
+	("chinese-traditional", Language::TraditionalChinese),
+	("czech", Language::Czech),
+	("french", Language::French),
+	("italian", Language::Italian),
+	("japanese", Language::Japanese),
+	("korean", Language::Korean),
+	("portuguese", Language::Portuguese),
+	("spanish", Language::Spanish),
+];
+
+/// Word counts of a mnemonic, 3 words for each 32 bits of entropy
+const WORD_COUNTS: [&str; 5] = ["12", "15", "18", "21", "24"];
+
+pub fn commands<'a, 'b>() -> Vec<Command<'a, 'b>> {
+	vec![
+		Command {
+			app: SubCommand::with_name("bip39_gen")
+				.about("Generate a BIP39 mnemonic")
+				.arg(
+					Arg::with_name("WORDS")
+						.long("words")
+						.short("w")
+						.help("Word count")
+						.takes_value(true)
+						.possible_values(&WORD_COUNTS)
+						.default_value("12"),
+				)
+				.arg(
+					Arg::with_name("ENTROPY_HEX")
+						.long("entropy-hex")
+						.help("Entropy (Hex) rather than random, 16 to 32 bytes by 4, which sets the word count")
+						.takes_value(true),
+				)
+				.arg(language_arg()),
+			f: bip39_gen,
+			serial: false,
+			pure: true,
+		},
+		Command {
+			app: SubCommand::with_name("bip39_validate")
+				.about("Validate a BIP39 mnemonic (exit code: 0 valid, 4 invalid)")
+				.arg(Arg::with_name("INPUT").required(false).index(1))
+				.arg(base::file_arg())
+				.arg(language_arg()),
+			f: bip39_validate,
+			serial: false,
+			pure: true,
+		},
+		Command {
+			app: SubCommand::with_name("bip39_seed")
+				.about("BIP39 seed of a mnemonic (PBKDF2, 64 bytes)")
+				.arg(Arg::with_name("INPUT").required(false).index(1))
+				.arg(base::file_arg())
+				.arg(
+					Arg::with_name("PASSPHRASE")
+						.long("passphrase")
+						.short("p")
+						.help("Passphrase, the \"25th word\"")
+						.takes_value(true)
+						.default_value(""),
+				)
+				.arg(language_arg()),
+			f: bip39_seed,
+			serial: false,
+			pure: true,
+		},
+	]
+}
+
+fn language_arg<'a, 'b>() -> Arg<'a, 'b> {
+	Arg::with_name("LANGUAGE")
+		.long("language")
+		.short("l")
+		.help("Word list")
+		.takes_value(true)
+		.possible_values(&LANGUAGES.iter().map(|x| x.0).collect::<Vec<_>>())
+		.default_value("english")
+}
+
+fn language(matches: &ArgMatches) -> Language {
+	let name = matches.value_of("LANGUAGE").unwrap();
+	LANGUAGES
+		.iter()
+		.find(|x| x.0 == name)
+		.map(|x| x.1)
+		.unwrap_or(Language::English)
+}
+
+fn bip39_gen(matches: &ArgMatches) -> Result<Output, Error> {
+	let entropy: Vec<u8> = match matches.value_of("ENTROPY_HEX") {
+		Some(entropy) => entropy
+			.trim()
+			.parse::<Hex>()
+			.map_err(|_| Error::Usage("Invalid entropy (should be Hex)".to_string()))?
+			.into(),
+		None => {
+			let words = matches.value_of("WORDS").unwrap().parse::<usize>().unwrap();
+			let mut entropy = vec![0u8; words / 3 * 4];
+			OsRng.fill_bytes(&mut entropy);
+			entropy
+		}
+	};
+
+	let mnemonic = Mnemonic::from_entropy_in(language(matches), &entropy).map_err(|_| {
+		Error::Usage(format!(
+			"Invalid entropy (should be 16, 20, 24, 28 or 32 bytes, got {})",
+			entropy.len()
+		))
+	})?;
+
+	Ok(Output::Lines(vec![mnemonic.to_string()]))
+}
+
+fn bip39_validate(matches: &ArgMatches) -> Result<Output, Error> {
+	let phrase = normalize(&base::input_string(matches)?);
+
+	match Mnemonic::parse_in(language(matches), phrase.as_str()) {
+		Ok(_) => {
+			base::set_exit_code(0);
+			Ok(Output::Lines(vec!["Valid: true".to_string()]))
+		}
+		Err(e) => {
+			base::set_exit_code(exit_code::INVALID);
+			Ok(Output::Lines(vec![
+				"Valid: false".to_string(),
+				format!("Error: {}", describe(&e, &phrase)),
+			]))
+		}
+	}
+}
+
+fn bip39_seed(matches: &ArgMatches) -> Result<Output, Error> {
+	let phrase = normalize(&base::input_string(matches)?);
+
+	let mnemonic = Mnemonic::parse_in(language(matches), phrase.as_str()).map_err(|e| {
+		Error::invalid_input(format!("Invalid mnemonic: {}", describe(&e, &phrase)))
+			.with_hint("see which word is wrong with bip39_validate")
+	})?;
+	let seed = mnemonic.to_seed(matches.value_of("PASSPHRASE").unwrap());
+
+	Ok(Output::Lines(vec![hex::encode(seed)]))
+}
+
+/// Lowercase words separated by single spaces, the NFKD is left to `Mnemonic::parse_in`
+fn normalize(phrase: &str) -> String {
+	phrase
+		.split_whitespace()
+		.map(str::to_lowercase)
+		.collect::<Vec<_>>()
+		.join(" ")
+}
+
+fn describe(e: &bip39::Error, phrase: &str) -> String {
+	match e {
+		bip39::Error::BadWordCount(count) => {
+			format!("{} words, should be 12, 15, 18, 21 or 24", count)
+		}
+		bip39::Error::UnknownWord(i) => format!(
+			"unknown word #{}: {}",
+			i + 1,
+			phrase.split_whitespace().nth(*i).unwrap_or_default()
+		),
+		bip39::Error::InvalidChecksum => {
+			"checksum mismatch, a word is wrong or the words are out of order".to_string()
+		}
+		e => e.to_string(),
+	}
+}
+
+mod cases {
+	use crate::modules::Case;
+	use linked_hash_map::LinkedHashMap;
+
+	pub fn cases() -> LinkedHashMap<&'static str, Vec<Case>> {
+		vec![
+			(
+				"bip39_gen",
+				vec![
+					Case {
+						desc: "BIP39 test vector, 128 bits of entropy".to_string(),
+						input: vec!["--entropy-hex", "7f7f7f7f7f7f7f7f7f7f7f7f7f7f7f7f"]
+							.into_iter()
+							.map(Into::into)
+							.collect(),
+						output: vec!["legal winner thank year wave sausage worth useful legal winner thank yellow"]
+							.into_iter()
+							.map(Into::into)
+							.collect(),
+						is_example: true,
+						is_test: true,
+						since: "0.17.0".to_string(),
+					},
+					Case {
+						desc: "BIP39 test vector, 256 bits of entropy".to_string(),
+						input: vec!["--entropy-hex", "68a79eaca2324873eacc50cb9c6eca8cc68ea5d936f98787c60c7ebc74e6ce7c"]
+							.into_iter()
+							.map(Into::into)
+							.collect(),
+						output: vec!["hamster diagram private dutch cause delay private meat slide toddler razor book happy fancy gospel tennis maple dilemma loan word shrug inflict delay length"]
+							.into_iter()
+							.map(Into::into)
+							.collect(),
+						is_example: false,
+						is_test: true,
+						since: "0.17.0".to_string(),
+					},
+					Case {
+						desc: "24 random words".to_string(),
+						input: vec!["--words", "24"].into_iter().map(Into::into).collect(),
+						output: vec!["void come effort suffer camp survey warrior heavy shoot primary clutch crush open amazing screen patrol group space point ten exist slush involve unfold"]
+							.into_iter()
+							.map(Into::into)
+							.collect(),
+						is_example: true,
+						is_test: false, // random words
+						since: "0.17.0".to_string(),
+					},
+				],
+			),
+			(
+				"bip39_validate",
+				vec![
+					Case {
+						desc: "A valid mnemonic".to_string(),
+						input: vec!["'letter advice cage absurd amount doctor acoustic avoid letter advice cage above'"]
+							.into_iter()
+							.map(Into::into)
+							.collect(),
+						output: vec!["Valid: true"].into_iter().map(Into::into).collect(),
+						is_example: true,
+						is_test: true,
+						since: "0.17.0".to_string(),
+					},
+					Case {
+						desc: "Words are normalized: lowercase, single spaces".to_string(),
+						input: vec!["'  Letter ADVICE cage absurd amount doctor acoustic avoid letter advice cage  above '"]
+							.into_iter()
+							.map(Into::into)
+							.collect(),
+						output: vec!["Valid: true"].into_iter().map(Into::into).collect(),
+						is_example: false,
+						is_test: true,
+						since: "0.17.0".to_string(),
+					},
+					Case {
+						desc: "A mistyped word".to_string(),
+						input: vec!["'letter advice cage absurd amount doctor acoustic avoid letter advise cage above'"]
+							.into_iter()
+							.map(Into::into)
+							.collect(),
+						output: vec!["Valid: false", "Error: unknown word #10: advise"]
+							.into_iter()
+							.map(Into::into)
+							.collect(),
+						is_example: true,
+						is_test: true,
+						since: "0.17.0".to_string(),
+					},
+					Case {
+						desc: "Words out of order".to_string(),
+						input: vec!["'advice letter cage absurd amount doctor acoustic avoid letter advice cage above'"]
+							.into_iter()
+							.map(Into::into)
+							.collect(),
+						output: vec![
+							"Valid: false",
+							"Error: checksum mismatch, a word is wrong or the words are out of order",
+						]
+						.into_iter()
+						.map(Into::into)
+						.collect(),
+						is_example: false,
+						is_test: true,
+						since: "0.17.0".to_string(),
+					},
+					Case {
+						desc: "A missing word".to_string(),
+						input: vec!["'letter advice cage absurd amount doctor acoustic avoid letter advice cage'"]
+							.into_iter()
+							.map(Into::into)
+							.collect(),
+						output: vec!["Valid: false", "Error: 11 words, should be 12, 15, 18, 21 or 24"]
+							.into_iter()
+							.map(Into::into)
+							.collect(),
+						is_example: false,
+						is_test: true,
+						since: "0.17.0".to_string(),
+					},
+				],
+			),
+			(
+				"bip39_seed",
+				vec![
+					Case {
+						desc: "BIP39 test vector, passphrase TREZOR".to_string(),
+						input: vec!["--passphrase", "TREZOR", "'abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about'"]
+							.into_iter()
+							.map(Into::into)
+							.collect(),
+						output: vec!["c55257c360c07c72029aebc1b53c05ed0362ada38ead3e3e9efa3708e53495531f09a6987599d18264c1e1c92f2cf141630c7a3c4ab7c81b2f001698e7463b04"]
+							.into_iter()
+							.map(Into::into)
+							.collect(),
+						is_example: true,
+						is_test: true,
+						since: "0.17.0".to_string(),
+					},
+					Case {
+						desc: "BIP39 test vector of 24 words, passphrase TREZOR".to_string(),
+						input: vec!["-p", "TREZOR", "'void come effort suffer camp survey warrior heavy shoot primary clutch crush open amazing screen patrol group space point ten exist slush involve unfold'"]
+							.into_iter()
+							.map(Into::into)
+							.collect(),
+						output: vec!["01f5bced59dec48e362f2c45b5de68b9fd6c92c6634f44d6d40aab69056506f0e35524a518034ddc1192e1dacd32c1ed3eaa3c3b131c88ed8e7e54c49a5d0998"]
+							.into_iter()
+							.map(Into::into)
+							.collect(),
+						is_example: false,
+						is_test: true,
+						since: "0.17.0".to_string(),
+					},
+				],
+			),
+		]
+		.into_iter()
+		.collect()
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::modules::base::test::test_module;
+
+	#[test]
+	fn test_cases() {
+		test_module(module());
+	}
+
+	#[test]
+	fn test_bip39_exit_code() {
+		let validate = |phrase: &str| {
+			let app = &commands()[1].app;
+			let matches = app.clone().get_matches_from(vec!["bip39_validate", phrase]);
+			bip39_validate(&matches).unwrap();
+			base::exit_code()
+		};
+		assert_eq!(
+			validate("zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo wrong"),
+			0
+		);
+		assert_eq!(
+			validate("zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo"),
+			exit_code::INVALID
+		);
+	}
+
+	#[test]
+	fn test_bip39_seed_invalid() {
+		let app = &commands()[2].app;
+		let matches = app
+			.clone()
+			.get_matches_from(vec!["bip39_seed", "zoo zoo zoo"]);
+		assert_eq!(
+			bip39_seed(&matches),
+			Err(Error::invalid_input(
+				"Invalid mnemonic: 3 words, should be 12, 15, 18, 21 or 24".to_string()
+			)
+			.with_hint("see which word is wrong with bip39_validate"))
+		);
+	}
+}
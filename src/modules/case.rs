@@ -1,4 +1,4 @@
-use crate::modules::{base, Command, Module};
+use crate::modules::{base, Command, Error, Module, Output};
 use clap::{Arg, ArgMatches, SubCommand};
 use heck::{CamelCase, KebabCase, MixedCase, ShoutySnakeCase, SnakeCase, TitleCase};
 
@@ -27,12 +27,15 @@ pub fn commands<'a, 'b>() -> Vec<Command<'a, 'b>> {
 					.takes_value(true)
 					.required(false),
 			)
-			.arg(Arg::with_name("INPUT").required(false).index(1)),
+			.arg(Arg::with_name("INPUT").required(false).index(1))
+			.arg(base::file_arg()),
 		f: case,
+		serial: false,
+		pure: true,
 	}]
 }
 
-fn case(matches: &ArgMatches) -> Result<Vec<String>, String> {
+fn case(matches: &ArgMatches) -> Result<Output, Error> {
 	let input = base::input_string(matches)?;
 
 	let t = matches.value_of("TYPE").unwrap_or_default();
@@ -63,7 +66,7 @@ fn case(matches: &ArgMatches) -> Result<Vec<String>, String> {
 		}
 	};
 
-	Ok(vec![result])
+	Ok(Output::Lines(vec![result]))
 }
 
 fn to_sarcasm_case(input: &str) -> String {
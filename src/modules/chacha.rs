@@ -0,0 +1,594 @@
+use crate::modules::aes::{
+	aad, authentication_failed, cipher_input, cipher_output, kdf_arg, key_args, nonce, nonce_arg,
+	opening_key, sealing_key,
+};
+use crate::modules::{base, Command, Error, Module, Output};
+use chacha20poly1305::aead::{Aead, KeyInit, Payload};
+use chacha20poly1305::{ChaCha20Poly1305, XChaCha20Poly1305};
+use clap::{Arg, ArgMatches, SubCommand};
+use crypto_secretbox::XSalsa20Poly1305;
+
+const KEY_LEN: usize = 32;
+const NONCE_LEN: usize = 12;
+const X_NONCE_LEN: usize = 24;
+const TAG_LEN: usize = 16;
+
+pub fn module<'a, 'b>() -> Module<'a, 'b> {
+	Module {
+		desc: "ChaCha20-Poly1305 encrypt / decrypt".to_string(),
+		commands: commands(),
+		get_cases: cases::cases,
+	}
+}
+
+pub fn commands<'a, 'b>() -> Vec<Command<'a, 'b>> {
+	vec![
+		Command {
+			app: SubCommand::with_name("chacha_encrypt")
+				.about("ChaCha20-Poly1305 encrypt, the random nonce is prefixed to the ciphertext")
+				.arg(
+					Arg::with_name("INPUT")
+						.help("Plain")
+						.required(false)
+						.index(1),
+				)
+				.arg(base::file_arg())
+				.args(&key_args())
+				.args(&variant_args())
+				.arg(kdf_arg())
+				.arg(nonce_arg()),
+			f: chacha_encrypt,
+			serial: false,
+			pure: true,
+		},
+		Command {
+			app: SubCommand::with_name("chacha_decrypt")
+				.about("ChaCha20-Poly1305 decrypt (exit code: 4 authentication failed)")
+				.arg(
+					Arg::with_name("INPUT")
+						.help("Cipher (Base64) of chacha_encrypt")
+						.required(false)
+						.index(1),
+				)
+				.arg(base::file_arg())
+				.args(&key_args())
+				.args(&variant_args()),
+			f: chacha_decrypt,
+			serial: false,
+			pure: true,
+		},
+	]
+}
+
+fn variant_args<'a, 'b>() -> Vec<Arg<'a, 'b>> {
+	vec![
+		Arg::with_name("X")
+			.long("x")
+			.help("XChaCha20-Poly1305, with a 24-byte nonce"),
+		Arg::with_name("SODIUM")
+			.long("sodium")
+			.help("XSalsa20-Poly1305 of libsodium crypto_secretbox_easy: nonce | tag | ciphertext, no associated data")
+			.conflicts_with_all(&["X", "AAD", "AAD_HEX"]),
+	]
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Variant {
+	/// RFC 8439, 12-byte nonce
+	Ietf,
+	/// 24-byte nonce
+	X,
+	/// crypto_secretbox of libsodium, 24-byte nonce, the tag before the ciphertext
+	Sodium,
+}
+
+impl Variant {
+	fn of(matches: &ArgMatches) -> Self {
+		match (matches.is_present("X"), matches.is_present("SODIUM")) {
+			(true, _) => Variant::X,
+			(_, true) => Variant::Sodium,
+			_ => Variant::Ietf,
+		}
+	}
+
+	fn nonce_len(self) -> usize {
+		match self {
+			Variant::Ietf => NONCE_LEN,
+			Variant::X | Variant::Sodium => X_NONCE_LEN,
+		}
+	}
+}
+
+/// Output: nonce | ciphertext | tag (nonce | tag | ciphertext with --sodium), after
+/// kdf | salt with --key-from-password
+fn chacha_encrypt(matches: &ArgMatches) -> Result<Output, Error> {
+	let input = base::input_bytes(matches)?;
+	let variant = Variant::of(matches);
+	let nonce = match variant {
+		Variant::Ietf => nonce::<NONCE_LEN>(matches)?.to_vec(),
+		Variant::X | Variant::Sodium => nonce::<X_NONCE_LEN>(matches)?.to_vec(),
+	};
+	let (header, key) = sealing_key(matches)?;
+	let result = header
+		.into_iter()
+		.chain(seal(variant, &key, &nonce, &aad(matches)?, &input)?)
+		.collect();
+
+	Ok(cipher_output(matches, result))
+}
+
+fn chacha_decrypt(matches: &ArgMatches) -> Result<Output, Error> {
+	let input = cipher_input(matches)?;
+	let (key, sealed) = opening_key(matches, &input)?;
+
+	Ok(Output::Bytes(open(
+		Variant::of(matches),
+		&key,
+		&aad(matches)?,
+		sealed,
+	)?))
+}
+
+/// nonce | ciphertext | tag, the ciphertext and tag as the cipher orders them
+fn seal(
+	variant: Variant,
+	key: &[u8],
+	nonce: &[u8],
+	aad: &[u8],
+	plain: &[u8],
+) -> Result<Vec<u8>, Error> {
+	let payload = Payload { msg: plain, aad };
+	let sealed = match variant {
+		Variant::Ietf => encrypt::<ChaCha20Poly1305>(key, nonce, payload),
+		Variant::X => encrypt::<XChaCha20Poly1305>(key, nonce, payload),
+		Variant::Sodium => encrypt::<XSalsa20Poly1305>(key, nonce, payload),
+	}?;
+	Ok(nonce.iter().copied().chain(sealed).collect())
+}
+
+fn open(variant: Variant, key: &[u8], aad: &[u8], sealed: &[u8]) -> Result<Vec<u8>, Error> {
+	let nonce_len = variant.nonce_len();
+	if sealed.len() < nonce_len + TAG_LEN {
+		return Err(Error::invalid_input(format!(
+			"The cipher is too short, it needs the {}-byte nonce and the {}-byte tag",
+			nonce_len, TAG_LEN
+		)));
+	}
+	let (nonce, cipher) = sealed.split_at(nonce_len);
+	let payload = Payload { msg: cipher, aad };
+	match variant {
+		Variant::Ietf => decrypt::<ChaCha20Poly1305>(key, nonce, payload),
+		Variant::X => decrypt::<XChaCha20Poly1305>(key, nonce, payload),
+		Variant::Sodium => decrypt::<XSalsa20Poly1305>(key, nonce, payload),
+	}
+}
+
+fn cipher<C: KeyInit>(key: &[u8]) -> Result<C, Error> {
+	C::new_from_slice(key)
+		.map_err(|_| Error::Usage(format!("Invalid key size (should be {} bytes)", KEY_LEN)))
+}
+
+fn encrypt<C: Aead + KeyInit>(
+	key: &[u8],
+	nonce: &[u8],
+	payload: Payload,
+) -> Result<Vec<u8>, Error> {
+	cipher::<C>(key)?
+		.encrypt(nonce.into(), payload)
+		.map_err(|_| Error::Other("Failed to encrypt".to_string()))
+}
+
+fn decrypt<C: Aead + KeyInit>(
+	key: &[u8],
+	nonce: &[u8],
+	payload: Payload,
+) -> Result<Vec<u8>, Error> {
+	cipher::<C>(key)?
+		.decrypt(nonce.into(), payload)
+		.map_err(|_| authentication_failed())
+}
+
+mod cases {
+	use crate::modules::Case;
+	use linked_hash_map::LinkedHashMap;
+
+	pub fn cases() -> LinkedHashMap<&'static str, Vec<Case>> {
+		vec![
+			(
+				"chacha_encrypt",
+				vec![
+					Case {
+						desc: "ChaCha20-Poly1305, the nonce is random".to_string(),
+						input: vec![
+							"--key-hex",
+							"feffe9928665731c6d6a8f9467308308feffe9928665731c6d6a8f9467308308",
+							"'hello world'",
+						]
+						.into_iter()
+						.map(Into::into)
+						.collect(),
+						output: vec![
+							"yv66vvrO263eyviI2WC5zmmB1BLlvhf5oK1KanDUN35Hf3q/r8ax",
+						]
+						.into_iter()
+						.map(Into::into)
+						.collect(),
+						is_example: true,
+						is_test: false, // Random nonce
+						since: "0.17.0".to_string(),
+					},
+					Case {
+						desc: "XChaCha20-Poly1305, the nonce is random".to_string(),
+						input: vec![
+							"--x",
+							"--key-hex",
+							"feffe9928665731c6d6a8f9467308308feffe9928665731c6d6a8f9467308308",
+							"'hello world'",
+						]
+						.into_iter()
+						.map(Into::into)
+						.collect(),
+						output: vec![
+							"AAECAwQFBgcICQoLDA0ODxAREhMUFRYXcsOfvQF7Hh9KMfAlspVxNy0+6O6AtCsOpWgC",
+						]
+						.into_iter()
+						.map(Into::into)
+						.collect(),
+						is_example: true,
+						is_test: false, // Random nonce
+						since: "0.17.0".to_string(),
+					},
+					Case {
+						desc: "RFC 8439 section 2.8.2".to_string(),
+						input: vec![
+							"--key-hex",
+							"808182838485868788898a8b8c8d8e8f909192939495969798999a9b9c9d9e9f",
+							"--nonce-hex",
+							"070000004041424344454647",
+							"--aad-hex",
+							"50515253c0c1c2c3c4c5c6c7",
+							"'Ladies and Gentlemen of the class of '99: If I could offer you only one tip for the future, sunscreen would be it.'",
+						]
+						.into_iter()
+						.map(Into::into)
+						.collect(),
+						output: vec![
+							"BwAAAEBBQkNERUZH0xqNNGSOYNt7hq+8U+9+wqSt7VEpbgj+qeK1pzbuYtY9vqRejKlnEoL6+2naknKLGnHeCp4GCykF1qW2fs07NpLdvX8td4uMmAOu4ygJG1j6syTk+tZ1lFWFgItIMde8P/Te8I5Lep3ldtJlhs7GS2EWGuELWU8J4mp+kC7L0GAGkQ==",
+						]
+						.into_iter()
+						.map(Into::into)
+						.collect(),
+						is_example: false,
+						is_test: true,
+						since: "0.17.0".to_string(),
+					},
+					Case {
+						desc: "XChaCha20-Poly1305 of draft-irtf-cfrg-xchacha A.3.1".to_string(),
+						input: vec![
+							"--x",
+							"--key-hex",
+							"808182838485868788898a8b8c8d8e8f909192939495969798999a9b9c9d9e9f",
+							"--nonce-hex",
+							"404142434445464748494a4b4c4d4e4f5051525354555657",
+							"--aad-hex",
+							"50515253c0c1c2c3c4c5c6c7",
+							"'Ladies and Gentlemen of the class of '99: If I could offer you only one tip for the future, sunscreen would be it.'",
+						]
+						.into_iter()
+						.map(Into::into)
+						.collect(),
+						output: vec![
+							"QEFCQ0RFRkdISUpLTE1OT1BRUlNUVVZXvW0XnT6D1DuVdleUk8DpOVcqFwAlK/rMvtKQLCE5bLtzHH8bC0qmRAvzqC9O2n45rmTGcIxUwhbLlrcuEhO0Ui+Mm6QNtdlFsRtpuYLBu54/P6wrw2lIj3ayODVl0//5IflmTJdjfal2iBL2FcaLE7UuwIdZJMHHmHlH3q/YeArPSQ==",
+						]
+						.into_iter()
+						.map(Into::into)
+						.collect(),
+						is_example: false,
+						is_test: true,
+						since: "0.17.0".to_string(),
+					},
+					Case {
+						desc: "XChaCha20-Poly1305 with a fixed nonce".to_string(),
+						input: vec![
+							"--x",
+							"--key-hex",
+							"feffe9928665731c6d6a8f9467308308feffe9928665731c6d6a8f9467308308",
+							"--nonce-hex",
+							"000102030405060708090a0b0c0d0e0f1011121314151617",
+							"'hello world'",
+						]
+						.into_iter()
+						.map(Into::into)
+						.collect(),
+						output: vec![
+							"AAECAwQFBgcICQoLDA0ODxAREhMUFRYXcsOfvQF7Hh9KMfAlspVxNy0+6O6AtCsOpWgC",
+						]
+						.into_iter()
+						.map(Into::into)
+						.collect(),
+						is_example: false,
+						is_test: true,
+						since: "0.17.0".to_string(),
+					},
+					Case {
+						desc: "crypto_secretbox_easy of libsodium with a fixed nonce".to_string(),
+						input: vec![
+							"--sodium",
+							"--key-hex",
+							"feffe9928665731c6d6a8f9467308308feffe9928665731c6d6a8f9467308308",
+							"--nonce-hex",
+							"000102030405060708090a0b0c0d0e0f1011121314151617",
+							"'hello world'",
+						]
+						.into_iter()
+						.map(Into::into)
+						.collect(),
+						output: vec![
+							"AAECAwQFBgcICQoLDA0ODxAREhMUFRYXwQwGH272SLfswKTJcVlTK2t34GRj7VtGMOAl",
+						]
+						.into_iter()
+						.map(Into::into)
+						.collect(),
+						is_example: false,
+						is_test: true,
+						since: "0.17.0".to_string(),
+					},
+				],
+			),
+			(
+				"chacha_decrypt",
+				vec![
+					Case {
+						desc: "ChaCha20-Poly1305".to_string(),
+						input: vec![
+							"--key-hex",
+							"feffe9928665731c6d6a8f9467308308feffe9928665731c6d6a8f9467308308",
+							"yv66vvrO263eyviI2WC5zmmB1BLlvhf5oK1KanDUN35Hf3q/r8ax",
+						]
+						.into_iter()
+						.map(Into::into)
+						.collect(),
+						output: vec![
+							"hello world",
+						]
+						.into_iter()
+						.map(Into::into)
+						.collect(),
+						is_example: true,
+						is_test: true,
+						since: "0.17.0".to_string(),
+					},
+					Case {
+						desc: "XChaCha20-Poly1305".to_string(),
+						input: vec![
+							"--x",
+							"--key-hex",
+							"feffe9928665731c6d6a8f9467308308feffe9928665731c6d6a8f9467308308",
+							"AAECAwQFBgcICQoLDA0ODxAREhMUFRYXcsOfvQF7Hh9KMfAlspVxNy0+6O6AtCsOpWgC",
+						]
+						.into_iter()
+						.map(Into::into)
+						.collect(),
+						output: vec![
+							"hello world",
+						]
+						.into_iter()
+						.map(Into::into)
+						.collect(),
+						is_example: true,
+						is_test: true,
+						since: "0.17.0".to_string(),
+					},
+					Case {
+						desc: "crypto_secretbox_easy of libsodium".to_string(),
+						input: vec![
+							"--sodium",
+							"--key-hex",
+							"feffe9928665731c6d6a8f9467308308feffe9928665731c6d6a8f9467308308",
+							"AAECAwQFBgcICQoLDA0ODxAREhMUFRYXwQwGH272SLfswKTJcVlTK2t34GRj7VtGMOAl",
+						]
+						.into_iter()
+						.map(Into::into)
+						.collect(),
+						output: vec![
+							"hello world",
+						]
+						.into_iter()
+						.map(Into::into)
+						.collect(),
+						is_example: true,
+						is_test: true,
+						since: "0.17.0".to_string(),
+					},
+					Case {
+						desc: "RFC 8439 section 2.8.2".to_string(),
+						input: vec![
+							"--key-hex",
+							"808182838485868788898a8b8c8d8e8f909192939495969798999a9b9c9d9e9f",
+							"--aad-hex",
+							"50515253c0c1c2c3c4c5c6c7",
+							"BwAAAEBBQkNERUZH0xqNNGSOYNt7hq+8U+9+wqSt7VEpbgj+qeK1pzbuYtY9vqRejKlnEoL6+2naknKLGnHeCp4GCykF1qW2fs07NpLdvX8td4uMmAOu4ygJG1j6syTk+tZ1lFWFgItIMde8P/Te8I5Lep3ldtJlhs7GS2EWGuELWU8J4mp+kC7L0GAGkQ==",
+						]
+						.into_iter()
+						.map(Into::into)
+						.collect(),
+						output: vec![
+							"Ladies and Gentlemen of the class of '99: If I could offer you only one tip for the future, sunscreen would be it.",
+						]
+						.into_iter()
+						.map(Into::into)
+						.collect(),
+						is_example: false,
+						is_test: true,
+						since: "0.17.0".to_string(),
+					},
+					Case {
+						desc: "XChaCha20-Poly1305 of draft-irtf-cfrg-xchacha A.3.1".to_string(),
+						input: vec![
+							"--x",
+							"--key-hex",
+							"808182838485868788898a8b8c8d8e8f909192939495969798999a9b9c9d9e9f",
+							"--aad-hex",
+							"50515253c0c1c2c3c4c5c6c7",
+							"QEFCQ0RFRkdISUpLTE1OT1BRUlNUVVZXvW0XnT6D1DuVdleUk8DpOVcqFwAlK/rMvtKQLCE5bLtzHH8bC0qmRAvzqC9O2n45rmTGcIxUwhbLlrcuEhO0Ui+Mm6QNtdlFsRtpuYLBu54/P6wrw2lIj3ayODVl0//5IflmTJdjfal2iBL2FcaLE7UuwIdZJMHHmHlH3q/YeArPSQ==",
+						]
+						.into_iter()
+						.map(Into::into)
+						.collect(),
+						output: vec![
+							"Ladies and Gentlemen of the class of '99: If I could offer you only one tip for the future, sunscreen would be it.",
+						]
+						.into_iter()
+						.map(Into::into)
+						.collect(),
+						is_example: false,
+						is_test: true,
+						since: "0.17.0".to_string(),
+					},
+				],
+			),
+		]
+		.into_iter()
+		.collect()
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::modules::base::test::test_module;
+	use crate::modules::exit_code;
+	use base64::{engine::general_purpose, Engine as _};
+
+	#[test]
+	fn test_cases() {
+		test_module(module());
+	}
+
+	fn run(name: &str, args: Vec<&str>) -> Result<Output, Error> {
+		let command = &commands()[if name == "chacha_encrypt" { 0 } else { 1 }];
+		let matches = command
+			.app
+			.clone()
+			.args(&crate::app::global_args())
+			.get_matches_from_safe(Some(name).into_iter().chain(args))
+			.map_err(|e| Error::Usage(e.message))?;
+		(command.f)(&matches)
+	}
+
+	fn with<'s>(args: &[&'s str], input: &'s str) -> Vec<&'s str> {
+		args.iter().copied().chain(Some(input)).collect()
+	}
+
+	#[test]
+	fn test_round_trip() {
+		let key = "000102030405060708090a0b0c0d0e0f101112131415161718191a1b1c1d1e1f";
+		for (variant, sealed_len) in [
+			(None, NONCE_LEN + 3 + TAG_LEN),
+			(Some("--x"), X_NONCE_LEN + 3 + TAG_LEN),
+			(Some("--sodium"), X_NONCE_LEN + 3 + TAG_LEN),
+		] {
+			let args = variant
+				.into_iter()
+				.chain(vec!["--key-hex", key])
+				.collect::<Vec<_>>();
+			let cipher = match run("chacha_encrypt", with(&args, "abc")).unwrap() {
+				Output::Lines(lines) => lines[0].clone(),
+				output => panic!("Unexpected output: {:?}", output),
+			};
+			let sealed = general_purpose::STANDARD.decode(&cipher).unwrap();
+			assert_eq!(sealed.len(), sealed_len);
+			assert_eq!(
+				run("chacha_decrypt", with(&args, &cipher)),
+				Ok(Output::Bytes(b"abc".to_vec()))
+			);
+
+			let mut tampered = sealed;
+			tampered[sealed_len - 1] ^= 1;
+			let tampered = general_purpose::STANDARD.encode(&tampered);
+			assert_eq!(
+				run("chacha_decrypt", with(&args, &tampered)).map_err(|e| e.exit_code()),
+				Err(exit_code::INVALID)
+			);
+		}
+
+		// A cipher of one variant does not open with another
+		let cipher = match run("chacha_encrypt", vec!["--x", "--key-hex", key, "abc"]).unwrap() {
+			Output::Lines(lines) => lines[0].clone(),
+			output => panic!("Unexpected output: {:?}", output),
+		};
+		assert_eq!(
+			run("chacha_decrypt", vec!["--key-hex", key, &cipher]).map_err(String::from),
+			Err("Authentication failed".to_string())
+		);
+	}
+
+	#[test]
+	fn test_sodium_secretbox() {
+		// The first test of secretbox in NaCl
+		let key = hex::decode("1b27556473e985d462cd51197a9a46c76009549eac6474f206c4ee0844f68389")
+			.unwrap();
+		let nonce = hex::decode("69696ee955b62b73cd62bda875fc73d68219e0036b7a0b37").unwrap();
+		let plain = vec![0x42; 131];
+		let sealed = seal(Variant::Sodium, &key, &nonce, b"", &plain).unwrap();
+		assert_eq!(sealed[..X_NONCE_LEN], nonce[..]);
+		assert_eq!(sealed.len(), X_NONCE_LEN + TAG_LEN + plain.len());
+		assert_eq!(open(Variant::Sodium, &key, b"", &sealed), Ok(plain.clone()));
+
+		let m = hex::decode("be075fc53c81f2d5cf141316ebeb0c7b5228c52a4c62cbd44b66849b64244ffce5ecbaaf33bd751a1ac728d45e6c61296cdc3c01233561f41db66cce314adb310e3be8250c46f06dceea3a7fa1348057e2f6556ad6b1318a024a838f21af1fde048977eb48f59ffd4924ca1c60902e52f0a089bc76897040e082f937763848645e0705").unwrap();
+		let sealed = seal(Variant::Sodium, &key, &nonce, b"", &m).unwrap();
+		// The tag is before the ciphertext
+		assert_eq!(
+			hex::encode(&sealed[X_NONCE_LEN..X_NONCE_LEN + 24]),
+			"f3ffc7703f9400e52a7dfb4b3d3305d98e993b9f48681273"
+		);
+	}
+
+	#[test]
+	fn test_invalid_args() {
+		assert_eq!(
+			run("chacha_encrypt", vec!["--key-hex", "0011", "abc"]),
+			Err(Error::Usage(
+				"Invalid key size (should be 32 bytes)".to_string()
+			))
+		);
+		assert_eq!(
+			run(
+				"chacha_encrypt",
+				vec![
+					"--x",
+					"--key-hex",
+					&"00".repeat(32),
+					"--nonce-hex",
+					&"00".repeat(12),
+					"abc"
+				]
+			),
+			Err(Error::Usage(
+				"Invalid nonce, should be 24 bytes".to_string()
+			))
+		);
+		assert_eq!(
+			run(
+				"chacha_decrypt",
+				vec!["--key-hex", &"00".repeat(32), "AAAA"]
+			),
+			Err(Error::invalid_input(
+				"The cipher is too short, it needs the 12-byte nonce and the 16-byte tag"
+					.to_string()
+			))
+		);
+		assert!(run(
+			"chacha_encrypt",
+			vec![
+				"--sodium",
+				"--aad",
+				"v1",
+				"--key-hex",
+				&"00".repeat(32),
+				"abc"
+			]
+		)
+		.is_err());
+	}
+}
@@ -0,0 +1,471 @@
+use crate::modules::base::Hex;
+use crate::modules::{base, Command, Error, Module, Output};
+use base64::{engine::general_purpose, Engine as _};
+use clap::{Arg, ArgMatches, SubCommand};
+use crc::Crc;
+use serde_json::json;
+
+/// The standard input of the check values of the CRC catalog
+const CHECK_INPUT: &[u8] = b"123456789";
+
+pub fn module<'a, 'b>() -> Module<'a, 'b> {
+	Module {
+		desc: "Checksum (CRC32, CRC32C, CRC16, Adler32)".to_string(),
+		commands: commands(),
+		get_cases: cases::cases,
+	}
+}
+
+const INPUT_FORMAT_HELP: &str = "Input format
+utf8: UTF-8 string
+hex: Hex
+base64: Base64";
+
+pub fn commands<'a, 'b>() -> Vec<Command<'a, 'b>> {
+	vec![
+		Command {
+			app: SubCommand::with_name("crc32")
+				.about("CRC32 checksum")
+				.arg(input_format_arg())
+				.arg(Arg::with_name("INPUT").required(false).index(1))
+				.arg(base::file_arg()),
+			f: crc32,
+			serial: false,
+			pure: true,
+		},
+		Command {
+			app: SubCommand::with_name("adler32")
+				.about("Adler32 checksum")
+				.arg(input_format_arg())
+				.arg(Arg::with_name("INPUT").required(false).index(1))
+				.arg(base::file_arg()),
+			f: adler32,
+			serial: false,
+			pure: true,
+		},
+		Command {
+			app: SubCommand::with_name("crc")
+				.about("CRC or Adler-32 checksum of the algorithm")
+				.arg(
+					Arg::with_name("ALGORITHM")
+						.long("algorithm")
+						.short("a")
+						.help("Algorithm, see --list for the parameters")
+						.takes_value(true)
+						.possible_values(&algorithms().iter().map(|x| x.name).collect::<Vec<_>>())
+						.default_value("crc32"),
+				)
+				.arg(
+					Arg::with_name("HEX")
+						.long("hex")
+						.help("INPUT is the bytes in hex rather than a string"),
+				)
+				.arg(
+					Arg::with_name("LIST")
+						.long("list")
+						.help("List the algorithms with their width, poly, init, refin, refout, xorout and check value"),
+				)
+				.arg(Arg::with_name("INPUT").required(false).index(1))
+				.arg(base::file_arg()),
+			f: crc,
+			serial: false,
+			pure: true,
+		},
+	]
+}
+
+/// An algorithm of `crc`
+struct Algorithm {
+	name: &'static str,
+	/// The name in the CRC catalog, to match a datasheet
+	catalog: &'static str,
+	width: u8,
+	/// None for Adler-32, which is not a CRC
+	params: Option<CrcParams>,
+	checksum: fn(&[u8]) -> u32,
+}
+
+struct CrcParams {
+	poly: u32,
+	init: u32,
+	refin: bool,
+	refout: bool,
+	xorout: u32,
+}
+
+impl<W: crc::Width + Into<u32> + Copy> From<&crc::Algorithm<W>> for CrcParams {
+	fn from(algorithm: &crc::Algorithm<W>) -> Self {
+		Self {
+			poly: algorithm.poly.into(),
+			init: algorithm.init.into(),
+			refin: algorithm.refin,
+			refout: algorithm.refout,
+			xorout: algorithm.xorout.into(),
+		}
+	}
+}
+
+fn algorithms() -> Vec<Algorithm> {
+	vec![
+		Algorithm {
+			name: "crc32",
+			catalog: "CRC-32/ISO-HDLC",
+			width: 32,
+			params: Some((&crc::CRC_32_ISO_HDLC).into()),
+			checksum: |x| Crc::<u32>::new(&crc::CRC_32_ISO_HDLC).checksum(x),
+		},
+		Algorithm {
+			name: "crc32c",
+			catalog: "CRC-32/ISCSI",
+			width: 32,
+			params: Some((&crc::CRC_32_ISCSI).into()),
+			checksum: |x| Crc::<u32>::new(&crc::CRC_32_ISCSI).checksum(x),
+		},
+		Algorithm {
+			name: "crc16-ccitt",
+			catalog: "CRC-16/IBM-3740 (CCITT-FALSE)",
+			width: 16,
+			params: Some((&crc::CRC_16_IBM_3740).into()),
+			checksum: |x| Crc::<u16>::new(&crc::CRC_16_IBM_3740).checksum(x).into(),
+		},
+		Algorithm {
+			name: "crc16-modbus",
+			catalog: "CRC-16/MODBUS",
+			width: 16,
+			params: Some((&crc::CRC_16_MODBUS).into()),
+			checksum: |x| Crc::<u16>::new(&crc::CRC_16_MODBUS).checksum(x).into(),
+		},
+		Algorithm {
+			name: "adler32",
+			catalog: "Adler-32",
+			width: 32,
+			params: None,
+			checksum: adler2::adler32_slice,
+		},
+	]
+}
+
+fn input_format_arg<'a, 'b>() -> Arg<'a, 'b> {
+	Arg::with_name("INPUT_FORMAT")
+		.long("input-format")
+		.short("i")
+		.help(INPUT_FORMAT_HELP)
+		.takes_value(true)
+		.possible_values(&["utf8", "hex", "base64"])
+		.default_value("utf8")
+		.required(false)
+}
+
+fn crc32(matches: &ArgMatches) -> Result<Output, Error> {
+	let input = input(matches)?;
+
+	let result = Crc::<u32>::new(&crc::CRC_32_ISO_HDLC).checksum(&input);
+
+	output(matches, result, 32)
+}
+
+fn adler32(matches: &ArgMatches) -> Result<Output, Error> {
+	let input = input(matches)?;
+
+	let result = adler2::adler32_slice(&input);
+
+	output(matches, result, 32)
+}
+
+fn crc(matches: &ArgMatches) -> Result<Output, Error> {
+	if matches.is_present("LIST") {
+		return list(matches);
+	}
+	let name = matches.value_of("ALGORITHM").unwrap();
+	let algorithm = algorithms()
+		.into_iter()
+		.find(|x| x.name == name)
+		.ok_or_else(|| Error::Unsupported(format!("Unsupported algorithm: {}", name)))?;
+
+	let input = if matches.is_present("HEX") {
+		base::input_string(matches)?
+			.parse::<Hex>()
+			.map_err(|_| Error::invalid_input("Invalid hex".to_string()))?
+			.into()
+	} else {
+		base::input_bytes(matches)?
+	};
+
+	output(matches, (algorithm.checksum)(&input), algorithm.width)
+}
+
+fn list(matches: &ArgMatches) -> Result<Output, Error> {
+	let algorithms = algorithms();
+	let hex = |value: u32, width: u8| format!("0x{:0w$x}", value, w = width as usize / 4);
+
+	if matches.is_present("json") {
+		let list = algorithms
+			.iter()
+			.map(|x| {
+				let mut result = json!({
+					"name": x.name,
+					"catalog": x.catalog,
+					"width": x.width,
+					"check": hex((x.checksum)(CHECK_INPUT), x.width),
+				});
+				if let Some(params) = &x.params {
+					result["poly"] = json!(hex(params.poly, x.width));
+					result["init"] = json!(hex(params.init, x.width));
+					result["refin"] = json!(params.refin);
+					result["refout"] = json!(params.refout);
+					result["xorout"] = json!(hex(params.xorout, x.width));
+				}
+				result
+			})
+			.collect();
+		return Ok(Output::Structured(list));
+	}
+
+	let lines = algorithms
+		.iter()
+		.map(|x| {
+			let params = match &x.params {
+				Some(params) => format!(
+					"poly={} init={} refin={} refout={} xorout={}",
+					hex(params.poly, x.width),
+					hex(params.init, x.width),
+					params.refin,
+					params.refout,
+					hex(params.xorout, x.width)
+				),
+				None => "two sums modulo 65521, not a CRC".to_string(),
+			};
+			format!(
+				"{}: {}, width={} {} check={}",
+				x.name,
+				x.catalog,
+				x.width,
+				params,
+				hex((x.checksum)(CHECK_INPUT), x.width)
+			)
+		})
+		.collect();
+	Ok(Output::Lines(lines))
+}
+
+fn input(matches: &ArgMatches) -> Result<Vec<u8>, String> {
+	let input = base::input_string(matches)?;
+
+	let input = match matches.value_of("INPUT_FORMAT") {
+		Some("hex") => input.parse::<Hex>().map_err(|_| "Invalid hex")?.into(),
+		Some("base64") => general_purpose::STANDARD
+			.decode(input.trim())
+			.map_err(|_| "Invalid base64")?,
+		_ => input.into_bytes(),
+	};
+
+	Ok(input)
+}
+
+/// The checksum of `width` bits in hex and decimal
+fn output(matches: &ArgMatches, checksum: u32, width: u8) -> Result<Output, Error> {
+	let hex = format!("0x{:0w$x}", checksum, w = width as usize / 4);
+
+	if matches.is_present("json") {
+		return Ok(Output::Structured(json!({
+			"hex": hex,
+			"decimal": checksum,
+		})));
+	}
+
+	Ok(Output::Lines(vec![
+		format!("Hex: {}", hex),
+		format!("Decimal: {}", checksum),
+	]))
+}
+
+mod cases {
+	use crate::modules::Case;
+	use linked_hash_map::LinkedHashMap;
+
+	pub fn cases() -> LinkedHashMap<&'static str, Vec<Case>> {
+		vec![
+			(
+				"crc32",
+				vec![
+					Case {
+						desc: "".to_string(),
+						input: vec!["123456789"].into_iter().map(Into::into).collect(),
+						output: vec!["Hex: 0xcbf43926", "Decimal: 3421780262"]
+							.into_iter()
+							.map(Into::into)
+							.collect(),
+						is_example: true,
+						is_test: true,
+						since: "0.17.0".to_string(),
+					},
+					Case {
+						desc: "Hex input".to_string(),
+						input: vec!["-i", "hex", "0x616263"]
+							.into_iter()
+							.map(Into::into)
+							.collect(),
+						output: vec!["Hex: 0x352441c2", "Decimal: 891568578"]
+							.into_iter()
+							.map(Into::into)
+							.collect(),
+						is_example: true,
+						is_test: true,
+						since: "0.17.0".to_string(),
+					},
+					Case {
+						desc: "Base64 input".to_string(),
+						input: vec!["-i", "base64", "MTIzNDU2Nzg5"]
+							.into_iter()
+							.map(Into::into)
+							.collect(),
+						output: vec!["Hex: 0xcbf43926", "Decimal: 3421780262"]
+							.into_iter()
+							.map(Into::into)
+							.collect(),
+						is_example: false,
+						is_test: true,
+						since: "0.17.0".to_string(),
+					},
+				],
+			),
+			(
+				"adler32",
+				vec![
+					Case {
+						desc: "".to_string(),
+						input: vec!["123456789"].into_iter().map(Into::into).collect(),
+						output: vec!["Hex: 0x091e01de", "Decimal: 152961502"]
+							.into_iter()
+							.map(Into::into)
+							.collect(),
+						is_example: true,
+						is_test: true,
+						since: "0.17.0".to_string(),
+					},
+					Case {
+						desc: "Output as JSON".to_string(),
+						input: vec!["--json", "-i", "hex", "0x616263"]
+							.into_iter()
+							.map(Into::into)
+							.collect(),
+						output: vec![
+							r#"{
+  "decimal": 38600999,
+  "hex": "0x024d0127"
+}"#,
+						]
+						.into_iter()
+						.map(Into::into)
+						.collect(),
+						is_example: false,
+						is_test: true,
+						since: "0.17.0".to_string(),
+					},
+				],
+			),
+			(
+				"crc",
+				vec![
+					Case {
+						desc: "CRC-32C check value".to_string(),
+						input: vec!["-a", "crc32c", "123456789"].into_iter().map(Into::into).collect(),
+						output: vec!["Hex: 0xe3069283", "Decimal: 3808858755"].into_iter().map(Into::into).collect(),
+						is_example: true,
+						is_test: true,
+						since: "0.17.0".to_string(),
+					},
+					Case {
+						desc: "CRC-32 check value".to_string(),
+						input: vec!["123456789"].into_iter().map(Into::into).collect(),
+						output: vec!["Hex: 0xcbf43926", "Decimal: 3421780262"].into_iter().map(Into::into).collect(),
+						is_example: false,
+						is_test: true,
+						since: "0.17.0".to_string(),
+					},
+					Case {
+						desc: "CRC-16/CCITT-FALSE check value".to_string(),
+						input: vec!["-a", "crc16-ccitt", "123456789"].into_iter().map(Into::into).collect(),
+						output: vec!["Hex: 0x29b1", "Decimal: 10673"].into_iter().map(Into::into).collect(),
+						is_example: true,
+						is_test: true,
+						since: "0.17.0".to_string(),
+					},
+					Case {
+						desc: "CRC-16/MODBUS check value".to_string(),
+						input: vec!["-a", "crc16-modbus", "123456789"].into_iter().map(Into::into).collect(),
+						output: vec!["Hex: 0x4b37", "Decimal: 19255"].into_iter().map(Into::into).collect(),
+						is_example: false,
+						is_test: true,
+						since: "0.17.0".to_string(),
+					},
+					Case {
+						desc: "Adler-32 check value".to_string(),
+						input: vec!["-a", "adler32", "123456789"].into_iter().map(Into::into).collect(),
+						output: vec!["Hex: 0x091e01de", "Decimal: 152961502"].into_iter().map(Into::into).collect(),
+						is_example: false,
+						is_test: true,
+						since: "0.17.0".to_string(),
+					},
+					Case {
+						desc: "Modbus RTU frame in hex, sent as 84 0a".to_string(),
+						input: vec!["-a", "crc16-modbus", "--hex", "0x010300000001"].into_iter().map(Into::into).collect(),
+						output: vec!["Hex: 0x0a84", "Decimal: 2692"].into_iter().map(Into::into).collect(),
+						is_example: true,
+						is_test: true,
+						since: "0.17.0".to_string(),
+					},
+					Case {
+						desc: "Parameters of the algorithms".to_string(),
+						input: vec!["--list"].into_iter().map(Into::into).collect(),
+						output: vec![
+							"crc32: CRC-32/ISO-HDLC, width=32 poly=0x04c11db7 init=0xffffffff refin=true refout=true xorout=0xffffffff check=0xcbf43926",
+							"crc32c: CRC-32/ISCSI, width=32 poly=0x1edc6f41 init=0xffffffff refin=true refout=true xorout=0xffffffff check=0xe3069283",
+							"crc16-ccitt: CRC-16/IBM-3740 (CCITT-FALSE), width=16 poly=0x1021 init=0xffff refin=false refout=false xorout=0x0000 check=0x29b1",
+							"crc16-modbus: CRC-16/MODBUS, width=16 poly=0x8005 init=0xffff refin=true refout=true xorout=0x0000 check=0x4b37",
+							"adler32: Adler-32, width=32 two sums modulo 65521, not a CRC check=0x091e01de",
+						]
+						.into_iter()
+						.map(Into::into)
+						.collect(),
+						is_example: true,
+						is_test: true,
+						since: "0.17.0".to_string(),
+					},
+				],
+			),
+		]
+		.into_iter()
+		.collect()
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::modules::base::test::test_module;
+
+	#[test]
+	fn test_cases() {
+		test_module(module());
+	}
+
+	#[test]
+	fn test_check_values() {
+		// The check values of the CRC catalog
+		let checks = algorithms()
+			.iter()
+			.map(|x| (x.name, (x.checksum)(CHECK_INPUT)))
+			.collect::<Vec<_>>();
+		assert_eq!(
+			checks,
+			vec![
+				("crc32", 0xcbf43926),
+				("crc32c", 0xe3069283),
+				("crc16-ccitt", 0x29b1),
+				("crc16-modbus", 0x4b37),
+				("adler32", 0x091e01de),
+			]
+		);
+	}
+}
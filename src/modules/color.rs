@@ -0,0 +1,157 @@
+use clap::ArgMatches;
+use std::env;
+
+const RESET: &str = "\x1b[0m";
+const RED: &str = "\x1b[31m";
+const GREEN: &str = "\x1b[32m";
+const YELLOW: &str = "\x1b[33m";
+const BLUE: &str = "\x1b[34m";
+const MAGENTA: &str = "\x1b[35m";
+
+/// Whether to colorize given --color, auto colorizes a terminal unless NO_COLOR is set
+pub fn enabled(matches: &ArgMatches, is_terminal: bool) -> bool {
+	match matches.value_of("color") {
+		Some("always") => true,
+		Some("never") => false,
+		_ => is_terminal && env::var_os("NO_COLOR").is_none_or(|x| x.is_empty()),
+	}
+}
+
+pub fn error(line: &str) -> String {
+	paint(line, RED)
+}
+
+/// Colorize an output line: verification results as a whole, and the JSON
+/// value the line ends with, if any
+pub fn colorize(line: &str) -> String {
+	if line.starts_with("Valid: true") {
+		return paint(line, GREEN);
+	}
+	if line.starts_with("Valid: false") || line.starts_with("Error:") {
+		return paint(line, RED);
+	}
+
+	match line.find(['{', '[']) {
+		Some(i) if serde_json::from_str::<serde_json::Value>(&line[i..]).is_ok() => {
+			format!("{}{}", &line[..i], highlight_json(&line[i..]))
+		}
+		_ => line.to_string(),
+	}
+}
+
+/// Highlight keys, strings, numbers and literals of a valid JSON text
+fn highlight_json(json: &str) -> String {
+	let chars = json.chars().collect::<Vec<_>>();
+	let mut result = String::new();
+	let mut i = 0;
+	while i < chars.len() {
+		let start = i;
+		let color = match chars[i] {
+			'"' => {
+				i += 1;
+				while chars[i] != '"' {
+					i += if chars[i] == '\\' { 2 } else { 1 };
+				}
+				i += 1;
+				if chars[i..].iter().find(|c| !c.is_whitespace()) == Some(&':') {
+					BLUE
+				} else {
+					GREEN
+				}
+			}
+			'-' | '0'..='9' => {
+				let is_number = |c: char| matches!(c, '-' | '+' | '.' | 'e' | 'E' | '0'..='9');
+				while i < chars.len() && is_number(chars[i]) {
+					i += 1;
+				}
+				YELLOW
+			}
+			'a'..='z' => {
+				while i < chars.len() && chars[i].is_ascii_lowercase() {
+					i += 1;
+				}
+				MAGENTA
+			}
+			c => {
+				result.push(c);
+				i += 1;
+				continue;
+			}
+		};
+		result.push_str(&paint(&chars[start..i].iter().collect::<String>(), color));
+	}
+	result
+}
+
+fn paint(text: &str, color: &str) -> String {
+	format!("{}{}{}", color, text, RESET)
+}
+
+/// Parse a #RRGGBB hex color, the # is optional
+pub fn parse_rgb(hex: &str) -> Result<[u8; 3], String> {
+	let digits = hex.strip_prefix('#').unwrap_or(hex);
+	let invalid = || format!("Invalid color: {}, e.g. #000000", hex);
+	if digits.len() != 6 || !digits.bytes().all(|c| c.is_ascii_hexdigit()) {
+		return Err(invalid());
+	}
+	let channel = |i: usize| u8::from_str_radix(&digits[i..i + 2], 16).map_err(|_| invalid());
+	Ok([channel(0)?, channel(2)?, channel(4)?])
+}
+
+/// Paint text on a 24-bit background color
+pub fn background(text: &str, rgb: [u8; 3]) -> String {
+	let color = format!("\x1b[48;2;{};{};{}m", rgb[0], rgb[1], rgb[2]);
+	paint(text, &color)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::app;
+
+	#[test]
+	fn test_colorize() {
+		assert_eq!(colorize("Valid: true"), "\x1b[32mValid: true\x1b[0m");
+		assert_eq!(
+			colorize("Error: InvalidSignature"),
+			"\x1b[31mError: InvalidSignature\x1b[0m"
+		);
+		assert_eq!(colorize("Hex: 0x616263"), "Hex: 0x616263");
+		assert_eq!(
+			colorize(
+				"Payload: {\n  \"sub\": \"a\\\"b\",\n  \"iat\": -1.5e3,\n  \"admin\": true\n}"
+			),
+			"Payload: {\n  \x1b[34m\"sub\"\x1b[0m: \x1b[32m\"a\\\"b\"\x1b[0m,\n  \
+			 \x1b[34m\"iat\"\x1b[0m: \x1b[33m-1.5e3\x1b[0m,\n  \
+			 \x1b[34m\"admin\"\x1b[0m: \x1b[35mtrue\x1b[0m\n}"
+		);
+		// Not JSON
+		assert_eq!(colorize("Range: [0, 1)"), "Range: [0, 1)");
+	}
+
+	#[test]
+	fn test_enabled() {
+		let app = app::build_app().0;
+		let enabled_with = |color: &str, is_terminal: bool| {
+			let matches = app
+				.clone()
+				.get_matches_from(vec!["dtool", "--color", color, "s2h", "a"]);
+			enabled(&matches, is_terminal)
+		};
+		assert!(enabled_with("always", false));
+		assert!(!enabled_with("never", true));
+		assert!(!enabled_with("auto", false));
+	}
+
+	#[test]
+	fn test_parse_rgb() {
+		assert_eq!(parse_rgb("#1a2B3c"), Ok([0x1a, 0x2b, 0x3c]));
+		assert_eq!(parse_rgb("ffffff"), Ok([255, 255, 255]));
+		assert_eq!(
+			parse_rgb("#fff"),
+			Err("Invalid color: #fff, e.g. #000000".to_string())
+		);
+		assert!(parse_rgb("#gggggg").is_err());
+		assert_eq!(background("  ", [0, 0, 0]), "\x1b[48;2;0;0;0m  \x1b[0m");
+	}
+}
@@ -0,0 +1,120 @@
+use crate::log;
+use crate::modules::{base, usage, Module, Output};
+use clap::{App, Arg, ArgMatches, SubCommand};
+
+pub fn app<'a, 'b>() -> App<'a, 'b> {
+	SubCommand::with_name("doc")
+		.about("Generate the documentation of the sub commands from their examples")
+		.arg(
+			Arg::with_name("MARKDOWN")
+				.long("markdown")
+				.help("Markdown format, the default and only format for now"),
+		)
+		.arg(
+			Arg::with_name("MODULE")
+				.long("module")
+				.short("m")
+				.help("Document only the modules whose description contains the keyword, e.g. jwt")
+				.takes_value(true)
+				.required(false),
+		)
+		.arg(
+			Arg::with_name("OUT")
+				.long("out")
+				.help("Write the documentation to the file, e.g. USAGE.md (see --force)")
+				.takes_value(true)
+				.required(false),
+		)
+}
+
+pub fn run(matches: &ArgMatches, modules: &[Module]) -> Result<Output, String> {
+	let module = matches.value_of("MODULE").map(str::to_lowercase);
+	let modules = modules
+		.iter()
+		.filter(|x| match &module {
+			Some(module) => x.desc.to_lowercase().contains(module),
+			None => true,
+		})
+		.collect::<Vec<_>>();
+
+	let lines = markdown(&modules);
+
+	match matches.value_of("OUT") {
+		Some(path) => {
+			let content = lines.iter().map(|x| format!("{}\n", x)).collect::<String>();
+			base::write_file_atomic(path, content.as_bytes(), matches.is_present("force"))
+				.map_err(|e| e.to_string())?;
+			log::info(&format!("Output written to: {}", path));
+			Ok(Output::Lines(vec![]))
+		}
+		None => Ok(Output::Lines(lines)),
+	}
+}
+
+/// A section per module and a table of the examples per sub command
+fn markdown(modules: &[&Module]) -> Vec<String> {
+	let mut result = vec!["# Usage".to_string()];
+	for module in modules {
+		result.push("".to_string());
+		result.push(format!("## {}", module.desc));
+
+		let cases = (module.get_cases)();
+		for command in &module.commands {
+			let name = command.app.get_name();
+			let examples = cases
+				.get(name)
+				.into_iter()
+				.flatten()
+				.filter(|x| x.is_example)
+				.collect::<Vec<_>>();
+			if examples.is_empty() {
+				continue;
+			}
+
+			result.push("".to_string());
+			result.push(format!("### {}", name));
+			result.push("".to_string());
+			result.push(escape_cell(&usage::get_about(&command.app)));
+			result.push("".to_string());
+			result.push("| Description | Command line | Output | Since |".to_string());
+			result.push("|---|---|---|---|".to_string());
+			for case in examples {
+				let command_line = usage::command_line(name, &case.input);
+				result.push(format!(
+					"| {} | <code>{}</code> | {} | v{} |",
+					escape_cell(&case.desc),
+					escape_cell(&command_line),
+					escape_cell(&case.output.join("\n")),
+					case.since
+				));
+			}
+		}
+	}
+	result
+}
+
+/// Escape a text for a markdown table cell: HTML special chars, pipes, and
+/// line breaks and indentation which a cell can not keep otherwise
+fn escape_cell(text: &str) -> String {
+	text.replace('&', "&amp;")
+		.replace('<', "&lt;")
+		.replace('>', "&gt;")
+		.replace('|', "\\|")
+		.replace("  ", "&nbsp;&nbsp;")
+		.replace('\n', "<br>")
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_escape_cell() {
+		assert_eq!(escape_cell("a | b"), "a \\| b");
+		assert_eq!(escape_cell("<INPUT> & more"), "&lt;INPUT&gt; &amp; more");
+		assert_eq!(
+			escape_cell("{\n  \"a\": 1\n}"),
+			"{<br>&nbsp;&nbsp;\"a\": 1<br>}"
+		);
+	}
+}
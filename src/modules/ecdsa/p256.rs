@@ -1,6 +1,11 @@
-use crate::modules::ecdsa::SignatureFormEnum;
+use crate::modules::ecdsa::{PrivateKey, PublicKey, SignatureFormEnum, Verification};
 use crate::modules::Case;
 use linked_hash_map::LinkedHashMap;
+use p256::ecdsa::signature::hazmat::{PrehashSigner, PrehashVerifier};
+use p256::ecdsa::{Signature, SigningKey, VerifyingKey};
+use p256::pkcs8::{
+	DecodePrivateKey, DecodePublicKey, EncodePrivateKey, EncodePublicKey, LineEnding,
+};
 use p256::{elliptic_curve::sec1::ToEncodedPoint, SecretKey};
 use rand::thread_rng;
 use ring::{
@@ -16,7 +21,7 @@ pub fn ec_gk_p256(compress: bool) -> Result<(Vec<u8>, Vec<u8>), String> {
 	let secret_key = SecretKey::random(&mut thread_rng());
 	let public_key = secret_key.public_key();
 
-	let secret_key = secret_key.as_scalar_bytes().as_bytes().as_slice().to_vec();
+	let secret_key = secret_key.to_bytes().to_vec();
 	let public_key = public_key.to_encoded_point(compress).as_bytes().to_vec();
 
 	Ok((secret_key, public_key))
@@ -27,7 +32,7 @@ pub fn ec_sign_p256(
 	message: Vec<u8>,
 	sig_form: SignatureFormEnum,
 ) -> Result<Vec<u8>, String> {
-	let secret_key_obj = SecretKey::from_bytes(&secret_key).map_err(|_| "Invalid secret key")?;
+	let secret_key_obj = SecretKey::from_slice(&secret_key).map_err(|_| "Invalid secret key")?;
 	let public_key = secret_key_obj.public_key();
 	let public_key = public_key.to_encoded_point(false);
 	let public_key = public_key.as_bytes();
@@ -69,13 +74,82 @@ pub fn ec_verify_p256(
 }
 
 pub fn ec_pk_p256(secret_key: Vec<u8>, compress: bool) -> Result<Vec<u8>, String> {
-	let secret_key_obj = SecretKey::from_bytes(&secret_key).map_err(|_| "Invalid secret key")?;
+	let secret_key_obj = SecretKey::from_slice(&secret_key).map_err(|_| "Invalid secret key")?;
 	let public_key = secret_key_obj.public_key();
 	let public_key = public_key.to_encoded_point(compress);
 	let public_key = public_key.as_bytes().to_vec();
 	Ok(public_key)
 }
 
+pub fn keygen_p256() -> Result<(String, String), String> {
+	let secret_key = SecretKey::random(&mut thread_rng());
+	let private_pem = secret_key
+		.to_pkcs8_pem(LineEnding::LF)
+		.map_err(|e| format!("Failed to encode private key: {}", e))?
+		.to_string();
+	let public_pem = secret_key
+		.public_key()
+		.to_public_key_pem(LineEnding::LF)
+		.map_err(|e| format!("Failed to encode public key: {}", e))?;
+	Ok((private_pem, public_pem))
+}
+
+/// RFC 6979 deterministic signature of the digest
+pub fn sign_prehash_p256(
+	secret_key: PrivateKey,
+	digest: &[u8],
+	low_s: bool,
+	sig_form: SignatureFormEnum,
+) -> Result<Vec<u8>, String> {
+	let secret_key = match secret_key {
+		PrivateKey::Pkcs8(der) => SecretKey::from_pkcs8_der(&der).ok(),
+		PrivateKey::Sec1(der) => SecretKey::from_sec1_der(&der).ok(),
+		PrivateKey::Scalar(scalar) => SecretKey::from_slice(&scalar).ok(),
+	}
+	.ok_or("Invalid secret key (not a P-256 key)")?;
+
+	let sig: Signature = SigningKey::from(secret_key)
+		.sign_prehash(digest)
+		.map_err(|e| format!("Failed to sign: {}", e))?;
+	let sig = match low_s {
+		true => sig.normalize_s().unwrap_or(sig),
+		false => sig,
+	};
+
+	let sig = match sig_form {
+		SignatureFormEnum::Fixed => sig.to_bytes().to_vec(),
+		SignatureFormEnum::Der => sig.to_der().as_bytes().to_vec(),
+	};
+	Ok(sig)
+}
+
+/// The signature is DER or fixed r || s
+pub fn verify_prehash_p256(
+	public_key: PublicKey,
+	digest: &[u8],
+	sig: &[u8],
+	low_s: bool,
+) -> Result<Verification, String> {
+	let public_key = match public_key {
+		PublicKey::Spki(der) => p256::PublicKey::from_public_key_der(&der).ok(),
+		PublicKey::Sec1(point) => p256::PublicKey::from_sec1_bytes(&point).ok(),
+	}
+	.ok_or("Invalid public key (not a P-256 key)")?;
+	let sig = Signature::from_der(sig)
+		.or_else(|_| Signature::from_slice(sig))
+		.map_err(|_| "Invalid signature (should be DER or fixed r || s)")?;
+
+	let normalized = sig.normalize_s();
+	if low_s && normalized.is_some() {
+		return Ok(Verification::HighS);
+	}
+	let sig = normalized.unwrap_or(sig);
+	match VerifyingKey::from(public_key).verify_prehash(digest, &sig) {
+		Ok(()) => Ok(Verification::Valid),
+		Err(_) => Ok(Verification::Invalid),
+	}
+}
+
 pub fn cases() -> LinkedHashMap<&'static str, Vec<Case>> {
 	vec![
 		("ec_gk",
@@ -142,5 +216,70 @@ pub fn cases() -> LinkedHashMap<&'static str, Vec<Case>> {
 				 since: "0.7.0".to_string(),
 			 },
 		 ]),
+		("ecdsa_keygen",
+		 vec![
+			 Case {
+				 desc: "Generate a P-256 key pair".to_string(),
+				 input: vec!["-c", "p256", "--out-priv", "priv.pem", "--out-pub", "pub.pem"].into_iter().map(Into::into).collect(),
+				 output: vec!["Private key written to: priv.pem", "Public key written to: pub.pem"].into_iter().map(Into::into).collect(),
+				 is_example: true,
+				 is_test: false, // writes files of a random key
+				 since: "0.17.0".to_string(),
+			 },
+		 ]),
+		("ecdsa_sign",
+		 vec![
+			 Case {
+				 desc: "P-256, RFC 6979 A.2.5 (SHA-256, message sample)".to_string(),
+				 input: vec!["-c", "p256", "--key", "0xc9afa9d845ba75166b5c215767b1d6934e50c3db36e89b127b8a622b120f6721", "sample"].into_iter().map(Into::into).collect(),
+				 output: vec!["0xefd48b2aacb6a8fd1140dd9cd45e81d69d2c877b56aaf991c34d0ea84eaf3716f7cb1c942d657c41d436c7a1b6e29f65f3e900dbb9aff4064dc4ab2f843acda8"].into_iter().map(Into::into).collect(),
+				 is_example: true,
+				 is_test: true,
+				 since: "0.17.0".to_string(),
+			 },
+			 Case {
+				 desc: "P-256, low-s, DER signature form".to_string(),
+				 input: vec!["-c", "p256", "--key", "0xc9afa9d845ba75166b5c215767b1d6934e50c3db36e89b127b8a622b120f6721", "--low-s", "-f", "der", "sample"].into_iter().map(Into::into).collect(),
+				 output: vec!["0x3045022100efd48b2aacb6a8fd1140dd9cd45e81d69d2c877b56aaf991c34d0ea84eaf371602200834e36ad29a83bf2bc9385e491d6099c8fdf9d1ed67aa7ea5f51f93782857a9"].into_iter().map(Into::into).collect(),
+				 is_example: false,
+				 is_test: true,
+				 since: "0.17.0".to_string(),
+			 },
+			 Case {
+				 desc: "P-256, pre-hashed message".to_string(),
+				 input: vec!["-c", "p256", "--key", "0xc9afa9d845ba75166b5c215767b1d6934e50c3db36e89b127b8a622b120f6721", "--prehashed-hex", "0xaf2bdbe1aa9b6ec1e2ade1d694f41fc71a831d0268e9891562113d8a62add1bf"].into_iter().map(Into::into).collect(),
+				 output: vec!["0xefd48b2aacb6a8fd1140dd9cd45e81d69d2c877b56aaf991c34d0ea84eaf3716f7cb1c942d657c41d436c7a1b6e29f65f3e900dbb9aff4064dc4ab2f843acda8"].into_iter().map(Into::into).collect(),
+				 is_example: false,
+				 is_test: true,
+				 since: "0.17.0".to_string(),
+			 },
+		 ]),
+		("ecdsa_verify",
+		 vec![
+			 Case {
+				 desc: "P-256, the DER signature form is detected".to_string(),
+				 input: vec!["-c", "p256", "--key", "0x0360fed4ba255a9d31c961eb74c6356d68c049b8923b61fa6ce669622e60f29fb6", "-S", "0x3046022100efd48b2aacb6a8fd1140dd9cd45e81d69d2c877b56aaf991c34d0ea84eaf3716022100f7cb1c942d657c41d436c7a1b6e29f65f3e900dbb9aff4064dc4ab2f843acda8", "sample"].into_iter().map(Into::into).collect(),
+				 output: vec!["Valid: true"].into_iter().map(Into::into).collect(),
+				 is_example: true,
+				 is_test: true,
+				 since: "0.17.0".to_string(),
+			 },
+			 Case {
+				 desc: "P-256, pre-hashed message".to_string(),
+				 input: vec!["-c", "p256", "--key", "0x0360fed4ba255a9d31c961eb74c6356d68c049b8923b61fa6ce669622e60f29fb6", "--prehashed-hex", "0xaf2bdbe1aa9b6ec1e2ade1d694f41fc71a831d0268e9891562113d8a62add1bf", "-S", "0xefd48b2aacb6a8fd1140dd9cd45e81d69d2c877b56aaf991c34d0ea84eaf3716f7cb1c942d657c41d436c7a1b6e29f65f3e900dbb9aff4064dc4ab2f843acda8"].into_iter().map(Into::into).collect(),
+				 output: vec!["Valid: true"].into_iter().map(Into::into).collect(),
+				 is_example: false,
+				 is_test: true,
+				 since: "0.17.0".to_string(),
+			 },
+			 Case {
+				 desc: "P-256, a signature of another message".to_string(),
+				 input: vec!["-c", "p256", "--key", "0x0360fed4ba255a9d31c961eb74c6356d68c049b8923b61fa6ce669622e60f29fb6", "-S", "0xefd48b2aacb6a8fd1140dd9cd45e81d69d2c877b56aaf991c34d0ea84eaf3716f7cb1c942d657c41d436c7a1b6e29f65f3e900dbb9aff4064dc4ab2f843acda8", "sample!"].into_iter().map(Into::into).collect(),
+				 output: vec!["Valid: false"].into_iter().map(Into::into).collect(),
+				 is_example: false,
+				 is_test: true,
+				 since: "0.17.0".to_string(),
+			 },
+		 ]),
 	].into_iter().collect()
 }
@@ -1,6 +1,13 @@
-use crate::modules::ecdsa::SignatureFormEnum;
+use crate::modules::ecdsa::{PrivateKey, PublicKey, SignatureFormEnum, Verification};
 use crate::modules::Case;
 use linked_hash_map::LinkedHashMap;
+use p384::ecdsa::signature::hazmat::{PrehashSigner, PrehashVerifier};
+use p384::ecdsa::{Signature, SigningKey, VerifyingKey};
+use p384::pkcs8::{
+	DecodePrivateKey, DecodePublicKey, EncodePrivateKey, EncodePublicKey, LineEnding,
+};
+use p384::SecretKey;
+use rand::thread_rng;
 use ring::signature::{VerificationAlgorithm, ECDSA_P384_SHA384_ASN1, ECDSA_P384_SHA384_FIXED};
 use untrusted::Input;
 
@@ -42,6 +49,75 @@ pub fn ec_pk_p384(_secret_key: Vec<u8>, _compress: bool) -> Result<Vec<u8>, Stri
 	unimplemented!()
 }
 
+pub fn keygen_p384() -> Result<(String, String), String> {
+	let secret_key = SecretKey::random(&mut thread_rng());
+	let private_pem = secret_key
+		.to_pkcs8_pem(LineEnding::LF)
+		.map_err(|e| format!("Failed to encode private key: {}", e))?
+		.to_string();
+	let public_pem = secret_key
+		.public_key()
+		.to_public_key_pem(LineEnding::LF)
+		.map_err(|e| format!("Failed to encode public key: {}", e))?;
+	Ok((private_pem, public_pem))
+}
+
+/// RFC 6979 deterministic signature of the digest
+pub fn sign_prehash_p384(
+	secret_key: PrivateKey,
+	digest: &[u8],
+	low_s: bool,
+	sig_form: SignatureFormEnum,
+) -> Result<Vec<u8>, String> {
+	let secret_key = match secret_key {
+		PrivateKey::Pkcs8(der) => SecretKey::from_pkcs8_der(&der).ok(),
+		PrivateKey::Sec1(der) => SecretKey::from_sec1_der(&der).ok(),
+		PrivateKey::Scalar(scalar) => SecretKey::from_slice(&scalar).ok(),
+	}
+	.ok_or("Invalid secret key (not a P-384 key)")?;
+
+	let sig: Signature = SigningKey::from(secret_key)
+		.sign_prehash(digest)
+		.map_err(|e| format!("Failed to sign: {}", e))?;
+	let sig = match low_s {
+		true => sig.normalize_s().unwrap_or(sig),
+		false => sig,
+	};
+
+	let sig = match sig_form {
+		SignatureFormEnum::Fixed => sig.to_bytes().to_vec(),
+		SignatureFormEnum::Der => sig.to_der().as_bytes().to_vec(),
+	};
+	Ok(sig)
+}
+
+/// The signature is DER or fixed r || s
+pub fn verify_prehash_p384(
+	public_key: PublicKey,
+	digest: &[u8],
+	sig: &[u8],
+	low_s: bool,
+) -> Result<Verification, String> {
+	let public_key = match public_key {
+		PublicKey::Spki(der) => p384::PublicKey::from_public_key_der(&der).ok(),
+		PublicKey::Sec1(point) => p384::PublicKey::from_sec1_bytes(&point).ok(),
+	}
+	.ok_or("Invalid public key (not a P-384 key)")?;
+	let sig = Signature::from_der(sig)
+		.or_else(|_| Signature::from_slice(sig))
+		.map_err(|_| "Invalid signature (should be DER or fixed r || s)")?;
+
+	let normalized = sig.normalize_s();
+	if low_s && normalized.is_some() {
+		return Ok(Verification::HighS);
+	}
+	let sig = normalized.unwrap_or(sig);
+	match VerifyingKey::from(public_key).verify_prehash(digest, &sig) {
+		Ok(()) => Ok(Verification::Valid),
+		Err(_) => Ok(Verification::Invalid),
+	}
+}
+
 pub fn cases() -> LinkedHashMap<&'static str, Vec<Case>> {
 	vec![
 		("ec_gk",
@@ -108,5 +184,27 @@ pub fn cases() -> LinkedHashMap<&'static str, Vec<Case>> {
 				 since: "0.7.0".to_string(),
 			 },
 		 ]),
+		("ecdsa_sign",
+		 vec![
+			 Case {
+				 desc: "P-384, RFC 6979 A.2.6 (SHA-384, message sample)".to_string(),
+				 input: vec!["-c", "p384", "--key", "0x6b9d3dad2e1b8c1c05b19875b6659f4de23c3b667bf297ba9aa47740787137d896d5724e4c70a825f872c9ea60d2edf5", "sample"].into_iter().map(Into::into).collect(),
+				 output: vec!["0x94edbb92a5ecb8aad4736e56c691916b3f88140666ce9fa73d64c4ea95ad133c81a648152e44acf96e36dd1e80fabe4699ef4aeb15f178cea1fe40db2603138f130e740a19624526203b6351d0a3a94fa329c145786e679e7b82c71a38628ac8"].into_iter().map(Into::into).collect(),
+				 is_example: false,
+				 is_test: true,
+				 since: "0.17.0".to_string(),
+			 },
+		 ]),
+		("ecdsa_verify",
+		 vec![
+			 Case {
+				 desc: "P-384".to_string(),
+				 input: vec!["-c", "p384", "--key", "0x02ec3a4e415b4e19a4568618029f427fa5da9a8bc4ae92e02e06aae5286b300c64def8f0ea9055866064a254515480bc13", "-S", "0x94edbb92a5ecb8aad4736e56c691916b3f88140666ce9fa73d64c4ea95ad133c81a648152e44acf96e36dd1e80fabe4699ef4aeb15f178cea1fe40db2603138f130e740a19624526203b6351d0a3a94fa329c145786e679e7b82c71a38628ac8", "sample"].into_iter().map(Into::into).collect(),
+				 output: vec!["Valid: true"].into_iter().map(Into::into).collect(),
+				 is_example: false,
+				 is_test: true,
+				 since: "0.17.0".to_string(),
+			 },
+		 ]),
 	].into_iter().collect()
 }
@@ -3,8 +3,12 @@ use secp256k1::hashes::sha256;
 use secp256k1::rand::thread_rng;
 use secp256k1::{ecdsa, Message, PublicKey, Secp256k1, SecretKey};
 
-use crate::modules::ecdsa::SignatureFormEnum;
+use crate::modules::ecdsa::{PrivateKey, PublicKey as KeyInput, SignatureFormEnum, Verification};
 use crate::modules::Case;
+use k256::ecdsa::signature::hazmat::{PrehashSigner, PrehashVerifier};
+use k256::pkcs8::{
+	DecodePrivateKey, DecodePublicKey, EncodePrivateKey, EncodePublicKey, LineEnding,
+};
 
 pub fn ec_gk_secp256k1(compress: bool) -> Result<(Vec<u8>, Vec<u8>), String> {
 	let (secret_key, public_key) = secp256k1::Secp256k1::new().generate_keypair(&mut thread_rng());
@@ -74,6 +78,75 @@ pub fn ec_pk_secp256k1(secret_key: Vec<u8>, compress: bool) -> Result<Vec<u8>, S
 	Ok(public_key)
 }
 
+pub fn keygen_secp256k1() -> Result<(String, String), String> {
+	let secret_key = k256::SecretKey::random(&mut rand::thread_rng());
+	let private_pem = secret_key
+		.to_pkcs8_pem(LineEnding::LF)
+		.map_err(|e| format!("Failed to encode private key: {}", e))?
+		.to_string();
+	let public_pem = secret_key
+		.public_key()
+		.to_public_key_pem(LineEnding::LF)
+		.map_err(|e| format!("Failed to encode public key: {}", e))?;
+	Ok((private_pem, public_pem))
+}
+
+/// RFC 6979 deterministic signature of the digest, k256 always normalizes it to low-s
+pub fn sign_prehash_secp256k1(
+	secret_key: PrivateKey,
+	digest: &[u8],
+	low_s: bool,
+	sig_form: SignatureFormEnum,
+) -> Result<Vec<u8>, String> {
+	let secret_key = match secret_key {
+		PrivateKey::Pkcs8(der) => k256::SecretKey::from_pkcs8_der(&der).ok(),
+		PrivateKey::Sec1(der) => k256::SecretKey::from_sec1_der(&der).ok(),
+		PrivateKey::Scalar(scalar) => k256::SecretKey::from_slice(&scalar).ok(),
+	}
+	.ok_or("Invalid secret key (not a secp256k1 key)")?;
+
+	let sig: k256::ecdsa::Signature = k256::ecdsa::SigningKey::from(secret_key)
+		.sign_prehash(digest)
+		.map_err(|e| format!("Failed to sign: {}", e))?;
+	let sig = match low_s {
+		true => sig.normalize_s().unwrap_or(sig),
+		false => sig,
+	};
+
+	let sig = match sig_form {
+		SignatureFormEnum::Fixed => sig.to_bytes().to_vec(),
+		SignatureFormEnum::Der => sig.to_der().as_bytes().to_vec(),
+	};
+	Ok(sig)
+}
+
+/// The signature is DER or fixed r || s
+pub fn verify_prehash_secp256k1(
+	public_key: KeyInput,
+	digest: &[u8],
+	sig: &[u8],
+	low_s: bool,
+) -> Result<Verification, String> {
+	let public_key = match public_key {
+		KeyInput::Spki(der) => k256::PublicKey::from_public_key_der(&der).ok(),
+		KeyInput::Sec1(point) => k256::PublicKey::from_sec1_bytes(&point).ok(),
+	}
+	.ok_or("Invalid public key (not a secp256k1 key)")?;
+	let sig = k256::ecdsa::Signature::from_der(sig)
+		.or_else(|_| k256::ecdsa::Signature::from_slice(sig))
+		.map_err(|_| "Invalid signature (should be DER or fixed r || s)")?;
+
+	let normalized = sig.normalize_s();
+	if low_s && normalized.is_some() {
+		return Ok(Verification::HighS);
+	}
+	let sig = normalized.unwrap_or(sig);
+	match k256::ecdsa::VerifyingKey::from(public_key).verify_prehash(digest, &sig) {
+		Ok(()) => Ok(Verification::Valid),
+		Err(_) => Ok(Verification::Invalid),
+	}
+}
+
 pub fn cases() -> LinkedHashMap<&'static str, Vec<Case>> {
 	vec![
         ("ec_gk",
@@ -148,5 +221,43 @@ pub fn cases() -> LinkedHashMap<&'static str, Vec<Case>> {
                  since: "0.7.0".to_string(),
              },
          ]),
+        ("ecdsa_sign",
+         vec![
+             Case {
+                 desc: "Secp256k1, low-s (RFC 6979, message Satoshi Nakamoto)".to_string(),
+                 input: vec!["-c", "secp256k1", "--key", "0x0000000000000000000000000000000000000000000000000000000000000001", "Satoshi Nakamoto"].into_iter().map(Into::into).collect(),
+                 output: vec!["0x934b1ea10a4b3c1757e2b0c017d0b6143ce3c9a7e6a4a49860d7a6ab210ee3d82442ce9d2b916064108014783e923ec36b49743e2ffa1c4496f01a512aafd9e5"].into_iter().map(Into::into).collect(),
+                 is_example: true,
+                 is_test: true,
+                 since: "0.17.0".to_string(),
+             },
+             Case {
+                 desc: "Secp256k1 DER signature form".to_string(),
+                 input: vec!["-c", "secp256k1", "--key", "0x0000000000000000000000000000000000000000000000000000000000000001", "-f", "der", "Satoshi Nakamoto"].into_iter().map(Into::into).collect(),
+                 output: vec!["0x3045022100934b1ea10a4b3c1757e2b0c017d0b6143ce3c9a7e6a4a49860d7a6ab210ee3d802202442ce9d2b916064108014783e923ec36b49743e2ffa1c4496f01a512aafd9e5"].into_iter().map(Into::into).collect(),
+                 is_example: false,
+                 is_test: true,
+                 since: "0.17.0".to_string(),
+             },
+         ]),
+        ("ecdsa_verify",
+         vec![
+             Case {
+                 desc: "Secp256k1, a high s is normalized before verifying".to_string(),
+                 input: vec!["-c", "secp256k1", "--key", "0x0279be667ef9dcbbac55a06295ce870b07029bfcdb2dce28d959f2815b16f81798", "-S", "0x934b1ea10a4b3c1757e2b0c017d0b6143ce3c9a7e6a4a49860d7a6ab210ee3d8dbbd3162d46e9f9bef7feb87c16dc13b4f6568a87f4e83f728e2443ba586675c", "Satoshi Nakamoto"].into_iter().map(Into::into).collect(),
+                 output: vec!["Valid: true"].into_iter().map(Into::into).collect(),
+                 is_example: true,
+                 is_test: true,
+                 since: "0.17.0".to_string(),
+             },
+             Case {
+                 desc: "Secp256k1, a high s is rejected with --low-s".to_string(),
+                 input: vec!["-c", "secp256k1", "--key", "0x0279be667ef9dcbbac55a06295ce870b07029bfcdb2dce28d959f2815b16f81798", "--low-s", "-S", "0x934b1ea10a4b3c1757e2b0c017d0b6143ce3c9a7e6a4a49860d7a6ab210ee3d8dbbd3162d46e9f9bef7feb87c16dc13b4f6568a87f4e83f728e2443ba586675c", "Satoshi Nakamoto"].into_iter().map(Into::into).collect(),
+                 output: vec!["Valid: false", "Error: high s, not normalized (--low-s)"].into_iter().map(Into::into).collect(),
+                 is_example: false,
+                 is_test: true,
+                 since: "0.17.0".to_string(),
+             },
+         ]),
     ].into_iter().collect()
 }
@@ -0,0 +1,291 @@
+use crate::modules::{base, exit_code, Command, Error, Module, Output};
+use clap::{Arg, ArgMatches, SubCommand};
+use sha3::Digest;
+
+pub fn module<'a, 'b>() -> Module<'a, 'b> {
+	Module {
+		desc: "Ethereum address checksum (EIP-55)".to_string(),
+		commands: commands(),
+		get_cases: cases::cases,
+	}
+}
+
+pub fn commands<'a, 'b>() -> Vec<Command<'a, 'b>> {
+	vec![Command {
+		app: SubCommand::with_name("eth_checksum")
+			.about("EIP-55 checksum of an Ethereum address, or verify it (exit code: 0 valid, 4 invalid, 10 no checksum)")
+			.arg(
+				Arg::with_name("verify")
+					.long("verify")
+					.help("Verify the checksum of the case of the address rather than apply it"),
+			)
+			.arg(Arg::with_name("INPUT").required(false).index(1))
+			.arg(base::file_arg()),
+		f: eth_checksum,
+		serial: false,
+		pure: true,
+	}]
+}
+
+fn eth_checksum(matches: &ArgMatches) -> Result<Output, Error> {
+	let input = base::input_string(matches)?;
+	let address = input.trim();
+	let digits = address_digits(address)?;
+	let checksummed = to_checksum(digits);
+
+	if !matches.is_present("verify") {
+		return Ok(Output::Lines(vec![checksummed]));
+	}
+
+	// An address whose checksum is in a single case is valid in that case, otherwise
+	// a single case means no checksum was applied
+	let error = if address[2..] == checksummed[2..] {
+		None
+	} else if digits == digits.to_lowercase() || digits == digits.to_uppercase() {
+		Some((
+			"no checksum present (all lowercase or all uppercase)".to_string(),
+			exit_code::NO_CHECKSUM,
+		))
+	} else {
+		Some((
+			format!("checksum mismatch, expected {}", checksummed),
+			exit_code::INVALID,
+		))
+	};
+
+	match error {
+		None => {
+			base::set_exit_code(0);
+			Ok(Output::Lines(vec!["Valid: true".to_string()]))
+		}
+		Some((error, code)) => {
+			base::set_exit_code(code);
+			Ok(Output::Lines(vec![
+				"Valid: false".to_string(),
+				format!("Error: {}", error),
+			]))
+		}
+	}
+}
+
+/// The 40 hex digits of a 0x address, in the case given
+fn address_digits(address: &str) -> Result<&str, Error> {
+	let digits = address.strip_prefix("0x").ok_or_else(|| {
+		Error::invalid_input("Invalid address: missing the 0x prefix".to_string())
+	})?;
+	if let Some((i, c)) = digits
+		.chars()
+		.enumerate()
+		.find(|(_, c)| !c.is_ascii_hexdigit())
+	{
+		return Err(Error::invalid_input(format!(
+			"Invalid address: non-hex char '{}' at position {} after 0x",
+			c,
+			i + 1
+		)));
+	}
+	if digits.len() != 40 {
+		return Err(Error::invalid_input(format!(
+			"Invalid address: expected 40 hex digits after 0x, got {}",
+			digits.len()
+		)));
+	}
+	Ok(digits)
+}
+
+/// Uppercase the letters whose nibble of the keccak-256 of the lowercase hex is 8 or more
+fn to_checksum(digits: &str) -> String {
+	let lowercase = digits.to_lowercase();
+	let mut hasher = sha3::Keccak256::default();
+	hasher.input(lowercase.as_bytes());
+	let hash = hasher.result();
+
+	let checksummed = lowercase
+		.chars()
+		.enumerate()
+		.map(|(i, c)| {
+			let nibble = (hash[i / 2] >> (4 * (1 - i % 2))) & 0x0f;
+			match nibble >= 8 {
+				true => c.to_ascii_uppercase(),
+				false => c,
+			}
+		})
+		.collect::<String>();
+	format!("0x{}", checksummed)
+}
+
+mod cases {
+	use crate::modules::Case;
+	use linked_hash_map::LinkedHashMap;
+
+	pub fn cases() -> LinkedHashMap<&'static str, Vec<Case>> {
+		vec![(
+			"eth_checksum",
+			vec![
+				Case {
+					desc: "Apply the checksum".to_string(),
+					input: vec!["0x5aaeb6053f3e94c9b9a09f33669435e7ef1beaed"]
+						.into_iter()
+						.map(Into::into)
+						.collect(),
+					output: vec!["0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAed"]
+						.into_iter()
+						.map(Into::into)
+						.collect(),
+					is_example: true,
+					is_test: true,
+					since: "0.17.0".to_string(),
+				},
+				Case {
+					desc: "EIP-55 example, all uppercase".to_string(),
+					input: vec!["0x52908400098527886e0f7030069857d2e4169ee7"]
+						.into_iter()
+						.map(Into::into)
+						.collect(),
+					output: vec!["0x52908400098527886E0F7030069857D2E4169EE7"]
+						.into_iter()
+						.map(Into::into)
+						.collect(),
+					is_example: false,
+					is_test: true,
+					since: "0.17.0".to_string(),
+				},
+				Case {
+					desc: "EIP-55 example, all lowercase".to_string(),
+					input: vec!["0xDE709F2102306220921060314715629080E2FB77"]
+						.into_iter()
+						.map(Into::into)
+						.collect(),
+					output: vec!["0xde709f2102306220921060314715629080e2fb77"]
+						.into_iter()
+						.map(Into::into)
+						.collect(),
+					is_example: false,
+					is_test: true,
+					since: "0.17.0".to_string(),
+				},
+				Case {
+					desc: "Verify the checksum".to_string(),
+					input: vec!["--verify", "0xfB6916095ca1df60bB79Ce92cE3Ea74c37c5d359"]
+						.into_iter()
+						.map(Into::into)
+						.collect(),
+					output: vec!["Valid: true"].into_iter().map(Into::into).collect(),
+					is_example: true,
+					is_test: true,
+					since: "0.17.0".to_string(),
+				},
+				Case {
+					desc: "A wrong case".to_string(),
+					input: vec!["--verify", "0xdbF03B407c01E7cD3CBea99509d93f8DDDC8C6Fb"]
+						.into_iter()
+						.map(Into::into)
+						.collect(),
+					output: vec![
+						"Valid: false",
+						"Error: checksum mismatch, expected 0xdbF03B407c01E7cD3CBea99509d93f8DDDC8C6FB",
+					]
+					.into_iter()
+					.map(Into::into)
+					.collect(),
+					is_example: true,
+					is_test: true,
+					since: "0.17.0".to_string(),
+				},
+				Case {
+					desc: "No checksum".to_string(),
+					input: vec!["--verify", "0xd1220a0cf47c7b9be7a2e6ba89f429762e7b9adb"]
+						.into_iter()
+						.map(Into::into)
+						.collect(),
+					output: vec![
+						"Valid: false",
+						"Error: no checksum present (all lowercase or all uppercase)",
+					]
+					.into_iter()
+					.map(Into::into)
+					.collect(),
+					is_example: false,
+					is_test: true,
+					since: "0.17.0".to_string(),
+				},
+			],
+		)]
+		.into_iter()
+		.collect()
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::modules::base::test::test_module;
+
+	#[test]
+	fn test_cases() {
+		test_module(module());
+	}
+
+	#[test]
+	fn test_eip55() {
+		for address in [
+			"0x52908400098527886E0F7030069857D2E4169EE7",
+			"0x8617E340B3D01FA5F11F306F4090FD50E238070D",
+			"0xde709f2102306220921060314715629080e2fb77",
+			"0x27b1fdb04752bbc536007a920d24acb045561c26",
+			"0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAed",
+			"0xfB6916095ca1df60bB79Ce92cE3Ea74c37c5d359",
+			"0xdbF03B407c01E7cD3CBea99509d93f8DDDC8C6FB",
+			"0xD1220A0cf47c7B9Be7A2E6BA89F429762e7b9aDb",
+		] {
+			assert_eq!(to_checksum(&address[2..]), address);
+		}
+	}
+
+	#[test]
+	fn test_verify_exit_code() {
+		let verify = |address: &str| {
+			let matches = commands()[0].app.clone().get_matches_from(vec![
+				"eth_checksum",
+				"--verify",
+				address,
+			]);
+			eth_checksum(&matches).unwrap();
+			base::exit_code()
+		};
+		assert_eq!(verify("0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAed"), 0);
+		assert_eq!(
+			verify("0x5AAeb6053F3E94C9b9A09f33669435E7Ef1BeAed"),
+			exit_code::INVALID
+		);
+		assert_eq!(
+			verify("0x5AAEB6053F3E94C9B9A09F33669435E7EF1BEAED"),
+			exit_code::NO_CHECKSUM
+		);
+		// All uppercase is the checksum of this one
+		assert_eq!(verify("0x52908400098527886E0F7030069857D2E4169EE7"), 0);
+	}
+
+	#[test]
+	fn test_invalid_address() {
+		for (address, error) in [
+			(
+				"5aaeb6053f3e94c9b9a09f33669435e7ef1beaed",
+				"Invalid address: missing the 0x prefix",
+			),
+			(
+				"0x5aaeb6053f3e94c9b9a09f33669435e7ef1bea",
+				"Invalid address: expected 40 hex digits after 0x, got 38",
+			),
+			(
+				"0x5aaeb6053g3e94c9b9a09f33669435e7ef1beaed",
+				"Invalid address: non-hex char 'g' at position 10 after 0x",
+			),
+		] {
+			assert_eq!(
+				address_digits(address),
+				Err(Error::invalid_input(error.to_string()))
+			);
+		}
+	}
+}
@@ -1,12 +1,18 @@
 use crate::modules::base::Hex;
-use crate::modules::{base, Command, Module};
+use crate::modules::{base, exit_code, Command, Error, Module, Output};
+use base64::{engine::general_purpose, Engine as _};
 use blake3;
 use clap::{Arg, ArgMatches, SubCommand};
-use crc::crc32;
+use crc::{Crc, CRC_32_ISO_HDLC};
 use lazy_static::lazy_static;
 use ring::digest::{Context, SHA1_FOR_LEGACY_USE_ONLY};
+use serde_json::{json, Map, Value};
 use sha2::{Digest, Sha224, Sha256, Sha384, Sha512, Sha512Trunc224, Sha512Trunc256};
 use std::collections::HashMap;
+use std::fs::{self, File};
+use std::io::{self, Read};
+use std::iter;
+use std::path::{Path, PathBuf};
 use yogcrypt::sm3::sm3_enc;
 
 pub fn module<'a, 'b>() -> Module<'a, 'b> {
@@ -168,40 +174,191 @@ lazy_static! {
 			.join("\n");
 }
 
+/// Algorithms of s2hash, named like sha256sum and friends, in the order of --all
+const STREAM_ALGORITHMS: [&str; 10] = [
+	"md5", "sha1", "sha224", "sha256", "sha384", "sha512", "sha3-256", "sha3-512", "blake2b",
+	"blake3",
+];
+
+/// Tags of the BSD-style lines of a sums file, e.g. `SHA256 (file) = digest`
+const SUMS_TAGS: [(&str, &str); 10] = [
+	("md5", "MD5"),
+	("sha1", "SHA1"),
+	("sha224", "SHA224"),
+	("sha256", "SHA256"),
+	("sha384", "SHA384"),
+	("sha512", "SHA512"),
+	("sha3-256", "SHA3-256"),
+	("sha3-512", "SHA3-512"),
+	("blake2b", "BLAKE2b"),
+	("blake3", "BLAKE3"),
+];
+
+/// Algorithms of Subresource Integrity, weakest first
+const SRI_ALGORITHMS: [&str; 3] = ["sha256", "sha384", "sha512"];
+
+/// Size of the chunks s2hash reads its input in
+const CHUNK_SIZE: usize = 64 * 1024;
+
 pub fn commands<'a, 'b>() -> Vec<Command<'a, 'b>> {
-	vec![Command {
-		app: SubCommand::with_name("hash")
-			.about("Hex to hash")
-			.arg(
-				Arg::with_name("ALGORITHM")
-					.long("algo")
-					.short("a")
-					.help(&ALGORITHM_HELP)
-					.takes_value(true)
-					.required(true),
-			)
-			.arg(
-				Arg::with_name("KEY")
-					.long("key")
-					.short("k")
-					.help("Key for Blake2b")
-					.takes_value(true)
-					.required(false),
-			)
-			.arg(
-				Arg::with_name("SEED")
-					.long("seed")
-					.short("s")
-					.help("Seed for twox")
-					.takes_value(true)
-					.required(false),
-			)
-			.arg(Arg::with_name("INPUT").required(false).index(1)),
-		f: hash,
-	}]
+	vec![
+		Command {
+			app: SubCommand::with_name("hash")
+				.about("Hex to hash")
+				.arg(
+					Arg::with_name("ALGORITHM")
+						.long("algo")
+						.visible_alias("algorithm")
+						.short("a")
+						.help(&ALGORITHM_HELP)
+						.takes_value(true)
+						.required(true),
+				)
+				.arg(
+					Arg::with_name("KEY")
+						.long("key")
+						.short("k")
+						.help("Key for Blake2b")
+						.takes_value(true)
+						.required(false),
+				)
+				.arg(
+					Arg::with_name("SEED")
+						.long("seed")
+						.short("s")
+						.help("Seed for twox")
+						.takes_value(true)
+						.required(false),
+				)
+				.arg(
+					Arg::with_name("BASE64")
+						.long("base64")
+						.help("Output as base64 instead of hex"),
+				)
+				.arg(Arg::with_name("INPUT").required(false).index(1))
+				.arg(base::file_arg()),
+			f: hash,
+			serial: false,
+			pure: true,
+		},
+		Command {
+			app: SubCommand::with_name("s2hash")
+				.about("UTF-8 string or file to hash")
+				.arg(
+					Arg::with_name("ALGORITHM")
+						.long("algorithm")
+						.short("a")
+						.help("Hash algorithm")
+						.possible_values(&STREAM_ALGORITHMS)
+						.takes_value(true)
+						.required_unless("ALL")
+						.conflicts_with("ALL"),
+				)
+				.arg(
+					Arg::with_name("ALL")
+						.long("all")
+						.help("Print the digest of every algorithm, to identify an unknown hash"),
+				)
+				.arg(
+					Arg::with_name("B64")
+						.long("b64")
+						.help("Output as base64 instead of hex"),
+				)
+				.arg(
+					Arg::with_name("UPPERCASE")
+						.long("uppercase")
+						.short("u")
+						.help("Output uppercase hex")
+						.conflicts_with("B64"),
+				)
+				.arg(Arg::with_name("INPUT").required(false).index(1))
+				.arg(base::file_arg()),
+			f: s2hash,
+			serial: false,
+			pure: true,
+		},
+		Command {
+			app: SubCommand::with_name("sums_verify")
+				.about("Verify the files of a sums file, e.g. SHA256SUMS")
+				.arg(
+					Arg::with_name("ALGORITHM")
+						.long("algorithm")
+						.short("a")
+						.help("Hash algorithm, by default from the BSD tag or the digest length")
+						.possible_values(&STREAM_ALGORITHMS)
+						.takes_value(true),
+				)
+				.arg(Arg::with_name("INPUT").required(false).index(1))
+				.arg(
+					base::file_arg()
+						.help("Read the sums from file, the files are relative to its directory"),
+				),
+			f: sums_verify,
+			serial: false,
+			pure: true,
+		},
+		Command {
+			app: SubCommand::with_name("sums_create")
+				.about("Create a sums file of files and directories")
+				.arg(
+					Arg::with_name("ALGORITHM")
+						.long("algorithm")
+						.short("a")
+						.help("Hash algorithm")
+						.possible_values(&STREAM_ALGORITHMS)
+						.default_value("sha256")
+						.takes_value(true),
+				)
+				.arg(
+					Arg::with_name("TAG")
+						.long("tag")
+						.help("Write BSD-style lines: SHA256 (file) = digest"),
+				)
+				.arg(
+					Arg::with_name("PATHS")
+						.help("Files, directories are walked")
+						.multiple(true)
+						.required(true)
+						.index(1),
+				),
+			f: sums_create,
+			serial: false,
+			pure: true,
+		},
+		Command {
+			app: SubCommand::with_name("sri")
+				.about("Subresource Integrity of a file, the sha384-<base64> of an integrity attribute")
+				.arg(
+					Arg::with_name("ALGORITHM")
+						.long("algorithm")
+						.short("a")
+						.help("Hash algorithm, sha384 by default")
+						.possible_values(&SRI_ALGORITHMS)
+						.takes_value(true)
+						.conflicts_with("ALL"),
+				)
+				.arg(
+					Arg::with_name("ALL")
+						.long("all")
+						.help("Print a line per algorithm"),
+				)
+				.arg(
+					Arg::with_name("VERIFY")
+						.long("verify")
+						.help("Verify the input against an integrity value of space-separated hashes (exit code: 0 valid, 4 invalid)")
+						.takes_value(true)
+						.conflicts_with_all(&["ALGORITHM", "ALL"]),
+				)
+				.arg(Arg::with_name("INPUT").required(false).index(1))
+				.arg(base::file_arg()),
+			f: sri,
+			serial: false,
+			pure: true,
+		},
+	]
 }
 
-fn hash(matches: &ArgMatches) -> Result<Vec<String>, String> {
+fn hash(matches: &ArgMatches) -> Result<Output, Error> {
 	let input = base::input_string(matches)?;
 
 	let input: Vec<u8> = input.parse::<Hex>().map_err(|_| "Convert failed")?.into();
@@ -226,19 +383,403 @@ fn hash(matches: &ArgMatches) -> Result<Vec<String>, String> {
 				(f)(input, seed)?
 			}
 		},
-		None => return Err("Invalid algorithm".to_string()),
+		None => return Err("Invalid algorithm".into()),
 	};
 
-	let result = Hex::from(result).into();
+	let result = if matches.is_present("BASE64") {
+		general_purpose::STANDARD.encode(result)
+	} else {
+		Hex::from(result).into()
+	};
 
-	Ok(vec![result])
+	Ok(Output::Lines(vec![result]))
 }
 
-fn md5(data: Vec<u8>) -> Result<Vec<u8>, String> {
+fn s2hash(matches: &ArgMatches) -> Result<Output, Error> {
+	let names = match matches.value_of("ALGORITHM") {
+		Some(name) => vec![name],
+		None => STREAM_ALGORITHMS.to_vec(),
+	};
+	let mut hashers = names
+		.iter()
+		.map(|name| StreamHasher::new(name))
+		.collect::<Result<Vec<_>, _>>()?;
+
+	// Streamed, so a large --file is not read into memory
+	hash_reader(base::input_reader(matches)?, &mut hashers)?;
+
+	let digests = hashers.into_iter().map(|hasher| {
+		let digest = hasher.finish();
+		if matches.is_present("B64") {
+			general_purpose::STANDARD.encode(digest)
+		} else if matches.is_present("UPPERCASE") {
+			hex::encode_upper(digest)
+		} else {
+			hex::encode(digest)
+		}
+	});
+	let digests = names.into_iter().zip(digests).collect::<Vec<_>>();
+
+	if matches.is_present("json") {
+		let digests = digests
+			.into_iter()
+			.map(|(name, digest)| (name.to_string(), Value::String(digest)))
+			.collect::<Map<_, _>>();
+		return Ok(Output::Structured(Value::Object(digests)));
+	}
+
+	let result = match matches.is_present("ALL") {
+		true => digests
+			.into_iter()
+			.map(|(name, digest)| format!("{}: {}", name, digest))
+			.collect(),
+		false => digests.into_iter().map(|(_, digest)| digest).collect(),
+	};
+
+	Ok(Output::Lines(result))
+}
+
+fn hash_reader<R: Read>(mut reader: R, hashers: &mut [StreamHasher]) -> Result<(), Error> {
+	let mut buffer = vec![0u8; CHUNK_SIZE];
+	loop {
+		let n = match reader.read(&mut buffer) {
+			Ok(0) => return Ok(()),
+			Ok(n) => n,
+			Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+			Err(e) => return Err(Error::io("Failed to read input", e)),
+		};
+		for hasher in hashers.iter_mut() {
+			hasher.update(&buffer[..n]);
+		}
+	}
+}
+
+fn sums_verify(matches: &ArgMatches) -> Result<Output, Error> {
+	let sums = base::input_string(matches)?;
+	let dir = matches
+		.value_of("FILE")
+		.and_then(|x| Path::new(x).parent())
+		.map(Path::to_path_buf)
+		.unwrap_or_default();
+
+	let mut results = vec![];
+	for (i, line) in sums.lines().enumerate() {
+		let line = line.trim_end();
+		if line.is_empty() || line.starts_with('#') {
+			continue;
+		}
+		let (tag, digest, file) = parse_sums_line(line).ok_or_else(|| {
+			Error::invalid_input(format!("Invalid sums line {}: {}", i + 1, line))
+		})?;
+		let algorithm = match (matches.value_of("ALGORITHM"), tag) {
+			(Some(algorithm), _) => algorithm,
+			(None, Some(tag)) => tag,
+			(None, None) => algorithm_of_len(digest.len()).ok_or_else(|| {
+				Error::invalid_input(format!(
+					"Unknown algorithm of the {} hex digits digest on line {}",
+					digest.len(),
+					i + 1
+				))
+				.with_hint("Give it with --algorithm")
+			})?,
+		};
+
+		let path = dir.join(file);
+		let status = match File::open(&path) {
+			Ok(f) => {
+				let mut hashers = [StreamHasher::new(algorithm)?];
+				hash_reader(f, &mut hashers)?;
+				let [hasher] = hashers;
+				match hex::encode(hasher.finish()) == digest {
+					true => "OK",
+					false => "FAILED",
+				}
+			}
+			Err(e) if e.kind() == io::ErrorKind::NotFound => "MISSING",
+			Err(e) => {
+				return Err(Error::io(
+					&format!("Failed to read file {}", path.display()),
+					e,
+				))
+			}
+		};
+		results.push((file, algorithm, status));
+	}
+
+	let count = |status: &str| results.iter().filter(|(_, _, x)| *x == status).count();
+	let (ok, failed, missing) = (count("OK"), count("FAILED"), count("MISSING"));
+	if failed + missing > 0 {
+		base::set_exit_code(exit_code::INVALID);
+	}
+
+	if matches.is_present("json") {
+		let files = results
+			.into_iter()
+			.map(
+				|(file, algorithm, status)| json!({ "file": file, "algorithm": algorithm, "status": status }),
+			)
+			.collect::<Vec<_>>();
+		return Ok(Output::Structured(json!({
+			"files": files,
+			"ok": ok,
+			"failed": failed,
+			"missing": missing,
+		})));
+	}
+
+	let summary = format!("OK: {}, FAILED: {}, MISSING: {}", ok, failed, missing);
+	Ok(Output::Lines(
+		results
+			.into_iter()
+			.map(|(file, _, status)| format!("{}: {}", file, status))
+			.chain(iter::once(summary))
+			.collect(),
+	))
+}
+
+fn sums_create(matches: &ArgMatches) -> Result<Output, Error> {
+	let algorithm = matches.value_of("ALGORITHM").ok_or("Invalid algorithm")?;
+	let tag = SUMS_TAGS
+		.iter()
+		.find(|(name, _)| *name == algorithm)
+		.map(|(_, tag)| *tag)
+		.ok_or("Invalid algorithm")?;
+
+	let mut files = vec![];
+	for path in matches.values_of("PATHS").into_iter().flatten() {
+		walk(Path::new(path), &mut files)?;
+	}
+
+	let mut sums = vec![];
+	for path in files {
+		let f = File::open(&path)
+			.map_err(|e| Error::io(&format!("Failed to read file {}", path.display()), e))?;
+		let mut hashers = [StreamHasher::new(algorithm)?];
+		hash_reader(f, &mut hashers)?;
+		let [hasher] = hashers;
+		sums.push((path.display().to_string(), hex::encode(hasher.finish())));
+	}
+
+	if matches.is_present("json") {
+		let sums = sums
+			.into_iter()
+			.map(|(file, digest)| json!({ "file": file, "digest": digest }))
+			.collect();
+		return Ok(Output::Structured(Value::Array(sums)));
+	}
+
+	Ok(Output::Lines(
+		sums.into_iter()
+			.map(|(file, digest)| match matches.is_present("TAG") {
+				true => format!("{} ({}) = {}", tag, file, digest),
+				false => format!("{}  {}", digest, file),
+			})
+			.collect(),
+	))
+}
+
+fn sri(matches: &ArgMatches) -> Result<Output, Error> {
+	if let Some(integrity) = matches.value_of("VERIFY") {
+		return sri_verify(matches, integrity);
+	}
+
+	let names = match matches.is_present("ALL") {
+		true => SRI_ALGORITHMS.to_vec(),
+		false => vec![matches.value_of("ALGORITHM").unwrap_or("sha384")],
+	};
+	let digests = sri_digests(matches, &names)?;
+
+	Ok(Output::Lines(
+		names
+			.into_iter()
+			.zip(digests)
+			.map(|(name, digest)| format!("{}-{}", name, digest))
+			.collect(),
+	))
+}
+
+/// As the SRI spec, only the hashes of the strongest algorithm of the integrity value are
+/// compared, any of them matching is enough, and unknown algorithms and the options after
+/// `?` are ignored
+fn sri_verify(matches: &ArgMatches, integrity: &str) -> Result<Output, Error> {
+	let hashes = integrity
+		.split_whitespace()
+		.filter_map(|token| {
+			let (name, digest) = token.split_once('-')?;
+			let strength = SRI_ALGORITHMS.iter().position(|x| *x == name)?;
+			let digest = digest.split('?').next().unwrap_or_default();
+			Some((strength, digest))
+		})
+		.collect::<Vec<_>>();
+	let strongest = hashes
+		.iter()
+		.map(|(strength, _)| *strength)
+		.max()
+		.ok_or_else(|| {
+			Error::invalid_input("Invalid integrity: no sha256, sha384 or sha512 hash".to_string())
+		})?;
+
+	let name = SRI_ALGORITHMS[strongest];
+	let digest = sri_digests(matches, &[name])?.remove(0);
+	let valid = hashes
+		.iter()
+		.any(|(strength, x)| *strength == strongest && *x == digest);
+
+	if valid {
+		base::set_exit_code(0);
+		return Ok(Output::Lines(vec!["Valid: true".to_string()]));
+	}
+	base::set_exit_code(exit_code::INVALID);
+	Ok(Output::Lines(vec![
+		"Valid: false".to_string(),
+		format!("Error: {} mismatch, the input is {}-{}", name, name, digest),
+	]))
+}
+
+/// The base64 digests of the input, read once for all the algorithms
+fn sri_digests(matches: &ArgMatches, names: &[&str]) -> Result<Vec<String>, Error> {
+	let mut hashers = names
+		.iter()
+		.map(|name| StreamHasher::new(name))
+		.collect::<Result<Vec<_>, _>>()?;
+	hash_reader(base::input_reader(matches)?, &mut hashers)?;
+	Ok(hashers
+		.into_iter()
+		.map(|hasher| general_purpose::STANDARD.encode(hasher.finish()))
+		.collect())
+}
+
+/// The BSD tag, the lowercase digest and the file of a line of a sums file, either
+/// `<digest>  <file>`, `<digest> *<file>` for a binary file, or `<TAG> (<file>) = <digest>`
+fn parse_sums_line(line: &str) -> Option<(Option<&'static str>, String, &str)> {
+	let is_hex = |x: &str| !x.is_empty() && x.chars().all(|c| c.is_ascii_hexdigit());
+
+	if let Some((head, digest)) = line.rsplit_once(") = ") {
+		if let Some((tag, file)) = head.split_once(" (") {
+			let tag = SUMS_TAGS
+				.iter()
+				.find(|(_, x)| x.eq_ignore_ascii_case(tag))
+				.map(|(name, _)| *name)?;
+			return match is_hex(digest) {
+				true => Some((Some(tag), digest.to_lowercase(), file)),
+				false => None,
+			};
+		}
+	}
+
+	let (digest, rest) = line.split_once(' ')?;
+	let file = rest.strip_prefix(' ').or_else(|| rest.strip_prefix('*'))?;
+	match is_hex(digest) && !file.is_empty() {
+		true => Some((None, digest.to_lowercase(), file)),
+		false => None,
+	}
+}
+
+/// The algorithm of a sums file without a BSD tag, as sha256sum and friends name it
+fn algorithm_of_len(len: usize) -> Option<&'static str> {
+	let algorithm = match len {
+		32 => "md5",
+		40 => "sha1",
+		56 => "sha224",
+		64 => "sha256",
+		96 => "sha384",
+		128 => "sha512",
+		_ => return None,
+	};
+	Some(algorithm)
+}
+
+/// The files of a path, a directory is walked in the order of the names
+fn walk(path: &Path, files: &mut Vec<PathBuf>) -> Result<(), Error> {
+	if !path.is_dir() {
+		files.push(path.to_path_buf());
+		return Ok(());
+	}
+	let mut entries = fs::read_dir(path)
+		.and_then(|x| {
+			x.map(|x| x.map(|x| x.path()))
+				.collect::<Result<Vec<_>, _>>()
+		})
+		.map_err(|e| Error::io(&format!("Failed to read dir {}", path.display()), e))?;
+	entries.sort();
+	for entry in entries {
+		walk(&entry, files)?;
+	}
+	Ok(())
+}
+
+/// Incremental hasher of s2hash, fed the input chunk by chunk
+enum StreamHasher {
+	Md5(md5::Context),
+	Sha1(Context),
+	Sha224(Sha224),
+	Sha256(Sha256),
+	Sha384(Sha384),
+	Sha512(Sha512),
+	Sha3_256(sha3::Sha3_256),
+	Sha3_512(sha3::Sha3_512),
+	Blake2b(blake2b_simd::State),
+	Blake3(Box<blake3::Hasher>),
+}
+
+impl StreamHasher {
+	fn new(name: &str) -> Result<Self, Error> {
+		let hasher = match name {
+			"md5" => StreamHasher::Md5(md5::Context::new()),
+			"sha1" => StreamHasher::Sha1(Context::new(&SHA1_FOR_LEGACY_USE_ONLY)),
+			"sha224" => StreamHasher::Sha224(Sha224::new()),
+			"sha256" => StreamHasher::Sha256(Sha256::new()),
+			"sha384" => StreamHasher::Sha384(Sha384::new()),
+			"sha512" => StreamHasher::Sha512(Sha512::new()),
+			"sha3-256" => StreamHasher::Sha3_256(sha3::Sha3_256::default()),
+			"sha3-512" => StreamHasher::Sha3_512(sha3::Sha3_512::default()),
+			"blake2b" => StreamHasher::Blake2b(blake2b_simd::Params::new().to_state()),
+			"blake3" => StreamHasher::Blake3(Box::new(blake3::Hasher::new())),
+			_ => return Err(Error::Usage(format!("Invalid algorithm: {}", name))),
+		};
+		Ok(hasher)
+	}
+
+	fn update(&mut self, data: &[u8]) {
+		match self {
+			StreamHasher::Md5(x) => x.consume(data),
+			StreamHasher::Sha1(x) => x.update(data),
+			StreamHasher::Sha224(x) => x.input(data),
+			StreamHasher::Sha256(x) => x.input(data),
+			StreamHasher::Sha384(x) => x.input(data),
+			StreamHasher::Sha512(x) => x.input(data),
+			StreamHasher::Sha3_256(x) => x.input(data),
+			StreamHasher::Sha3_512(x) => x.input(data),
+			StreamHasher::Blake2b(x) => {
+				x.update(data);
+			}
+			StreamHasher::Blake3(x) => {
+				x.update(data);
+			}
+		}
+	}
+
+	fn finish(self) -> Vec<u8> {
+		match self {
+			StreamHasher::Md5(x) => x.compute().0.to_vec(),
+			StreamHasher::Sha1(x) => x.finish().as_ref().to_vec(),
+			StreamHasher::Sha224(x) => x.result().to_vec(),
+			StreamHasher::Sha256(x) => x.result().to_vec(),
+			StreamHasher::Sha384(x) => x.result().to_vec(),
+			StreamHasher::Sha512(x) => x.result().to_vec(),
+			StreamHasher::Sha3_256(x) => x.result().to_vec(),
+			StreamHasher::Sha3_512(x) => x.result().to_vec(),
+			StreamHasher::Blake2b(x) => x.finalize().as_bytes().to_vec(),
+			StreamHasher::Blake3(x) => x.finalize().as_bytes().to_vec(),
+		}
+	}
+}
+
+pub(crate) fn md5(data: Vec<u8>) -> Result<Vec<u8>, String> {
 	Ok(md5::compute(data).0.to_vec())
 }
 
-fn sha1(data: Vec<u8>) -> Result<Vec<u8>, String> {
+pub(crate) fn sha1(data: Vec<u8>) -> Result<Vec<u8>, String> {
 	let mut context = Context::new(&SHA1_FOR_LEGACY_USE_ONLY);
 	context.update(&data);
 	let result = context.finish().as_ref().to_vec();
@@ -351,7 +892,7 @@ fn ripemd_160(data: Vec<u8>) -> Result<Vec<u8>, String> {
 }
 
 fn crc_32(data: Vec<u8>) -> Result<Vec<u8>, String> {
-	let result = crc32::checksum_ieee(&data);
+	let result = Crc::<u32>::new(&CRC_32_ISO_HDLC).checksum(&data);
 	let result = result.to_be_bytes().to_vec();
 
 	Ok(result)
@@ -452,6 +993,14 @@ mod cases {
 					 is_test: true,
 					 since: "0.2.0".to_string(),
 				 },
+				 Case {
+					 desc: "SHA-2 256 as base64".to_string(),
+					 input: vec!["--algorithm", "sha2_256", "--base64", "0x616263"].into_iter().map(Into::into).collect(),
+					 output: vec!["ungWv48Bz+pBQUDeXa4iI7ADYaOWF3qctBD/YfIAFa0="].into_iter().map(Into::into).collect(),
+					 is_example: true,
+					 is_test: true,
+					 since: "0.17.0".to_string(),
+				 },
 				 Case {
 					 desc: "SHA-2 384".to_string(),
 					 input: vec!["-a", "sha2_384", "0x616263"].into_iter().map(Into::into).collect(),
@@ -653,6 +1202,178 @@ mod cases {
 					 since: "0.10.0".to_string(),
 				 },
 			 ]),
+			("s2hash",
+			 vec![
+				 Case {
+					 desc: "MD5".to_string(),
+					 input: vec!["-a", "md5", "abc"].into_iter().map(Into::into).collect(),
+					 output: vec!["900150983cd24fb0d6963f7d28e17f72"].into_iter().map(Into::into).collect(),
+					 is_example: false,
+					 is_test: true,
+					 since: "0.17.0".to_string(),
+				 },
+				 Case {
+					 desc: "SHA-1".to_string(),
+					 input: vec!["-a", "sha1", "abc"].into_iter().map(Into::into).collect(),
+					 output: vec!["a9993e364706816aba3e25717850c26c9cd0d89d"].into_iter().map(Into::into).collect(),
+					 is_example: false,
+					 is_test: true,
+					 since: "0.17.0".to_string(),
+				 },
+				 Case {
+					 desc: "SHA-2 224".to_string(),
+					 input: vec!["-a", "sha224", "abc"].into_iter().map(Into::into).collect(),
+					 output: vec!["23097d223405d8228642a477bda255b32aadbce4bda0b3f7e36c9da7"].into_iter().map(Into::into).collect(),
+					 is_example: false,
+					 is_test: true,
+					 since: "0.17.0".to_string(),
+				 },
+				 Case {
+					 desc: "SHA-2 256".to_string(),
+					 input: vec!["-a", "sha256", "abc"].into_iter().map(Into::into).collect(),
+					 output: vec!["ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"].into_iter().map(Into::into).collect(),
+					 is_example: true,
+					 is_test: true,
+					 since: "0.17.0".to_string(),
+				 },
+				 Case {
+					 desc: "SHA-2 384".to_string(),
+					 input: vec!["-a", "sha384", "abc"].into_iter().map(Into::into).collect(),
+					 output: vec!["cb00753f45a35e8bb5a03d699ac65007272c32ab0eded1631a8b605a43ff5bed8086072ba1e7cc2358baeca134c825a7"].into_iter().map(Into::into).collect(),
+					 is_example: false,
+					 is_test: true,
+					 since: "0.17.0".to_string(),
+				 },
+				 Case {
+					 desc: "SHA-2 512".to_string(),
+					 input: vec!["-a", "sha512", "abc"].into_iter().map(Into::into).collect(),
+					 output: vec!["ddaf35a193617abacc417349ae20413112e6fa4e89a97ea20a9eeee64b55d39a2192992a274fc1a836ba3c23a3feebbd454d4423643ce80e2a9ac94fa54ca49f"].into_iter().map(Into::into).collect(),
+					 is_example: false,
+					 is_test: true,
+					 since: "0.17.0".to_string(),
+				 },
+				 Case {
+					 desc: "SHA-3 256".to_string(),
+					 input: vec!["-a", "sha3-256", "abc"].into_iter().map(Into::into).collect(),
+					 output: vec!["3a985da74fe225b2045c172d6bd390bd855f086e3e9d525b46bfe24511431532"].into_iter().map(Into::into).collect(),
+					 is_example: false,
+					 is_test: true,
+					 since: "0.17.0".to_string(),
+				 },
+				 Case {
+					 desc: "SHA-3 512".to_string(),
+					 input: vec!["-a", "sha3-512", "abc"].into_iter().map(Into::into).collect(),
+					 output: vec!["b751850b1a57168a5693cd924b6b096e08f621827444f70d884f5d0240d2712e10e116e9192af3c91a7ec57647e3934057340b4cf408d5a56592f8274eec53f0"].into_iter().map(Into::into).collect(),
+					 is_example: false,
+					 is_test: true,
+					 since: "0.17.0".to_string(),
+				 },
+				 Case {
+					 desc: "Blake2b 512".to_string(),
+					 input: vec!["-a", "blake2b", "abc"].into_iter().map(Into::into).collect(),
+					 output: vec!["ba80a53f981c4d0d6a2797b69f12f6e94c212f14685ac4b74b12bb6fdbffa2d17d87c5392aab792dc252d5de4533cc9518d38aa8dbf1925ab92386edd4009923"].into_iter().map(Into::into).collect(),
+					 is_example: false,
+					 is_test: true,
+					 since: "0.17.0".to_string(),
+				 },
+				 Case {
+					 desc: "Blake3".to_string(),
+					 input: vec!["-a", "blake3", "abc"].into_iter().map(Into::into).collect(),
+					 output: vec!["6437b3ac38465133ffb63b75273a8db548c558465d79db03fd359c6cd5bd9d85"].into_iter().map(Into::into).collect(),
+					 is_example: false,
+					 is_test: true,
+					 since: "0.17.0".to_string(),
+				 },
+				 Case {
+					 desc: "SHA-2 256 as base64".to_string(),
+					 input: vec!["-a", "sha256", "--b64", "abc"].into_iter().map(Into::into).collect(),
+					 output: vec!["ungWv48Bz+pBQUDeXa4iI7ADYaOWF3qctBD/YfIAFa0="].into_iter().map(Into::into).collect(),
+					 is_example: true,
+					 is_test: true,
+					 since: "0.17.0".to_string(),
+				 },
+				 Case {
+					 desc: "SHA-2 256 in uppercase".to_string(),
+					 input: vec!["-a", "sha256", "-u", "abc"].into_iter().map(Into::into).collect(),
+					 output: vec!["BA7816BF8F01CFEA414140DE5DAE2223B00361A396177A9CB410FF61F20015AD"].into_iter().map(Into::into).collect(),
+					 is_example: true,
+					 is_test: true,
+					 since: "0.17.0".to_string(),
+				 },
+				 Case {
+					 desc: "Every algorithm, to identify an unknown hash".to_string(),
+					 input: vec!["--all", "abc"].into_iter().map(Into::into).collect(),
+					 output: vec!["md5: 900150983cd24fb0d6963f7d28e17f72", "sha1: a9993e364706816aba3e25717850c26c9cd0d89d", "sha224: 23097d223405d8228642a477bda255b32aadbce4bda0b3f7e36c9da7", "sha256: ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad", "sha384: cb00753f45a35e8bb5a03d699ac65007272c32ab0eded1631a8b605a43ff5bed8086072ba1e7cc2358baeca134c825a7", "sha512: ddaf35a193617abacc417349ae20413112e6fa4e89a97ea20a9eeee64b55d39a2192992a274fc1a836ba3c23a3feebbd454d4423643ce80e2a9ac94fa54ca49f", "sha3-256: 3a985da74fe225b2045c172d6bd390bd855f086e3e9d525b46bfe24511431532", "sha3-512: b751850b1a57168a5693cd924b6b096e08f621827444f70d884f5d0240d2712e10e116e9192af3c91a7ec57647e3934057340b4cf408d5a56592f8274eec53f0", "blake2b: ba80a53f981c4d0d6a2797b69f12f6e94c212f14685ac4b74b12bb6fdbffa2d17d87c5392aab792dc252d5de4533cc9518d38aa8dbf1925ab92386edd4009923", "blake3: 6437b3ac38465133ffb63b75273a8db548c558465d79db03fd359c6cd5bd9d85"].into_iter().map(Into::into).collect(),
+					 is_example: true,
+					 is_test: true,
+					 since: "0.17.0".to_string(),
+				 },
+			 ]),
+			("sums_verify",
+			 vec![
+				 Case {
+					 desc: "Verify the files of SHA256SUMS".to_string(),
+					 input: vec!["--file", "SHA256SUMS"].into_iter().map(Into::into).collect(),
+					 output: vec!["dtool-0.17.0.tar.gz: OK", "dtool-0.17.0.zip: FAILED", "OK: 1, FAILED: 1, MISSING: 0"].into_iter().map(Into::into).collect(),
+					 is_example: true,
+					 is_test: false, // Needs the files, covered by test_sums
+					 since: "0.17.0".to_string(),
+				 },
+				 Case {
+					 desc: "BSD-style line of a missing file".to_string(),
+					 input: vec!["'SHA256 (no_such_file) = e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855'"].into_iter().map(Into::into).collect(),
+					 output: vec!["no_such_file: MISSING", "OK: 0, FAILED: 0, MISSING: 1"].into_iter().map(Into::into).collect(),
+					 is_example: true,
+					 is_test: true,
+					 since: "0.17.0".to_string(),
+				 },
+			 ]),
+			("sums_create",
+			 vec![
+				 Case {
+					 desc: "SHA256SUMS of a directory".to_string(),
+					 input: vec!["dist"].into_iter().map(Into::into).collect(),
+					 output: vec!["e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855  dist/dtool-0.17.0.tar.gz"].into_iter().map(Into::into).collect(),
+					 is_example: true,
+					 is_test: false, // Needs the files, covered by test_sums
+					 since: "0.17.0".to_string(),
+				 },
+			 ]),
+			("sri",
+			 vec![
+				 Case {
+					 desc: "Integrity attribute of a script".to_string(),
+					 input: vec!["--file", "tests/data/hello.js"].into_iter().map(Into::into).collect(),
+					 output: vec!["sha384-H8BRh8j48O9oYatfu5AZzq6A9RINhZO5H16dQZngK7T62em8MUt1FLm52t+eX6xO"].into_iter().map(Into::into).collect(),
+					 is_example: true,
+					 is_test: true,
+					 since: "0.17.0".to_string(),
+				 },
+				 Case {
+					 desc: "Every algorithm".to_string(),
+					 input: vec!["--all", "--file", "tests/data/hello.js"].into_iter().map(Into::into).collect(),
+					 output: vec!["sha256-qznLcsROx4GACP2dm0UCKCzCG+HiZ1guq6ZZDob/Tng=", "sha384-H8BRh8j48O9oYatfu5AZzq6A9RINhZO5H16dQZngK7T62em8MUt1FLm52t+eX6xO", "sha512-Q2bFTOhEALkN8hOms2FKTDLy7eugP2zFZ1T8LCvX42Fp3WoNr3bjZSAHeOsHrbV1Fu9/A0EzCinRE7Af1ofPrw=="].into_iter().map(Into::into).collect(),
+					 is_example: false,
+					 is_test: true,
+					 since: "0.17.0".to_string(),
+				 },
+				 Case {
+					 desc: "Verify an integrity value of two hashes".to_string(),
+					 input: vec!["--verify", "'sha256-qznLcsROx4GACP2dm0UCKCzCG+HiZ1guq6ZZDob/Tng= sha384-H8BRh8j48O9oYatfu5AZzq6A9RINhZO5H16dQZngK7T62em8MUt1FLm52t+eX6xO'", "--file", "tests/data/hello.js"].into_iter().map(Into::into).collect(),
+					 output: vec!["Valid: true"].into_iter().map(Into::into).collect(),
+					 is_example: true,
+					 is_test: true,
+					 since: "0.17.0".to_string(),
+				 },
+				 Case {
+					 desc: "Only the strongest algorithm counts".to_string(),
+					 input: vec!["--verify", "'sha256-qznLcsROx4GACP2dm0UCKCzCG+HiZ1guq6ZZDob/Tng= sha512-ZHRvb2w='", "--file", "tests/data/hello.js"].into_iter().map(Into::into).collect(),
+					 output: vec!["Valid: false", "Error: sha512 mismatch, the input is sha512-Q2bFTOhEALkN8hOms2FKTDLy7eugP2zFZ1T8LCvX42Fp3WoNr3bjZSAHeOsHrbV1Fu9/A0EzCinRE7Af1ofPrw=="].into_iter().map(Into::into).collect(),
+					 is_example: false,
+					 is_test: true,
+					 since: "0.17.0".to_string(),
+				 },
+			 ]),
 		].into_iter().collect()
 	}
 }
@@ -660,10 +1381,159 @@ mod cases {
 #[cfg(test)]
 mod tests {
 	use super::*;
+	use crate::app;
 	use crate::modules::base::test::test_module;
 
 	#[test]
 	fn test_cases() {
 		test_module(module());
 	}
+
+	#[test]
+	fn test_hash_reader() {
+		// Spans several chunks, the last one partial
+		let data = (0..CHUNK_SIZE * 2 + 1)
+			.map(|x| (x % 251) as u8)
+			.collect::<Vec<_>>();
+		let mut hashers = vec![
+			StreamHasher::new("sha256").unwrap(),
+			StreamHasher::new("blake3").unwrap(),
+		];
+		hash_reader(io::Cursor::new(&data), &mut hashers).unwrap();
+		let digests = hashers
+			.into_iter()
+			.map(StreamHasher::finish)
+			.collect::<Vec<_>>();
+		assert_eq!(
+			digests,
+			vec![sha2_256(data.clone()).unwrap(), blake3_256(data).unwrap()]
+		);
+
+		assert!(StreamHasher::new("sha2_256").is_err());
+	}
+
+	#[test]
+	fn test_sri_verify() {
+		let verify = |integrity: &str| {
+			let matches = commands()[4].app.clone().get_matches_from(vec![
+				"sri",
+				"--verify",
+				integrity,
+				"alert('Hello, world.');",
+			]);
+			sri(&matches).map(|x| (x.render(false).unwrap()[0].clone(), base::exit_code()))
+		};
+
+		// Options and unknown algorithms are ignored
+		assert_eq!(
+			verify("md5-ZHRvb2w= sha384-H8BRh8j48O9oYatfu5AZzq6A9RINhZO5H16dQZngK7T62em8MUt1FLm52t+eX6xO?ct=application/javascript"),
+			Ok(("Valid: true".to_string(), 0))
+		);
+		// Any hash of the strongest algorithm
+		assert_eq!(
+			verify("sha384-ZHRvb2w= sha384-H8BRh8j48O9oYatfu5AZzq6A9RINhZO5H16dQZngK7T62em8MUt1FLm52t+eX6xO"),
+			Ok(("Valid: true".to_string(), 0))
+		);
+		assert_eq!(
+			verify("sha384-ZHRvb2w="),
+			Ok(("Valid: false".to_string(), exit_code::INVALID))
+		);
+		assert_eq!(
+			verify("md5-ZHRvb2w="),
+			Err(Error::invalid_input(
+				"Invalid integrity: no sha256, sha384 or sha512 hash".to_string()
+			))
+		);
+	}
+
+	#[test]
+	fn test_sums() {
+		let dir = std::env::temp_dir().join(format!("dtool_hash_sums_{}", std::process::id()));
+		fs::create_dir_all(dir.join("sub")).unwrap();
+		fs::write(dir.join("a.txt"), "abc").unwrap();
+		fs::write(dir.join("sub").join("b.txt"), "").unwrap();
+		fs::write(dir.join("c.txt"), "abc").unwrap();
+		let dir = dir.to_string_lossy().to_string();
+		let run = |i: usize, args: Vec<&str>| {
+			let app = commands()[i].app.clone().args(&app::global_args());
+			let name = app.get_name().to_string();
+			let matches = app.get_matches_from(
+				Some(name)
+					.into_iter()
+					.chain(args.into_iter().map(Into::into)),
+			);
+			(commands()[i].f)(&matches).and_then(|x| x.render(false).map_err(Error::from))
+		};
+
+		let sums = run(3, vec![&dir]).unwrap();
+		let abc = "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad";
+		let empty = "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855";
+		let path = |x: &str| Path::new(&dir).join(x).display().to_string();
+		assert_eq!(
+			sums,
+			vec![
+				format!("{}  {}", abc, path("a.txt")),
+				format!("{}  {}", abc, path("c.txt")),
+				format!("{}  {}", empty, path("sub/b.txt")),
+			]
+		);
+		assert_eq!(
+			run(3, vec!["--tag", "-a", "md5", &path("a.txt")]).unwrap(),
+			vec![format!(
+				"MD5 ({}) = 900150983cd24fb0d6963f7d28e17f72",
+				path("a.txt")
+			)]
+		);
+
+		// Relative to the sums file, with a binary marker, a BSD-style line, a corrupted
+		// file and a missing one
+		fs::write(path("c.txt"), "abd").unwrap();
+		let sums_file = path("SHA256SUMS");
+		fs::write(
+			&sums_file,
+			format!(
+				"{}  a.txt\n{} *sub/b.txt\nSHA256 (c.txt) = {}\n\n{}  d.txt\n",
+				abc.to_uppercase(),
+				empty,
+				abc,
+				abc
+			),
+		)
+		.unwrap();
+		assert_eq!(
+			run(2, vec!["--file", &sums_file]).unwrap(),
+			vec![
+				"a.txt: OK",
+				"sub/b.txt: OK",
+				"c.txt: FAILED",
+				"d.txt: MISSING",
+				"OK: 2, FAILED: 1, MISSING: 1",
+			]
+		);
+		assert_eq!(base::exit_code(), exit_code::INVALID);
+
+		// Inferred from the length unless given
+		fs::write(&sums_file, "900150983cd24fb0d6963f7d28e17f72  a.txt\n").unwrap();
+		assert_eq!(
+			run(2, vec!["--file", &sums_file]).unwrap(),
+			vec!["a.txt: OK", "OK: 1, FAILED: 0, MISSING: 0"]
+		);
+		assert_eq!(
+			run(2, vec!["-a", "blake3", "--file", &sums_file]).unwrap()[0],
+			"a.txt: FAILED"
+		);
+		assert_eq!(
+			run(2, vec!["abc  a.txt"]),
+			Err(Error::InvalidInput {
+				msg: "Unknown algorithm of the 3 hex digits digest on line 1".to_string(),
+				hint: Some("Give it with --algorithm".to_string()),
+			})
+		);
+		assert_eq!(
+			run(2, vec!["abc"]),
+			Err(Error::invalid_input("Invalid sums line 1: abc".to_string()))
+		);
+
+		fs::remove_dir_all(&dir).unwrap();
+	}
 }
@@ -1,9 +1,7 @@
 use crate::modules::base::Hex;
-use crate::modules::{base, Command, Module};
+use crate::modules::{base, Command, Error, Module, Output};
 use clap::{Arg, ArgMatches, SubCommand};
 use regex::Regex;
-use std::io;
-use std::io::Write;
 
 pub fn module<'a, 'b>() -> Module<'a, 'b> {
 	Module {
@@ -18,86 +16,94 @@ pub fn commands<'a, 'b>() -> Vec<Command<'a, 'b>> {
 		Command {
 			app: SubCommand::with_name("h2s")
 				.about("Convert hex to UTF-8 string")
-				.arg(Arg::with_name("INPUT").required(false).index(1)),
+				.arg(Arg::with_name("INPUT").required(false).index(1))
+				.arg(base::file_arg()),
 			f: h2s,
+			serial: false,
+			pure: true,
 		},
 		Command {
 			app: SubCommand::with_name("s2h")
 				.about("Convert UTF-8 string to hex")
-				.arg(Arg::with_name("INPUT").required(false).index(1)),
+				.arg(Arg::with_name("INPUT").required(false).index(1))
+				.arg(base::file_arg()),
 			f: s2h,
+			serial: false,
+			pure: true,
 		},
 		Command {
 			app: SubCommand::with_name("h2b")
 				.about("Convert hex to binary")
-				.arg(Arg::with_name("INPUT").required(false).index(1)),
+				.arg(Arg::with_name("INPUT").required(false).index(1))
+				.arg(base::file_arg()),
 			f: h2b,
+			serial: false,
+			pure: true,
 		},
 		Command {
 			app: SubCommand::with_name("b2h")
 				.about("Convert binary to hex")
-				.arg(Arg::with_name("INPUT").required(false).index(1)),
+				.arg(Arg::with_name("INPUT").required(false).index(1))
+				.arg(base::file_arg()),
 			f: b2h,
+			serial: false,
+			pure: true,
 		},
 		Command {
 			app: SubCommand::with_name("h2a")
 				.about("Convert hex to byte array")
-				.arg(Arg::with_name("INPUT").required(false).index(1)),
+				.arg(Arg::with_name("INPUT").required(false).index(1))
+				.arg(base::file_arg()),
 			f: h2a,
+			serial: false,
+			pure: true,
 		},
 		Command {
 			app: SubCommand::with_name("a2h")
 				.about("Convert byte array to hex")
-				.arg(Arg::with_name("INPUT").required(false).index(1)),
+				.arg(Arg::with_name("INPUT").required(false).index(1))
+				.arg(base::file_arg()),
 			f: a2h,
+			serial: false,
+			pure: true,
 		},
 	]
 }
 
-fn h2s(matches: &ArgMatches) -> Result<Vec<String>, String> {
+fn h2s(matches: &ArgMatches) -> Result<Output, Error> {
 	let input = base::input_string(matches)?;
 	let input: Vec<u8> = input.parse::<Hex>().map_err(|_| "Convert failed")?.into();
 
 	let result = String::from_utf8(input).map_err(|_| "Not UTF-8")?;
 
-	Ok(vec![result])
+	Ok(Output::Lines(vec![result]))
 }
 
-fn s2h(matches: &ArgMatches) -> Result<Vec<String>, String> {
+fn s2h(matches: &ArgMatches) -> Result<Output, Error> {
 	let input = base::input_string(matches).map_err(|_| "Not UTF-8")?;
 
 	let input = input.as_bytes().to_vec();
 	let result: String = Hex::from(input).into();
 
-	Ok(vec![result])
+	Ok(Output::Lines(vec![result]))
 }
 
-fn h2b_inner(matches: &ArgMatches) -> Result<Vec<u8>, String> {
+fn h2b(matches: &ArgMatches) -> Result<Output, Error> {
 	let input = base::input_string(matches)?;
 	let result: Vec<u8> = input.parse::<Hex>().map_err(|_| "Convert failed")?.into();
 
-	Ok(result)
+	Ok(Output::Bytes(result))
 }
 
-fn h2b(matches: &ArgMatches) -> Result<Vec<String>, String> {
-	let result = h2b_inner(matches)?;
-
-	io::stdout()
-		.write_all(&result)
-		.map_err(|_| "Convert failed")?;
-
-	Ok(vec![])
-}
-
-fn b2h(matches: &ArgMatches) -> Result<Vec<String>, String> {
+fn b2h(matches: &ArgMatches) -> Result<Output, Error> {
 	let input = base::input_bytes(matches)?;
 
 	let result: String = Hex::from(input).into();
 
-	Ok(vec![result])
+	Ok(Output::Lines(vec![result]))
 }
 
-fn h2a(matches: &ArgMatches) -> Result<Vec<String>, String> {
+fn h2a(matches: &ArgMatches) -> Result<Output, Error> {
 	let input = base::input_string(matches)?;
 	let input: Vec<u8> = input.parse::<Hex>().map_err(|_| "Convert failed")?.into();
 
@@ -108,10 +114,10 @@ fn h2a(matches: &ArgMatches) -> Result<Vec<String>, String> {
 		.join(", ");
 	let result = format!("[{}]", result);
 
-	Ok(vec![result])
+	Ok(Output::Lines(vec![result]))
 }
 
-fn a2h(matches: &ArgMatches) -> Result<Vec<String>, String> {
+fn a2h(matches: &ArgMatches) -> Result<Output, Error> {
 	let input = base::input_string(matches).map_err(|_| "Not UTF-8")?;
 
 	let input = input.trim_start_matches('[').trim_end_matches(']');
@@ -126,7 +132,7 @@ fn a2h(matches: &ArgMatches) -> Result<Vec<String>, String> {
 		.map_err(|_| "Invalid byte array")?;
 
 	let result: String = Hex::from(input).into();
-	Ok(vec![result])
+	Ok(Output::Lines(vec![result]))
 }
 
 mod cases {
@@ -164,7 +170,7 @@ mod cases {
 					input: vec!["0x61626364"].into_iter().map(Into::into).collect(),
 					output: vec!["abcd"].into_iter().map(Into::into).collect(),
 					is_example: true,
-					is_test: false,
+					is_test: true,
 					since: "0.1.0".to_string(),
 				}],
 			),
@@ -217,6 +223,7 @@ mod cases {
 mod tests {
 	use super::*;
 	use crate::modules::base::test::test_module;
+	use crate::modules::Format;
 
 	#[test]
 	fn test_cases() {
@@ -225,9 +232,40 @@ mod tests {
 
 	#[test]
 	fn test_h2b() {
-		let app = &commands()[1].app;
+		let app = &commands()[2].app;
 
 		let matches = app.clone().get_matches_from(vec!["h2b", "0x61626364"]);
-		assert_eq!(h2b_inner(&matches), Ok(vec![0x61, 0x62, 0x63, 0x64]));
+		assert_eq!(
+			h2b(&matches),
+			Ok(Output::Bytes(vec![0x61, 0x62, 0x63, 0x64]))
+		);
+	}
+
+	#[test]
+	fn test_binary_round_trip() {
+		let data = vec![0x00, 0xff, 0xfe, 0x0a, 0x80, 0x61];
+		let path = std::env::temp_dir().join(format!("dtool_hex_{}", std::process::id()));
+		std::fs::write(&path, &data).unwrap();
+		let path = path.to_string_lossy().to_string();
+
+		let matches = commands()[3]
+			.app
+			.clone()
+			.get_matches_from(vec!["b2h", "-f", path.as_str()]);
+		let hex = b2h(&matches).unwrap().render(false).unwrap().remove(0);
+		assert_eq!(hex, "0x00fffe0a8061");
+
+		let matches = commands()[2]
+			.app
+			.clone()
+			.get_matches_from(vec!["h2b", hex.as_str()]);
+		let mut out = vec![];
+		h2b(&matches)
+			.unwrap()
+			.write(&Format::default(), &mut out)
+			.unwrap();
+		assert_eq!(out, data);
+
+		std::fs::remove_file(&path).unwrap();
 	}
 }
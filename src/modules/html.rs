@@ -1,4 +1,4 @@
-use crate::modules::{base, Command, Module};
+use crate::modules::{base, Command, Error, Module, Output};
 use clap::{Arg, ArgMatches, SubCommand};
 
 pub fn module<'a, 'b>() -> Module<'a, 'b> {
@@ -14,32 +14,38 @@ pub fn commands<'a, 'b>() -> Vec<Command<'a, 'b>> {
 		Command {
 			app: SubCommand::with_name("he")
 				.about("HTML entity encode")
-				.arg(Arg::with_name("INPUT").required(false).index(1)),
+				.arg(Arg::with_name("INPUT").required(false).index(1))
+				.arg(base::file_arg()),
 			f: he,
+			serial: false,
+			pure: true,
 		},
 		Command {
 			app: SubCommand::with_name("hd")
 				.about("HTML entity decode")
-				.arg(Arg::with_name("INPUT").required(false).index(1)),
+				.arg(Arg::with_name("INPUT").required(false).index(1))
+				.arg(base::file_arg()),
 			f: hd,
+			serial: false,
+			pure: true,
 		},
 	]
 }
 
-fn he(matches: &ArgMatches) -> Result<Vec<String>, String> {
+fn he(matches: &ArgMatches) -> Result<Output, Error> {
 	let input = base::input_string(matches)?;
 
 	let result = escaper::encode_minimal(&input);
 
-	Ok(vec![result])
+	Ok(Output::Lines(vec![result]))
 }
 
-fn hd(matches: &ArgMatches) -> Result<Vec<String>, String> {
+fn hd(matches: &ArgMatches) -> Result<Output, Error> {
 	let input = base::input_string(matches)?;
 
 	let result = escaper::decode_html(&input).map_err(|_| "Decode failed")?;
 
-	Ok(vec![result])
+	Ok(Output::Lines(vec![result]))
 }
 
 mod cases {
@@ -0,0 +1,179 @@
+use crate::modules::{usage, Module, Output};
+use crate::plugin;
+use clap::{App, Arg, ArgMatches, SubCommand};
+use serde_json::{json, Value};
+
+pub fn app<'a, 'b>() -> App<'a, 'b> {
+	SubCommand::with_name("list")
+		.about("List the sub commands grouped by module")
+		.arg(
+			Arg::with_name("MODULE")
+				.long("module")
+				.short("m")
+				.help("Show only the modules whose description contains the keyword, e.g. jwt")
+				.takes_value(true)
+				.required(false),
+		)
+		.arg(
+			Arg::with_name("SEARCH")
+				.long("search")
+				.short("s")
+				.help("Show only the sub commands whose name or description contains the keyword")
+				.takes_value(true)
+				.required(false),
+		)
+		.arg(
+			Arg::with_name("PLUGINS")
+				.long("plugins")
+				.help("List the plugins instead, executables named dtool-<name> in PATH"),
+		)
+}
+
+pub fn run(matches: &ArgMatches, modules: &[Module]) -> Result<Output, String> {
+	if matches.is_present("PLUGINS") {
+		return Ok(plugins(matches));
+	}
+
+	let module = matches.value_of("MODULE").map(str::to_lowercase);
+	let search = matches.value_of("SEARCH").map(str::to_lowercase);
+
+	// Module description, sub command name, about and since
+	let mut list = vec![];
+	for m in modules {
+		if let Some(module) = &module {
+			if !m.desc.to_lowercase().contains(module) {
+				continue;
+			}
+		}
+		let cases = (m.get_cases)();
+		let commands = m
+			.commands
+			.iter()
+			.map(|command| {
+				let name = command.app.get_name().to_string();
+				let about = usage::get_about(&command.app);
+				let since = cases
+					.get(name.as_str())
+					.and_then(|cases| {
+						cases
+							.iter()
+							.map(|x| x.since.as_str())
+							.max_by_key(|x| version(x))
+					})
+					.unwrap_or_default()
+					.to_string();
+				(name, about, since)
+			})
+			.filter(|(name, about, _)| match &search {
+				Some(search) => {
+					name.to_lowercase().contains(search) || about.to_lowercase().contains(search)
+				}
+				None => true,
+			})
+			.collect::<Vec<_>>();
+		if !commands.is_empty() {
+			list.push((m.desc.clone(), commands));
+		}
+	}
+
+	if matches.is_present("json") {
+		let list = list
+			.into_iter()
+			.map(|(desc, commands)| {
+				let commands = commands
+					.into_iter()
+					.map(|(name, about, since)| {
+						json!({
+							"name": name,
+							"about": about,
+							"since": since,
+						})
+					})
+					.collect::<Vec<_>>();
+				json!({ "module": desc, "commands": commands })
+			})
+			.collect();
+		return Ok(Output::Structured(Value::Array(list)));
+	}
+
+	let width = list
+		.iter()
+		.flat_map(|(_, commands)| commands.iter().map(|(name, _, _)| name.len()))
+		.max()
+		.unwrap_or_default();
+
+	let mut result = vec![];
+	for (desc, commands) in list {
+		if !result.is_empty() {
+			result.push("".to_string());
+		}
+		result.push(desc);
+		result.extend(commands.into_iter().map(|(name, about, since)| {
+			format!("  {:width$}  {} (v{})", name, about, since, width = width)
+		}));
+	}
+
+	Ok(Output::Lines(result))
+}
+
+fn plugins(matches: &ArgMatches) -> Output {
+	let plugins = plugin::discover();
+
+	if matches.is_present("json") {
+		let plugins = plugins
+			.into_iter()
+			.map(|(name, path)| json!({ "name": name, "path": path.to_string_lossy() }))
+			.collect();
+		return Output::Structured(Value::Array(plugins));
+	}
+
+	let width = plugins
+		.iter()
+		.map(|(name, _)| name.len())
+		.max()
+		.unwrap_or_default();
+	Output::Lines(
+		plugins
+			.into_iter()
+			.map(|(name, path)| format!("{:width$}  {}", name, path.display(), width = width))
+			.collect(),
+	)
+}
+
+/// Numeric parts of a version, so that 0.10.0 comes after 0.9.0
+pub fn version(since: &str) -> Vec<u32> {
+	since.split('.').map(|x| x.parse().unwrap_or(0)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::modules::ModuleManager;
+
+	fn list(args: Vec<&str>) -> Result<Output, String> {
+		let matches = app().get_matches_from(Some("list").into_iter().chain(args));
+		run(&matches, &ModuleManager::new().modules)
+	}
+
+	#[test]
+	fn test_list() {
+		assert_eq!(
+			list(vec!["-m", "jwt", "-s", "explain"]),
+			Ok(Output::Lines(vec![
+				"JWT (JSON Web Token) tools".to_string(),
+				"  jwt_explain  Explain JWT token segments: raw base64url alongside the decoded JSON (v0.17.0)"
+					.to_string(),
+			]))
+		);
+		assert_eq!(
+			list(vec!["-s", "nothing like this"]),
+			Ok(Output::Lines(vec![]))
+		);
+	}
+
+	#[test]
+	fn test_version() {
+		assert!(version("0.10.0") > version("0.9.0"));
+		assert!(version("0.17.0") > version("0.16.1"));
+	}
+}
@@ -0,0 +1,196 @@
+use crate::app;
+use crate::modules::{usage, Module, Output};
+use clap::{App, Arg, ArgMatches, SubCommand};
+use linked_hash_map::LinkedHashMap;
+use std::fs;
+use std::path::Path;
+
+/// Description, command line and output of an example case
+type Example = (String, String, Vec<String>);
+
+pub fn app<'a, 'b>() -> App<'a, 'b> {
+	SubCommand::with_name("mangen")
+		.about("Generate man pages: dtool.1 and dtool-<sub command>.1 for each sub command")
+		.arg(
+			Arg::with_name("OUT_DIR")
+				.long("out-dir")
+				.help("Directory to write the pages to")
+				.takes_value(true)
+				.required(true),
+		)
+}
+
+pub fn run(matches: &ArgMatches) -> Result<Output, String> {
+	let out_dir = Path::new(matches.value_of("OUT_DIR").ok_or("Invalid out dir")?);
+	fs::create_dir_all(out_dir)
+		.map_err(|e| format!("Failed to create dir {}: {}", out_dir.display(), e))?;
+
+	let (app, module_manager) = app::build_app();
+
+	let mut result = vec![];
+	for (file_name, page) in pages(&app, &module_manager.apps(), &module_manager.modules) {
+		let path = out_dir.join(file_name);
+		fs::write(&path, page)
+			.map_err(|e| format!("Failed to write file {}: {}", path.display(), e))?;
+		result.push(path.display().to_string());
+	}
+
+	Ok(Output::Lines(result))
+}
+
+/// File names and contents of the main page and the sub command pages
+fn pages(app: &App, apps: &[App], modules: &[Module]) -> Vec<(String, String)> {
+	let mut examples = LinkedHashMap::<String, Vec<Example>>::new();
+	for module in modules {
+		for (name, cases) in (module.get_cases)() {
+			let cases = cases.into_iter().filter(|x| x.is_example).map(|case| {
+				let command_line = usage::command_line(name, &case.input);
+				(case.desc, command_line, case.output)
+			});
+			examples.entry(name.to_string()).or_default().extend(cases);
+		}
+	}
+
+	let see_also = apps
+		.iter()
+		.map(|x| format!("dtool-{}(1)", x.get_name()))
+		.collect::<Vec<_>>();
+	let mut result = vec![(
+		"dtool.1".to_string(),
+		page("dtool", app.clone(), &[], &see_also),
+	)];
+
+	for app in apps {
+		let name = app.get_name().to_string();
+		let examples = examples.get(&name).map(Vec::as_slice).unwrap_or_default();
+		let app = app.clone().bin_name(format!("dtool {}", name));
+		result.push((
+			format!("dtool-{}.1", name),
+			page(
+				&format!("dtool-{}", name),
+				app,
+				examples,
+				&["dtool(1)".to_string()],
+			),
+		));
+	}
+
+	result
+}
+
+/// Render a page from the help of the app, each help section is kept as is
+fn page(title: &str, app: App, examples: &[Example], see_also: &[String]) -> String {
+	let mut help = Vec::new();
+	let _ = app.set_term_width(80).write_help(&mut help);
+	let help = String::from_utf8_lossy(&help);
+
+	// The first line is the name, followed by the author of the main app and
+	// the about up to the first section
+	let is_heading =
+		|x: &str| x.ends_with(':') && x.chars().all(|c| c.is_ascii_uppercase() || c == ':');
+	let mut lines = help.lines().skip(1).peekable();
+	let mut about = vec![];
+	while let Some(line) = lines.next_if(|x| !is_heading(x)) {
+		if !line.trim().is_empty() && line != env!("CARGO_PKG_AUTHORS") {
+			about.push(line.trim());
+		}
+	}
+
+	let mut page = vec![
+		format!(
+			".TH {} 1 \"\" \"dtool {}\"",
+			escape(&title.to_uppercase()),
+			env!("CARGO_PKG_VERSION")
+		),
+		".SH NAME".to_string(),
+		format!("{} \\- {}", escape(title), escape(&about.join(" "))),
+	];
+
+	// Sections are kept preformatted, with the blank lines between long entries
+	let mut in_section = false;
+	for line in lines {
+		if is_heading(line) {
+			end_section(&mut page, in_section);
+			match line.trim_end_matches(':') {
+				"USAGE" => page.push(".SH SYNOPSIS".to_string()),
+				heading => page.push(format!(".SH {}", heading)),
+			}
+			page.push(".nf".to_string());
+			in_section = true;
+		} else {
+			page.push(escape(line.trim_end()));
+		}
+	}
+	end_section(&mut page, in_section);
+
+	if !examples.is_empty() {
+		page.push(".SH EXAMPLES".to_string());
+		for (desc, command_line, output) in examples {
+			page.push(".PP".to_string());
+			page.push(escape(desc));
+			page.push(".nf".to_string());
+			page.push(".RS 4".to_string());
+			page.push(escape(&format!("$ {}", command_line)));
+			page.extend(output.iter().flat_map(|x| x.lines()).map(escape));
+			page.push(".RE".to_string());
+			page.push(".fi".to_string());
+		}
+	}
+
+	page.push(".SH SEE ALSO".to_string());
+	page.push(escape(&see_also.join(", ")));
+
+	page.iter().map(|x| format!("{}\n", x)).collect()
+}
+
+fn end_section(page: &mut Vec<String>, in_section: bool) {
+	while page.last().is_some_and(|x| x.is_empty()) {
+		page.pop();
+	}
+	if in_section {
+		page.push(".fi".to_string());
+	}
+}
+
+/// Escape a text line for roff
+fn escape(line: &str) -> String {
+	let line = line.replace('\\', "\\e").replace('-', "\\-");
+	if line.starts_with('.') || line.starts_with('\'') {
+		format!("\\&{}", line)
+	} else {
+		line
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::modules::ModuleManager;
+
+	#[test]
+	fn test_pages() {
+		let (app, module_manager) = app::build_app();
+		let pages = pages(&app, &module_manager.apps(), &ModuleManager::new().modules);
+
+		let (_, main) = pages.iter().find(|(x, _)| x == "dtool.1").unwrap();
+		assert!(main.starts_with(".TH DTOOL 1 "));
+		assert!(main.contains(".SH NAME\ndtool \\- A command\\-line tool collection"));
+		assert!(main.contains("dtool\\-jwt_verify(1)"));
+
+		let (_, page) = pages
+			.iter()
+			.find(|(x, _)| x == "dtool-jwt_verify.1")
+			.unwrap();
+		assert!(page.contains(".SH NAME\ndtool\\-jwt_verify \\- Verify JWT"));
+		assert!(page.contains("\\-\\-secret"));
+		assert!(page.contains(".SH EXAMPLES\n"));
+		assert!(page.contains("$ dtool jwt_verify "));
+	}
+
+	#[test]
+	fn test_escape() {
+		assert_eq!(escape("-s, --secret"), "\\-s, \\-\\-secret");
+		assert_eq!(escape(".hidden"), "\\&.hidden");
+		assert_eq!(escape(r"a\b"), r"a\eb");
+	}
+}
@@ -1,5 +1,5 @@
 use crate::modules::base::Hex;
-use crate::modules::{base, Command, Module};
+use crate::modules::{base, Command, Error, Module, Output};
 use clap::{Arg, ArgMatches, SubCommand};
 use parity_codec::{Compact, Decode, Encode};
 
@@ -33,8 +33,11 @@ pub fn commands<'a, 'b>() -> Vec<Command<'a, 'b>> {
 						.takes_value(true)
 						.required(false),
 				)
-				.arg(Arg::with_name("INPUT").required(false).index(1)),
+				.arg(Arg::with_name("INPUT").required(false).index(1))
+				.arg(base::file_arg()),
 			f: ne,
+			serial: false,
+			pure: true,
 		},
 		Command {
 			app: SubCommand::with_name("nd")
@@ -56,13 +59,16 @@ pub fn commands<'a, 'b>() -> Vec<Command<'a, 'b>> {
 						.takes_value(true)
 						.required(false),
 				)
-				.arg(Arg::with_name("INPUT").required(false).index(1)),
+				.arg(Arg::with_name("INPUT").required(false).index(1))
+				.arg(base::file_arg()),
 			f: nd,
+			serial: false,
+			pure: true,
 		},
 	]
 }
 
-fn ne(matches: &ArgMatches) -> Result<Vec<String>, String> {
+fn ne(matches: &ArgMatches) -> Result<Output, Error> {
 	let input = base::input_string(matches)?;
 
 	let t = matches.value_of("TYPE").ok_or("Invalid number type")?;
@@ -70,7 +76,7 @@ fn ne(matches: &ArgMatches) -> Result<Vec<String>, String> {
 	let big = match e {
 		"big" => true,
 		"little" => false,
-		_ => return Err("Invalid endian".to_string()),
+		_ => return Err("Invalid endian".into()),
 	};
 
 	let result = match t {
@@ -103,15 +109,15 @@ fn ne(matches: &ArgMatches) -> Result<Vec<String>, String> {
 			let input = Compact(input.parse::<u128>().map_err(|_| "Invalid input")?);
 			input.encode()
 		}
-		_ => return Err("Invalid input".to_string()),
+		_ => return Err("Invalid input".into()),
 	};
 
 	let result = Hex::from(result).into();
 
-	Ok(vec![result])
+	Ok(Output::Lines(vec![result]))
 }
 
-fn nd(matches: &ArgMatches) -> Result<Vec<String>, String> {
+fn nd(matches: &ArgMatches) -> Result<Output, Error> {
 	let input = base::input_string(matches)?;
 
 	let t = matches.value_of("TYPE").ok_or("Invalid number type")?;
@@ -120,7 +126,7 @@ fn nd(matches: &ArgMatches) -> Result<Vec<String>, String> {
 	let big = match e {
 		"big" => true,
 		"little" => false,
-		_ => return Err("Invalid endian".to_string()),
+		_ => return Err("Invalid endian".into()),
 	};
 
 	let input: Vec<u8> = input.parse::<Hex>().map_err(|_| "Invalid input")?.into();
@@ -132,7 +138,7 @@ fn nd(matches: &ArgMatches) -> Result<Vec<String>, String> {
 			let input: u8 = if input.len() > 0 {
 				input[0]
 			} else {
-				return Err("Invalid input".to_string());
+				return Err("Invalid input".into());
 			};
 			let input = if big { input.to_be() } else { input };
 			format!("{}", input)
@@ -161,10 +167,10 @@ fn nd(matches: &ArgMatches) -> Result<Vec<String>, String> {
 			let input: Compact<u128> = Decode::decode(&mut input).ok_or("Invalid input")?;
 			format!("{}", input.0)
 		}
-		_ => return Err("Invalid input".to_string()),
+		_ => return Err("Invalid input".into()),
 	};
 
-	Ok(vec![result])
+	Ok(Output::Lines(vec![result]))
 }
 
 mod cases {
@@ -1,4 +1,4 @@
-use crate::modules::{base, Command, Module};
+use crate::modules::{base, Command, Error, Module, Output};
 use clap::{Arg, ArgMatches, SubCommand};
 
 pub fn module<'a, 'b>() -> Module<'a, 'b> {
@@ -41,12 +41,15 @@ pub fn commands<'a, 'b>() -> Vec<Command<'a, 'b>> {
 					.help("Output hexadecimal result")
 					.required(false),
 			)
-			.arg(Arg::with_name("INPUT").required(false).index(1)),
+			.arg(Arg::with_name("INPUT").required(false).index(1))
+			.arg(base::file_arg()),
 		f: ns,
+		serial: false,
+		pure: true,
 	}]
 }
 
-fn ns(matches: &ArgMatches) -> Result<Vec<String>, String> {
+fn ns(matches: &ArgMatches) -> Result<Output, Error> {
 	let input = base::input_string(matches)?;
 
 	let (radix, number) = match input {
@@ -81,7 +84,7 @@ fn ns(matches: &ArgMatches) -> Result<Vec<String>, String> {
 		];
 	}
 
-	Ok(results)
+	Ok(Output::Lines(results))
 }
 
 mod cases {
@@ -0,0 +1,1254 @@
+use crate::modules::base::Hex;
+use crate::modules::{base, exit_code, Command, Error, Module, Output};
+use argon2::password_hash::{self, PasswordHash, PasswordHasher, PasswordVerifier, SaltString};
+use argon2::{Argon2, Params};
+use base64::{engine::general_purpose, Engine as _};
+use bcrypt::{BcryptError, Version};
+use clap::{Arg, ArgMatches, SubCommand};
+use scrypt::Scrypt;
+use serde_json::json;
+use std::convert::{TryFrom, TryInto};
+
+/// bcrypt ignores the bytes after the 72nd, refuse them rather than truncate silently
+const MAX_PASSWORD_BYTES: usize = 72;
+const MIN_COST: u32 = 4;
+const MAX_COST: u32 = 31;
+const DEFAULT_COST: &str = "12";
+/// The defaults of the argon2 crate, the minimum OWASP recommends for Argon2id
+const DEFAULT_MEMORY_KIB: &str = "19456";
+const DEFAULT_ITERATIONS: &str = "2";
+const DEFAULT_PARALLELISM: &str = "1";
+/// The recommended parameters of the scrypt crate, 128 MiB
+const DEFAULT_LOG_N: &str = "17";
+const DEFAULT_R: &str = "8";
+const DEFAULT_P: &str = "1";
+const DEFAULT_KEY_LENGTH: &str = "32";
+const DEFAULT_MAX_MEMORY: &str = "1G";
+
+pub fn module<'a, 'b>() -> Module<'a, 'b> {
+	Module {
+		desc: "Password hash (bcrypt, Argon2, scrypt)".to_string(),
+		commands: commands(),
+		get_cases: cases::cases,
+	}
+}
+
+pub fn commands<'a, 'b>() -> Vec<Command<'a, 'b>> {
+	vec![
+		Command {
+			app: SubCommand::with_name("bcrypt_hash")
+				.about("Hash a password with bcrypt, prompted for when stdin is a terminal")
+				.arg(
+					Arg::with_name("COST")
+						.long("cost")
+						.short("c")
+						.help("Cost, 4 to 31, each step doubles the time")
+						.takes_value(true)
+						.default_value(DEFAULT_COST),
+				)
+				.arg(
+					Arg::with_name("SALT_HEX")
+						.long("salt-hex")
+						.help(
+							"16 salt bytes (Hex) in place of random ones, for reproducible hashes",
+						)
+						.takes_value(true),
+				)
+				.arg(
+					Arg::with_name("INPUT")
+						.help("Password")
+						.required(false)
+						.index(1),
+				)
+				.arg(base::file_arg()),
+			f: bcrypt_hash,
+			serial: false,
+			pure: true,
+		},
+		Command {
+			app: SubCommand::with_name("bcrypt_verify")
+				.about("Verify a password against a bcrypt hash (exit code: 0 match, 4 no match)")
+				.arg(
+					Arg::with_name("HASH")
+						.help("bcrypt hash, $2a$, $2b$ or $2y$")
+						.required(true)
+						.index(1),
+				)
+				.arg(
+					Arg::with_name("INPUT")
+						.help("Password, prompted for or read from stdin when not given")
+						.required(false)
+						.index(2),
+				)
+				.arg(base::file_arg()),
+			f: bcrypt_verify,
+			serial: false,
+			pure: true,
+		},
+		Command {
+			app: SubCommand::with_name("argon2_hash")
+				.about("Hash a password with Argon2 in the PHC string format, prompted for when stdin is a terminal")
+				.arg(
+					Arg::with_name("VARIANT")
+						.long("variant")
+						.help("Variant")
+						.takes_value(true)
+						.possible_values(&["argon2id", "argon2i", "argon2d"])
+						.default_value("argon2id"),
+				)
+				.arg(
+					Arg::with_name("MEMORY_KIB")
+						.long("memory-kib")
+						.short("m")
+						.help("Memory in KiB")
+						.takes_value(true)
+						.default_value(DEFAULT_MEMORY_KIB),
+				)
+				.arg(
+					Arg::with_name("ITERATIONS")
+						.long("iterations")
+						.short("t")
+						.help("Iterations")
+						.takes_value(true)
+						.default_value(DEFAULT_ITERATIONS),
+				)
+				.arg(
+					Arg::with_name("PARALLELISM")
+						.long("parallelism")
+						.short("p")
+						.help("Parallelism (lanes)")
+						.takes_value(true)
+						.default_value(DEFAULT_PARALLELISM),
+				)
+				.arg(
+					Arg::with_name("SALT_B64")
+						.long("salt-b64")
+						.help("Salt (Base64), 8 bytes or more, in place of 16 random bytes")
+						.takes_value(true),
+				)
+				.arg(
+					Arg::with_name("INPUT")
+						.help("Password")
+						.required(false)
+						.index(1),
+				)
+				.arg(base::file_arg()),
+			f: argon2_hash,
+			serial: false,
+			pure: true,
+		},
+		Command {
+			app: SubCommand::with_name("argon2_verify")
+				.about("Verify a password against an Argon2 PHC string and show its parameters (exit code: 0 match, 4 no match)")
+				.arg(
+					Arg::with_name("HASH")
+						.help("PHC string, e.g. $argon2id$v=19$m=19456,t=2,p=1$...")
+						.required(true)
+						.index(1),
+				)
+				.arg(
+					Arg::with_name("INPUT")
+						.help("Password, prompted for or read from stdin when not given")
+						.required(false)
+						.index(2),
+				)
+				.arg(base::file_arg()),
+			f: argon2_verify,
+			serial: false,
+			pure: true,
+		},
+		Command {
+			app: SubCommand::with_name("scrypt")
+				.about("Derive a key with scrypt, the password is prompted for when stdin is a terminal")
+				.arg(
+					Arg::with_name("LOG_N")
+						.long("log-n")
+						.help("Log2 of the CPU/memory cost N")
+						.takes_value(true)
+						.default_value(DEFAULT_LOG_N),
+				)
+				.arg(
+					Arg::with_name("R")
+						.long("r")
+						.help("Block size")
+						.takes_value(true)
+						.default_value(DEFAULT_R),
+				)
+				.arg(
+					Arg::with_name("P")
+						.long("p")
+						.help("Parallelism")
+						.takes_value(true)
+						.default_value(DEFAULT_P),
+				)
+				.arg(
+					Arg::with_name("SALT_HEX")
+						.long("salt-hex")
+						.help("Salt (Hex), 0x for none")
+						.takes_value(true)
+						.required(true),
+				)
+				.arg(
+					Arg::with_name("LENGTH")
+						.long("length")
+						.short("l")
+						.help("Key length in bytes")
+						.takes_value(true)
+						.default_value(DEFAULT_KEY_LENGTH),
+				)
+				.arg(
+					Arg::with_name("B64")
+						.long("b64")
+						.help("Output as base64 instead of hex"),
+				)
+				.arg(max_memory_arg())
+				.arg(
+					Arg::with_name("INPUT")
+						.help("Password")
+						.required(false)
+						.index(1),
+				)
+				.arg(base::file_arg()),
+			f: scrypt,
+			serial: false,
+			pure: true,
+		},
+		Command {
+			app: SubCommand::with_name("scrypt_verify")
+				.about("Verify a password against a $scrypt$ hash and show its parameters (exit code: 0 match, 4 no match)")
+				.arg(
+					Arg::with_name("HASH")
+						.help("$scrypt$ hash, e.g. $scrypt$ln=17,r=8,p=1$...")
+						.required(true)
+						.index(1),
+				)
+				.arg(max_memory_arg())
+				.arg(
+					Arg::with_name("INPUT")
+						.help("Password, prompted for or read from stdin when not given")
+						.required(false)
+						.index(2),
+				)
+				.arg(base::file_arg()),
+			f: scrypt_verify,
+			serial: false,
+			pure: true,
+		},
+	]
+}
+
+fn max_memory_arg<'a, 'b>() -> Arg<'a, 'b> {
+	Arg::with_name("MAX_MEMORY")
+		.long("max-memory")
+		.help("Refuse parameters needing more memory, e.g. 256M, unless --force is given")
+		.takes_value(true)
+		.default_value(DEFAULT_MAX_MEMORY)
+}
+
+fn bcrypt_hash(matches: &ArgMatches) -> Result<Output, Error> {
+	let cost = matches
+		.value_of("COST")
+		.unwrap()
+		.parse::<u32>()
+		.ok()
+		.filter(|x| (MIN_COST..=MAX_COST).contains(x))
+		.ok_or_else(|| {
+			Error::Usage(format!(
+				"Invalid cost: {} to {} is required",
+				MIN_COST, MAX_COST
+			))
+		})?;
+	let salt = match matches.value_of("SALT_HEX") {
+		Some(salt) => salt_bytes(salt)?,
+		None => rand::random::<[u8; 16]>(),
+	};
+	let password = bcrypt_password(matches)?;
+
+	let hash = bcrypt::hash_with_salt(password.as_bytes(), cost, salt)
+		.map_err(|e| format!("Failed to hash: {}", e))?
+		.format_for_version(Version::TwoB);
+
+	Ok(Output::Lines(vec![hash]))
+}
+
+fn bcrypt_verify(matches: &ArgMatches) -> Result<Output, Error> {
+	let hash = matches.value_of("HASH").unwrap();
+	let password = bcrypt_password(matches)?;
+
+	let result = bcrypt::verify(password.as_bytes(), hash).map_err(|e| match e {
+		BcryptError::InvalidPrefix(prefix) => Error::invalid_input(format!(
+			"Invalid bcrypt hash: unsupported prefix ${}$",
+			prefix
+		))
+		.with_hint("use a $2a$, $2b$ or $2y$ hash"),
+		BcryptError::InvalidCost(_) => {
+			Error::invalid_input("Invalid bcrypt hash: invalid cost".to_string())
+		}
+		_ => Error::invalid_input("Invalid bcrypt hash".to_string()),
+	})?;
+
+	if !result {
+		base::set_exit_code(exit_code::INVALID);
+	}
+	if matches.is_present("json") {
+		return Ok(Output::Structured(json!({ "match": result })));
+	}
+
+	Ok(Output::Lines(vec![format!("Match: {}", result)]))
+}
+
+fn bcrypt_password(matches: &ArgMatches) -> Result<String, Error> {
+	let password = base::secret_input(matches, "Password: ")?;
+	if password.len() > MAX_PASSWORD_BYTES {
+		return Err(Error::Usage(format!(
+			"Password too long: {} bytes, bcrypt uses at most {}",
+			password.len(),
+			MAX_PASSWORD_BYTES
+		)));
+	}
+	Ok(password)
+}
+
+fn argon2_hash(matches: &ArgMatches) -> Result<Output, Error> {
+	let algorithm = argon2_algorithm(matches.value_of("VARIANT").unwrap())?;
+	let number = |name: &str, arg: &str| {
+		matches
+			.value_of(arg)
+			.unwrap()
+			.parse::<u32>()
+			.map_err(|_| Error::Usage(format!("Invalid {}", name)))
+	};
+	let params = Params::new(
+		number("memory", "MEMORY_KIB")?,
+		number("iterations", "ITERATIONS")?,
+		number("parallelism", "PARALLELISM")?,
+		None,
+	)
+	.map_err(|e| Error::Usage(format!("Invalid Argon2 parameters: {}", e)))?;
+	let salt = match matches.value_of("SALT_B64") {
+		Some(salt) => general_purpose::STANDARD_NO_PAD
+			.decode(salt.trim_end_matches('='))
+			.map_err(|_| Error::invalid_input("Invalid salt: base64 is required".to_string()))?,
+		None => rand::random::<[u8; 16]>().to_vec(),
+	};
+	let salt =
+		SaltString::encode_b64(&salt).map_err(|e| Error::Usage(format!("Invalid salt: {}", e)))?;
+	let password = base::secret_input(matches, "Password: ")?;
+
+	let hash = Argon2::new(algorithm, argon2::Version::V0x13, params)
+		.hash_password(password.as_bytes(), &salt)
+		.map_err(|e| format!("Failed to hash: {}", e))?;
+
+	Ok(Output::Lines(vec![hash.to_string()]))
+}
+
+fn argon2_verify(matches: &ArgMatches) -> Result<Output, Error> {
+	let hash = matches.value_of("HASH").unwrap();
+	let hash = PasswordHash::new(hash)
+		.map_err(|e| Error::invalid_input(format!("Invalid PHC string: {}", e)))?;
+	argon2_algorithm(hash.algorithm.as_str())?;
+	let password = base::secret_input(matches, "Password: ")?;
+
+	// The algorithm, version and parameters are the ones of the PHC string
+	let result = match Argon2::default().verify_password(password.as_bytes(), &hash) {
+		Ok(()) => true,
+		Err(password_hash::Error::Password) => false,
+		Err(e) => return Err(Error::invalid_input(format!("Invalid PHC string: {}", e))),
+	};
+	if !result {
+		base::set_exit_code(exit_code::INVALID);
+	}
+
+	let param = |name: &str| hash.params.get_decimal(name);
+	// Without v=, the string is of version 1.0 (16)
+	let version = hash.version.unwrap_or(0x10);
+	if matches.is_present("json") {
+		return Ok(Output::Structured(json!({
+			"match": result,
+			"algorithm": hash.algorithm.as_str(),
+			"version": version,
+			"memory_kib": param("m"),
+			"iterations": param("t"),
+			"parallelism": param("p"),
+		})));
+	}
+
+	let param = |name: &str| param(name).map(|x| x.to_string()).unwrap_or_default();
+	Ok(Output::Lines(vec![
+		format!("Match: {}", result),
+		format!("Algorithm: {}", hash.algorithm),
+		format!("Version: {}", version),
+		format!("Memory: {} KiB", param("m")),
+		format!("Iterations: {}", param("t")),
+		format!("Parallelism: {}", param("p")),
+	]))
+}
+
+fn argon2_algorithm(name: &str) -> Result<argon2::Algorithm, Error> {
+	match name {
+		"argon2id" => Ok(argon2::Algorithm::Argon2id),
+		"argon2i" => Ok(argon2::Algorithm::Argon2i),
+		"argon2d" => Ok(argon2::Algorithm::Argon2d),
+		_ => Err(
+			Error::invalid_input(format!("Unsupported algorithm: {}", name))
+				.with_hint("use an argon2id, argon2i or argon2d PHC string"),
+		),
+	}
+}
+
+fn scrypt(matches: &ArgMatches) -> Result<Output, Error> {
+	let number = |name: &str, arg: &str| {
+		matches
+			.value_of(arg)
+			.unwrap()
+			.parse::<u32>()
+			.map_err(|_| Error::Usage(format!("Invalid {}", name)))
+	};
+	let log_n = u8::try_from(number("log n", "LOG_N")?)
+		.map_err(|_| Error::Usage("Invalid log n".to_string()))?;
+	let (r, p) = (number("r", "R")?, number("p", "P")?);
+	let length = number("length", "LENGTH")?;
+	if length == 0 {
+		return Err(Error::Usage("Invalid length".to_string()));
+	}
+	let params = scrypt::Params::new(log_n, r, p, scrypt::Params::RECOMMENDED_LEN)
+		.map_err(|e| Error::Usage(format!("Invalid scrypt parameters: {}", e)))?;
+	check_scrypt_memory(matches, &params)?;
+	let salt: Vec<u8> = matches
+		.value_of("SALT_HEX")
+		.unwrap()
+		.parse::<Hex>()
+		.map_err(|_| Error::invalid_input("Invalid salt".to_string()))?
+		.into();
+	// Empty in the first test vector of RFC 7914
+	let password = base::secret_input_or_empty(matches, "Password: ", true)?;
+
+	let mut key = vec![0u8; length as usize];
+	scrypt::scrypt(password.as_bytes(), &salt, &params, &mut key)
+		.map_err(|e| format!("Failed to derive: {}", e))?;
+
+	let key = if matches.is_present("B64") {
+		general_purpose::STANDARD.encode(&key)
+	} else {
+		Hex::from(key).into()
+	};
+	Ok(Output::Lines(vec![key]))
+}
+
+fn scrypt_verify(matches: &ArgMatches) -> Result<Output, Error> {
+	let hash = matches.value_of("HASH").unwrap();
+	let hash = PasswordHash::new(hash)
+		.map_err(|e| Error::invalid_input(format!("Invalid $scrypt$ hash: {}", e)))?;
+	if hash.algorithm.as_str() != "scrypt" {
+		return Err(
+			Error::invalid_input(format!("Unsupported algorithm: {}", hash.algorithm))
+				.with_hint("use a $scrypt$ln=...,r=...,p=...$ hash"),
+		);
+	}
+	let params = scrypt::Params::try_from(&hash)
+		.map_err(|e| Error::invalid_input(format!("Invalid $scrypt$ hash: {}", e)))?;
+	check_scrypt_memory(matches, &params)?;
+	let password = base::secret_input(matches, "Password: ")?;
+
+	let result = match Scrypt.verify_password(password.as_bytes(), &hash) {
+		Ok(()) => true,
+		Err(password_hash::Error::Password) => false,
+		Err(e) => {
+			return Err(Error::invalid_input(format!(
+				"Invalid $scrypt$ hash: {}",
+				e
+			)))
+		}
+	};
+	if !result {
+		base::set_exit_code(exit_code::INVALID);
+	}
+
+	if matches.is_present("json") {
+		return Ok(Output::Structured(json!({
+			"match": result,
+			"log_n": params.log_n(),
+			"r": params.r(),
+			"p": params.p(),
+		})));
+	}
+
+	Ok(Output::Lines(vec![
+		format!("Match: {}", result),
+		format!("Log N: {}", params.log_n()),
+		format!("R: {}", params.r()),
+		format!("P: {}", params.p()),
+	]))
+}
+
+/// scrypt allocates 128 * r * N bytes, plus 128 * r * p, refuse more than --max-memory
+/// as a typo in --log-n or an untrusted hash could exhaust the memory
+fn check_scrypt_memory(matches: &ArgMatches, params: &scrypt::Params) -> Result<(), Error> {
+	let max = matches.value_of("MAX_MEMORY").unwrap();
+	let max = base::parse_size(max)
+		.ok_or_else(|| Error::Usage(format!("Invalid max memory: {}", max)))?;
+	let (r, p) = (params.r() as u128, params.p() as u128);
+	let memory = 128 * r * ((1u128 << params.log_n()) + p);
+	if memory > max as u128 && !matches.is_present("force") {
+		return Err(Error::Usage(format!(
+			"scrypt would need {} MiB of memory with log n {}, r {} and p {}, above --max-memory",
+			memory.div_ceil(1 << 20),
+			params.log_n(),
+			r,
+			p
+		))
+		.with_hint("lower --log-n or --r, or raise --max-memory, or pass --force"));
+	}
+	Ok(())
+}
+
+fn salt_bytes(salt: &str) -> Result<[u8; 16], Error> {
+	let salt: Vec<u8> = salt
+		.parse::<Hex>()
+		.map_err(|_| Error::invalid_input("Invalid salt".to_string()))?
+		.into();
+	salt.try_into().map_err(|_| {
+		Error::invalid_input("Invalid salt: exactly 16 bytes are required".to_string())
+	})
+}
+
+mod cases {
+	use crate::modules::Case;
+	use linked_hash_map::LinkedHashMap;
+
+	pub fn cases() -> LinkedHashMap<&'static str, Vec<Case>> {
+		vec![
+			(
+				"bcrypt_hash",
+				vec![
+					Case {
+						desc: "Reproducible with a fixed salt".to_string(),
+						input: vec![
+							"--cost",
+							"4",
+							"--salt-hex",
+							"00112233445566778899aabbccddeeff",
+							"password",
+						]
+						.into_iter()
+						.map(Into::into)
+						.collect(),
+						output: vec![
+							"$2b$04$./CgKyPTXlcGkYo5xL1s9urOdM1yp3Az9nzhPzSJ4f3hC5Ykk0hKm",
+						]
+						.into_iter()
+						.map(Into::into)
+						.collect(),
+						is_example: true,
+						is_test: true,
+						since: "0.17.0".to_string(),
+					},
+					Case {
+						desc: "".to_string(),
+						input: vec![
+							"-c",
+							"5",
+							"--salt-hex",
+							"0x5d2a6e3f1c8b4a7d9e0f1a2b3c4d5e6f",
+							"hunter2",
+						]
+						.into_iter()
+						.map(Into::into)
+						.collect(),
+						output: vec![
+							"$2b$05$VQnsNvwJQl0cBvmpNCzcZufMFhiTQ0xekil4ZBwj8VpLvAmLTfjsS",
+						]
+						.into_iter()
+						.map(Into::into)
+						.collect(),
+						is_example: false,
+						is_test: true,
+						since: "0.17.0".to_string(),
+					},
+				],
+			),
+			(
+				"bcrypt_verify",
+				vec![
+					Case {
+						desc: "".to_string(),
+						input: vec![
+							"$2b$04$./CgKyPTXlcGkYo5xL1s9urOdM1yp3Az9nzhPzSJ4f3hC5Ykk0hKm",
+							"password",
+						]
+						.into_iter()
+						.map(Into::into)
+						.collect(),
+						output: vec!["Match: true"].into_iter().map(Into::into).collect(),
+						is_example: true,
+						is_test: true,
+						since: "0.17.0".to_string(),
+					},
+					Case {
+						desc: "Wrong password, exits with 4".to_string(),
+						input: vec![
+							"$2b$04$./CgKyPTXlcGkYo5xL1s9urOdM1yp3Az9nzhPzSJ4f3hC5Ykk0hKm",
+							"passw0rd",
+						]
+						.into_iter()
+						.map(Into::into)
+						.collect(),
+						output: vec!["Match: false"].into_iter().map(Into::into).collect(),
+						is_example: true,
+						is_test: true,
+						since: "0.17.0".to_string(),
+					},
+					Case {
+						desc: "$2y$ of htpasswd".to_string(),
+						input: vec![
+							"$2y$04$./CgKyPTXlcGkYo5xL1s9urOdM1yp3Az9nzhPzSJ4f3hC5Ykk0hKm",
+							"password",
+						]
+						.into_iter()
+						.map(Into::into)
+						.collect(),
+						output: vec!["Match: true"].into_iter().map(Into::into).collect(),
+						is_example: true,
+						is_test: true,
+						since: "0.17.0".to_string(),
+					},
+					Case {
+						desc: "".to_string(),
+						input: vec![
+							"$2a$05$CCCCCCCCCCCCCCCCCCCCC.E5YPO9kmyuRGyh0XouQYb4YMJKvyOeW",
+							"U*U",
+						]
+						.into_iter()
+						.map(Into::into)
+						.collect(),
+						output: vec!["Match: true"].into_iter().map(Into::into).collect(),
+						is_example: false,
+						is_test: true,
+						since: "0.17.0".to_string(),
+					},
+				],
+			),
+			(
+				"argon2_hash",
+				vec![
+					Case {
+						desc: "Reproducible with a fixed salt".to_string(),
+						input: vec![
+							"-m",
+							"64",
+							"-t",
+							"2",
+							"-p",
+							"1",
+							"--salt-b64",
+							"AAECAwQFBgcICQoLDA0ODw==",
+							"password",
+						]
+						.into_iter()
+						.map(Into::into)
+						.collect(),
+						output: vec![
+							"$argon2id$v=19$m=64,t=2,p=1$AAECAwQFBgcICQoLDA0ODw$cWiCgh33e8ZBQ1iwcVn7Y8ockL6uudDKDkO9iH3PLKk",
+						]
+						.into_iter()
+						.map(Into::into)
+						.collect(),
+						is_example: true,
+						is_test: true,
+						since: "0.17.0".to_string(),
+					},
+					Case {
+						desc: "Argon2i".to_string(),
+						input: vec![
+							"--variant",
+							"argon2i",
+							"-m",
+							"64",
+							"-t",
+							"2",
+							"--salt-b64",
+							"AAECAwQFBgcICQoLDA0ODw==",
+							"password",
+						]
+						.into_iter()
+						.map(Into::into)
+						.collect(),
+						output: vec![
+							"$argon2i$v=19$m=64,t=2,p=1$AAECAwQFBgcICQoLDA0ODw$zu7UvKYL/uwF2/Z3BuOvis8LEGMHFBNkSOK82WX12XE",
+						]
+						.into_iter()
+						.map(Into::into)
+						.collect(),
+						is_example: false,
+						is_test: true,
+						since: "0.17.0".to_string(),
+					},
+					Case {
+						desc: "Argon2d".to_string(),
+						input: vec![
+							"--variant",
+							"argon2d",
+							"-m",
+							"64",
+							"-t",
+							"2",
+							"--salt-b64",
+							"AAECAwQFBgcICQoLDA0ODw",
+							"password",
+						]
+						.into_iter()
+						.map(Into::into)
+						.collect(),
+						output: vec![
+							"$argon2d$v=19$m=64,t=2,p=1$AAECAwQFBgcICQoLDA0ODw$CosZg7LkdXRiWaAuhkwx9wkSBX20Fccq2pzor+3zmVE",
+						]
+						.into_iter()
+						.map(Into::into)
+						.collect(),
+						is_example: false,
+						is_test: true,
+						since: "0.17.0".to_string(),
+					},
+					Case {
+						desc: "".to_string(),
+						input: vec![
+							"--memory-kib",
+							"256",
+							"--iterations",
+							"3",
+							"--parallelism",
+							"2",
+							"--salt-b64",
+							"c2FsdHNhbHRzYWx0c2FsdA",
+							"hunter2",
+						]
+						.into_iter()
+						.map(Into::into)
+						.collect(),
+						output: vec![
+							"$argon2id$v=19$m=256,t=3,p=2$c2FsdHNhbHRzYWx0c2FsdA$5Hy/AbEDukTFm6SLsmyLlIf0S30iwyaFij0fP6kE99E",
+						]
+						.into_iter()
+						.map(Into::into)
+						.collect(),
+						is_example: false,
+						is_test: true,
+						since: "0.17.0".to_string(),
+					},
+				],
+			),
+			(
+				"argon2_verify",
+				vec![
+					Case {
+						desc: "Of the reference implementation, with a 24 byte hash".to_string(),
+						input: vec![
+							"$argon2i$v=19$m=65536,t=2,p=4$c29tZXNhbHQ$RdescudvJCsgt3ub+b+dWRWJTmaaJObG",
+							"password",
+						]
+						.into_iter()
+						.map(Into::into)
+						.collect(),
+						output: vec![
+							"Match: true",
+							"Algorithm: argon2i",
+							"Version: 19",
+							"Memory: 65536 KiB",
+							"Iterations: 2",
+							"Parallelism: 4",
+						]
+						.into_iter()
+						.map(Into::into)
+						.collect(),
+						is_example: false,
+						is_test: true,
+						since: "0.17.0".to_string(),
+					},
+					Case {
+						desc: "".to_string(),
+						input: vec![
+							"$argon2id$v=19$m=64,t=2,p=1$AAECAwQFBgcICQoLDA0ODw$cWiCgh33e8ZBQ1iwcVn7Y8ockL6uudDKDkO9iH3PLKk",
+							"password",
+						]
+						.into_iter()
+						.map(Into::into)
+						.collect(),
+						output: vec![
+							"Match: true",
+							"Algorithm: argon2id",
+							"Version: 19",
+							"Memory: 64 KiB",
+							"Iterations: 2",
+							"Parallelism: 1",
+						]
+						.into_iter()
+						.map(Into::into)
+						.collect(),
+						is_example: true,
+						is_test: true,
+						since: "0.17.0".to_string(),
+					},
+					Case {
+						desc: "Wrong password, exits with 4".to_string(),
+						input: vec![
+							"$argon2id$v=19$m=64,t=2,p=1$AAECAwQFBgcICQoLDA0ODw$cWiCgh33e8ZBQ1iwcVn7Y8ockL6uudDKDkO9iH3PLKk",
+							"passw0rd",
+						]
+						.into_iter()
+						.map(Into::into)
+						.collect(),
+						output: vec![
+							"Match: false",
+							"Algorithm: argon2id",
+							"Version: 19",
+							"Memory: 64 KiB",
+							"Iterations: 2",
+							"Parallelism: 1",
+						]
+						.into_iter()
+						.map(Into::into)
+						.collect(),
+						is_example: true,
+						is_test: true,
+						since: "0.17.0".to_string(),
+					},
+					Case {
+						desc: "".to_string(),
+						input: vec![
+							"$argon2i$v=19$m=64,t=2,p=1$AAECAwQFBgcICQoLDA0ODw$zu7UvKYL/uwF2/Z3BuOvis8LEGMHFBNkSOK82WX12XE",
+							"password",
+						]
+						.into_iter()
+						.map(Into::into)
+						.collect(),
+						output: vec![
+							"Match: true",
+							"Algorithm: argon2i",
+							"Version: 19",
+							"Memory: 64 KiB",
+							"Iterations: 2",
+							"Parallelism: 1",
+						]
+						.into_iter()
+						.map(Into::into)
+						.collect(),
+						is_example: false,
+						is_test: true,
+						since: "0.17.0".to_string(),
+					},
+					Case {
+						desc: "".to_string(),
+						input: vec![
+							"$argon2d$v=19$m=64,t=2,p=1$AAECAwQFBgcICQoLDA0ODw$CosZg7LkdXRiWaAuhkwx9wkSBX20Fccq2pzor+3zmVE",
+							"password",
+						]
+						.into_iter()
+						.map(Into::into)
+						.collect(),
+						output: vec![
+							"Match: true",
+							"Algorithm: argon2d",
+							"Version: 19",
+							"Memory: 64 KiB",
+							"Iterations: 2",
+							"Parallelism: 1",
+						]
+						.into_iter()
+						.map(Into::into)
+						.collect(),
+						is_example: false,
+						is_test: true,
+						since: "0.17.0".to_string(),
+					},
+					Case {
+						desc: "".to_string(),
+						input: vec![
+							"$argon2id$v=19$m=256,t=3,p=2$c2FsdHNhbHRzYWx0c2FsdA$5Hy/AbEDukTFm6SLsmyLlIf0S30iwyaFij0fP6kE99E",
+							"hunter2",
+						]
+						.into_iter()
+						.map(Into::into)
+						.collect(),
+						output: vec![
+							"Match: true",
+							"Algorithm: argon2id",
+							"Version: 19",
+							"Memory: 256 KiB",
+							"Iterations: 3",
+							"Parallelism: 2",
+						]
+						.into_iter()
+						.map(Into::into)
+						.collect(),
+						is_example: false,
+						is_test: true,
+						since: "0.17.0".to_string(),
+					},
+				],
+			),
+			(
+				"scrypt",
+				vec![
+					Case {
+						desc: "RFC 7914 test vector 1".to_string(),
+						input: vec![
+							"--log-n",
+							"4",
+							"--r",
+							"1",
+							"--p",
+							"1",
+							"--salt-hex",
+							"0x",
+							"-l",
+							"64",
+							"''",
+						]
+						.into_iter()
+						.map(Into::into)
+						.collect(),
+						output: vec![
+							"0x77d6576238657b203b19ca42c18a0497f16b4844e3074ae8dfdffa3fede21442fcd0069ded0948f8326a753a0fc81f17e8d3e0fb2e0d3628cf35e20c38d18906",
+						]
+						.into_iter()
+						.map(Into::into)
+						.collect(),
+						is_example: true,
+						is_test: true,
+						since: "0.17.0".to_string(),
+					},
+					Case {
+						desc: "RFC 7914 test vector 2".to_string(),
+						input: vec![
+							"--log-n",
+							"10",
+							"--r",
+							"8",
+							"--p",
+							"16",
+							"--salt-hex",
+							"0x4e61436c",
+							"-l",
+							"64",
+							"password",
+						]
+						.into_iter()
+						.map(Into::into)
+						.collect(),
+						output: vec![
+							"0xfdbabe1c9d3472007856e7190d01e9fe7c6ad7cbc8237830e77376634b3731622eaf30d92e22a3886ff109279d9830dac727afb94a83ee6d8360cbdfa2cc0640",
+						]
+						.into_iter()
+						.map(Into::into)
+						.collect(),
+						is_example: false,
+						is_test: true,
+						since: "0.17.0".to_string(),
+					},
+					Case {
+						desc: "In base64".to_string(),
+						input: vec![
+							"--log-n",
+							"10",
+							"--r",
+							"8",
+							"--p",
+							"16",
+							"--salt-hex",
+							"0x4e61436c",
+							"-l",
+							"64",
+							"--b64",
+							"password",
+						]
+						.into_iter()
+						.map(Into::into)
+						.collect(),
+						output: vec![
+							"/bq+HJ00cgB4VucZDQHp/nxq18vII3gw53N2Y0s3MWIurzDZLiKjiG/xCSedmDDaxyevuUqD7m2DYMvfoswGQA==",
+						]
+						.into_iter()
+						.map(Into::into)
+						.collect(),
+						is_example: true,
+						is_test: true,
+						since: "0.17.0".to_string(),
+					},
+					Case {
+						desc: "RFC 7914 test vector 3".to_string(),
+						input: vec![
+							"--log-n",
+							"14",
+							"--r",
+							"8",
+							"--p",
+							"1",
+							"--salt-hex",
+							"0x536f6469756d43686c6f72696465",
+							"-l",
+							"64",
+							"pleaseletmein",
+						]
+						.into_iter()
+						.map(Into::into)
+						.collect(),
+						output: vec![
+							"0x7023bdcb3afd7348461c06cd81fd38ebfda8fbba904f8e3ea9b543f6545da1f2d5432955613f0fcf62d49705242a9af9e61e85dc0d651e40dfcf017b45575887",
+						]
+						.into_iter()
+						.map(Into::into)
+						.collect(),
+						is_example: false,
+						is_test: true,
+						since: "0.17.0".to_string(),
+					},
+					Case {
+						desc: "RFC 7914 test vector 4, 1 GiB of memory".to_string(),
+						input: vec![
+							"--log-n",
+							"20",
+							"--r",
+							"8",
+							"--p",
+							"1",
+							"--salt-hex",
+							"0x536f6469756d43686c6f72696465",
+							"-l",
+							"64",
+							"--max-memory",
+							"2G",
+							"pleaseletmein",
+						]
+						.into_iter()
+						.map(Into::into)
+						.collect(),
+						output: vec![
+							"0x2101cb9b6a511aaeaddbbe09cf70f881ec568d574a2ffd4dabe5ee9820adaa478e56fd8f4ba5d09ffa1c6d927c40f4c337304049e8a952fbcbf45c6fa77a41a4",
+						]
+						.into_iter()
+						.map(Into::into)
+						.collect(),
+						is_example: true,
+						is_test: false, // Too slow and large for the tests
+						since: "0.17.0".to_string(),
+					},
+				],
+			),
+			(
+				"scrypt_verify",
+				vec![
+					Case {
+						desc: "".to_string(),
+						input: vec![
+							"$scrypt$ln=4,r=8,p=1$AAECAwQFBgcICQoLDA0ODw$SifLA7K0t5yM6JUFWSfwEu2b21TUGoi7m1p3x7fgMoI",
+							"password",
+						]
+						.into_iter()
+						.map(Into::into)
+						.collect(),
+						output: vec![
+							"Match: true",
+							"Log N: 4",
+							"R: 8",
+							"P: 1",
+						]
+						.into_iter()
+						.map(Into::into)
+						.collect(),
+						is_example: true,
+						is_test: true,
+						since: "0.17.0".to_string(),
+					},
+					Case {
+						desc: "Wrong password, exits with 4".to_string(),
+						input: vec![
+							"$scrypt$ln=4,r=8,p=1$AAECAwQFBgcICQoLDA0ODw$SifLA7K0t5yM6JUFWSfwEu2b21TUGoi7m1p3x7fgMoI",
+							"passw0rd",
+						]
+						.into_iter()
+						.map(Into::into)
+						.collect(),
+						output: vec![
+							"Match: false",
+							"Log N: 4",
+							"R: 8",
+							"P: 1",
+						]
+						.into_iter()
+						.map(Into::into)
+						.collect(),
+						is_example: true,
+						is_test: true,
+						since: "0.17.0".to_string(),
+					},
+					Case {
+						desc: "".to_string(),
+						input: vec![
+							"$scrypt$ln=5,r=4,p=2$AAECAwQFBgcICQoLDA0ODw$t9bA70+vz/3n2BfC6tlOi8APNMsY7VFEOHXl/uWjuGg",
+							"hunter2",
+						]
+						.into_iter()
+						.map(Into::into)
+						.collect(),
+						output: vec![
+							"Match: true",
+							"Log N: 5",
+							"R: 4",
+							"P: 2",
+						]
+						.into_iter()
+						.map(Into::into)
+						.collect(),
+						is_example: false,
+						is_test: true,
+						since: "0.17.0".to_string(),
+					},
+					Case {
+						desc: "Of passlib".to_string(),
+						input: vec![
+							"$scrypt$ln=16,r=8,p=1$aM15713r3Xsvxbi31lqr1Q$nFNh2CVHVjNldFVKDHDlm4CbdRSCdEBsjjJxD+iCs5E",
+							"password",
+						]
+						.into_iter()
+						.map(Into::into)
+						.collect(),
+						output: vec![
+							"Match: true",
+							"Log N: 16",
+							"R: 8",
+							"P: 1",
+						]
+						.into_iter()
+						.map(Into::into)
+						.collect(),
+						is_example: false,
+						is_test: false, // 64 MiB, slow in debug builds
+						since: "0.17.0".to_string(),
+					},
+				],
+			),
+		]
+		.into_iter()
+		.collect()
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::app;
+	use crate::modules::base::test::test_module;
+
+	#[test]
+	fn test_cases() {
+		test_module(module());
+	}
+
+	#[test]
+	fn test_password_limit() {
+		let app = &commands()[0].app;
+		let hash = |password: &str| {
+			let matches = app.clone().get_matches_from(vec![
+				"bcrypt_hash",
+				"-c",
+				"4",
+				"--salt-hex",
+				"00112233445566778899aabbccddeeff",
+				password,
+			]);
+			bcrypt_hash(&matches)
+		};
+
+		assert!(hash(&"a".repeat(72)).is_ok());
+		assert_eq!(
+			hash(&"a".repeat(73)),
+			Err(Error::Usage(
+				"Password too long: 73 bytes, bcrypt uses at most 72".to_string()
+			))
+		);
+	}
+
+	#[test]
+	fn test_invalid_hash() {
+		let app = &commands()[1].app;
+		let verify = |hash: &str| {
+			let matches = app
+				.clone()
+				.get_matches_from(vec!["bcrypt_verify", hash, "password"]);
+			bcrypt_verify(&matches)
+		};
+
+		assert_eq!(
+			verify("$2c$04$./CgKyPTXlcGkYo5xL1s9urOdM1yp3Az9nzhPzSJ4f3hC5Ykk0hKm"),
+			Err(
+				Error::invalid_input("Invalid bcrypt hash: unsupported prefix $2c$".to_string())
+					.with_hint("use a $2a$, $2b$ or $2y$ hash")
+			)
+		);
+		assert_eq!(
+			verify("password"),
+			Err(Error::invalid_input("Invalid bcrypt hash".to_string()))
+		);
+	}
+
+	#[test]
+	fn test_scrypt_max_memory() {
+		let app = commands()[4].app.clone().args(&app::global_args());
+		let derive = |args: Vec<&str>| {
+			let matches = app.clone().get_matches_from(
+				vec!["scrypt", "--r", "8", "--salt-hex", "0x"]
+					.into_iter()
+					.chain(args)
+					.chain(vec!["password"]),
+			);
+			scrypt(&matches)
+		};
+
+		assert_eq!(
+			derive(vec!["--log-n", "20"]),
+			Err(Error::Usage(
+				"scrypt would need 1025 MiB of memory with log n 20, r 8 and p 1, above --max-memory"
+					.to_string()
+			)
+			.with_hint("lower --log-n or --r, or raise --max-memory, or pass --force"))
+		);
+		assert!(matches!(
+			derive(vec!["--log-n", "12", "--max-memory", "4M"]),
+			Err(Error::Hinted(..))
+		));
+		assert!(derive(vec!["--log-n", "12", "--max-memory", "4M", "--force"]).is_ok());
+		assert_eq!(
+			derive(vec!["--log-n", "4", "--max-memory", "4X"]),
+			Err(Error::Usage("Invalid max memory: 4X".to_string()))
+		);
+
+		// The parameters of an untrusted hash too
+		let matches = commands()[5].app.clone().get_matches_from(vec![
+			"scrypt_verify",
+			"$scrypt$ln=30,r=8,p=1$AAECAwQFBgcICQoLDA0ODw$SifLA7K0t5yM6JUFWSfwEu2b21TUGoi7m1p3x7fgMoI",
+			"password",
+		]);
+		assert!(matches!(scrypt_verify(&matches), Err(Error::Hinted(..))));
+	}
+
+	#[test]
+	fn test_argon2_verify_invalid() {
+		let app = &commands()[3].app;
+		let verify = |hash: &str| {
+			let matches = app
+				.clone()
+				.get_matches_from(vec!["argon2_verify", hash, "password"]);
+			argon2_verify(&matches)
+		};
+
+		assert_eq!(
+			verify("$scrypt$ln=16,r=8,p=1$aM15713r3Xsvxbi31lqr1Q$nFNh2CVHVjNldFVKDHDlm4CbdRSCdEBsjjJxD+iCs5E"),
+			Err(Error::invalid_input("Unsupported algorithm: scrypt".to_string())
+				.with_hint("use an argon2id, argon2i or argon2d PHC string"))
+		);
+		assert!(matches!(
+			verify("argon2id"),
+			Err(Error::InvalidInput { .. })
+		));
+	}
+}
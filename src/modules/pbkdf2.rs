@@ -1,10 +1,25 @@
+use crate::log;
 use crate::modules::base::Hex;
-use crate::modules::{base, Command, Module};
+use crate::modules::{base, exit_code, Command, Error, Module, Output};
+use base64::{engine::general_purpose, Engine as _};
 use clap::{Arg, ArgMatches, SubCommand};
+use rand::distributions::Alphanumeric;
+use rand::Rng;
+use ring::digest::{SHA1_OUTPUT_LEN, SHA256_OUTPUT_LEN, SHA384_OUTPUT_LEN, SHA512_OUTPUT_LEN};
 use ring::pbkdf2::{
-	derive, PBKDF2_HMAC_SHA1, PBKDF2_HMAC_SHA256, PBKDF2_HMAC_SHA384, PBKDF2_HMAC_SHA512,
+	self, PBKDF2_HMAC_SHA1, PBKDF2_HMAC_SHA256, PBKDF2_HMAC_SHA384, PBKDF2_HMAC_SHA512,
 };
+use serde_json::json;
 use std::num::NonZeroU32;
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// The default of Django 5.2
+const DJANGO_ITERATIONS: u32 = 1_000_000;
+const DJANGO_SALT_LENGTH: usize = 22;
+/// Slower derivations show the elapsed time on stderr, updated as often
+const PROGRESS_DELAY: Duration = Duration::from_secs(1);
 
 pub fn module<'a, 'b>() -> Module<'a, 'b> {
 	Module {
@@ -21,102 +36,275 @@ pub fn commands<'a, 'b>() -> Vec<Command<'a, 'b>> {
 				.arg(
 					Arg::with_name("ITERATIONS")
 						.long("iterations")
-						.short("i").help("Iterations")
+						.short("i").help("Iterations, 1 by default, 1000000 with --django")
 						.takes_value(true)
-						.default_value("1")
 						.required(false))
 				.arg(
 					Arg::with_name("ALGORITHM")
 						.long("algorithm")
-						.short("a").help("Algorithm\nsha1: SHA-1\nsha2_256: SHA-2 256\nsha2_384: SHA-2 384\nsha2_512: SHA-2 512")
+						.visible_alias("hash")
+						.short("a").help("Algorithm, sha1 by default, sha256 with --django\nsha1: SHA-1\nsha256 (sha2_256): SHA-2 256\nsha384 (sha2_384): SHA-2 384\nsha512 (sha2_512): SHA-2 512")
+						.takes_value(true)
+						.required(false))
+				.arg(
+					Arg::with_name("SALT_HEX")
+						.long("salt-hex")
+						.short("s").help("Salt (Hex)")
 						.takes_value(true)
-						.default_value("sha1")
+						.conflicts_with("SALT")
 						.required(false))
 				.arg(
 					Arg::with_name("SALT")
 						.long("salt")
-						.short("s").help("Salt (Hex)")
+						.help("Salt (UTF-8 string), random with --django")
 						.takes_value(true)
-						.default_value("0x")
 						.required(false))
 				.arg(
 					Arg::with_name("KEY_LENGTH")
 						.long("key-length")
 						.short("l").help("Key length")
-						.help("Key length: must be a multiple of 8")
+						.help("Key length in bits: must be a multiple of 8, 128 by default")
 						.takes_value(true)
-						.default_value("128")
+						.conflicts_with("LENGTH")
 						.required(false))
+				.arg(
+					Arg::with_name("LENGTH")
+						.long("length")
+						.help("Key length in bytes")
+						.takes_value(true)
+						.required(false))
+				.arg(
+					Arg::with_name("B64")
+						.long("b64")
+						.help("Output as base64 instead of hex"))
+				.arg(
+					Arg::with_name("DJANGO")
+						.long("django")
+						.help("Output the pbkdf2_sha256$iterations$salt$hash of Django")
+						.conflicts_with_all(&["SALT_HEX", "KEY_LENGTH", "LENGTH", "B64"]))
+				.arg(
+					Arg::with_name("VERIFY")
+						.long("verify")
+						.help("Verify the password against a Django hash (exit code: 0 match, 4 no match)")
+						.takes_value(true)
+						.requires("DJANGO")
+						.conflicts_with_all(&["ALGORITHM", "ITERATIONS", "SALT"]))
+				.arg(
+					Arg::with_name("PASSWORD")
+						.long("password")
+						.help("INPUT is a password (UTF-8 string) rather than hex, prompted for when stdin is a terminal; implied by --django"))
 				.arg(
 					Arg::with_name("INPUT")
 						.help("Secret (Hex)")
 						.required(false)
-						.index(1)),
+						.index(1))
+				.arg(base::file_arg()),
 
 			f: pbkdf2,
+			serial: false,
+			pure: true,
 		}
 	]
 }
 
-fn pbkdf2(matches: &ArgMatches) -> Result<Vec<String>, String> {
-	let algo = match matches.value_of("ALGORITHM") {
-		Some("sha1") => PBKDF2_HMAC_SHA1,
-		Some("sha2_256") => PBKDF2_HMAC_SHA256,
-		Some("sha2_384") => PBKDF2_HMAC_SHA384,
-		Some("sha2_512") => PBKDF2_HMAC_SHA512,
-		_ => return Err("Invalid algorithm".to_string()),
+fn pbkdf2(matches: &ArgMatches) -> Result<Output, Error> {
+	if let Some(hash) = matches.value_of("VERIFY") {
+		return django_verify(matches, hash);
+	}
+	let django = matches.is_present("DJANGO");
+
+	let default_algorithm = if django { "sha256" } else { "sha1" };
+	let (algo, algo_name, digest_len) =
+		algorithm(matches.value_of("ALGORITHM").unwrap_or(default_algorithm))?;
+	let django_name = match algo_name {
+		"sha256" => "pbkdf2_sha256",
+		"sha1" => "pbkdf2_sha1",
+		_ if django => {
+			return Err(
+				Error::Usage("Django only supports sha256 and sha1".to_string())
+					.with_hint("use --hash sha256"),
+			)
+		}
+		_ => "",
 	};
 
 	let iterations = match matches.value_of("ITERATIONS") {
 		Some(iterations) => iterations
 			.parse::<u32>()
-			.map_err(|_| "Invalid Iterations".to_string()),
-		_ => Err("Invalid Iterations".to_string()),
+			.ok()
+			.and_then(NonZeroU32::new)
+			.ok_or_else(|| "Invalid Iterations".to_string())?,
+		None if django => NonZeroU32::new(DJANGO_ITERATIONS).expect("not zero"),
+		None => NonZeroU32::new(1).expect("not zero"),
+	};
+
+	let salt: Vec<u8> = match (matches.value_of("SALT"), matches.value_of("SALT_HEX")) {
+		(Some(salt), _) => salt.as_bytes().to_vec(),
+		(None, Some(salt)) => salt
+			.parse::<Hex>()
+			.map_err(|_| "Invalid salt".to_string())?
+			.into(),
+		(None, None) if django => django_salt().into_bytes(),
+		(None, None) => vec![],
+	};
+	if django && salt.contains(&b'$') {
+		return Err(Error::Usage(
+			"Invalid salt: a Django salt can not contain $".to_string(),
+		));
 	}
-	.and_then(|x| {
-		if x > 0 {
-			NonZeroU32::new(x).ok_or_else(|| "Invalid Iterations".to_string())
-		} else {
-			Err("Invalid Iterations".to_string())
+
+	let key_byte_length = match (matches.value_of("LENGTH"), matches.value_of("KEY_LENGTH")) {
+		(Some(length), _) => length
+			.parse::<usize>()
+			.ok()
+			.filter(|x| *x > 0)
+			.ok_or_else(|| "Invalid key length".to_string())?,
+		(None, Some(key_length)) => {
+			let key_length = key_length
+				.parse::<usize>()
+				.ok()
+				.filter(|x| *x > 0)
+				.ok_or_else(|| "Invalid key length".to_string())?;
+			if key_length % 8 != 0 {
+				return Err("Invalid key length (must be a multiple of 8)".into());
+			}
+			key_length / 8
 		}
-	})?;
+		// As Django, the length of the digest
+		(None, None) if django => digest_len,
+		(None, None) => 16,
+	};
+
+	let secret: Vec<u8> = if django || matches.is_present("PASSWORD") {
+		base::secret_input(matches, "Password: ")?.into_bytes()
+	} else {
+		let secret = base::input_string(matches)?;
+		secret.parse::<Hex>().map_err(|_| "Invalid secret")?.into()
+	};
 
-	let salt: Vec<u8> = match matches.value_of("SALT") {
-		Some(salt) => salt.parse::<Hex>().map_err(|_| "Invalid salt".to_string()),
-		_ => Err("Invalid salt".to_string()),
-	}?
-	.into();
+	let mut result = vec![0u8; key_byte_length];
 
-	let key_length = match matches.value_of("KEY_LENGTH") {
-		Some(key_length) => key_length
-			.parse::<u32>()
-			.map_err(|_| "Invalid key length".to_string()),
-		_ => Err("Invalid key length".to_string()),
+	with_progress(|| pbkdf2::derive(algo, iterations, &salt, &secret, &mut result));
+
+	if django {
+		// The salt is text, the random one or of --salt
+		let salt = String::from_utf8_lossy(&salt);
+		let hash = general_purpose::STANDARD.encode(&result);
+		return Ok(Output::Lines(vec![format!(
+			"{}${}${}${}",
+			django_name, iterations, salt, hash
+		)]));
 	}
-	.and_then(|x| {
-		if x > 0 {
-			Ok(x)
-		} else {
-			Err("Invalid key length".to_string())
-		}
-	})?;
 
-	let key_byte_length = if key_length % 8 == 0 {
-		Ok(key_length / 8)
+	let result = if matches.is_present("B64") {
+		general_purpose::STANDARD.encode(&result)
 	} else {
-		Err("Invalid key length (must be a multiple of 8)".to_string())
-	}?;
+		Hex::from(result).into()
+	};
 
-	let secret = base::input_string(matches)?;
-	let secret: Vec<u8> = secret.parse::<Hex>().map_err(|_| "Invalid secret")?.into();
+	Ok(Output::Lines(vec![result]))
+}
 
-	let mut result = vec![0u8; key_byte_length as usize];
+/// Check the password against a Django algorithm$iterations$salt$hash
+fn django_verify(matches: &ArgMatches, hash: &str) -> Result<Output, Error> {
+	let invalid = || {
+		Error::invalid_input(
+			"Invalid Django hash: algorithm$iterations$salt$hash is required".to_string(),
+		)
+	};
+	let (name, iterations, salt, expected) = match hash.splitn(4, '$').collect::<Vec<_>>()[..] {
+		[name, iterations, salt, expected] => (name, iterations, salt, expected),
+		_ => return Err(invalid()),
+	};
+	let algo = match name {
+		"pbkdf2_sha256" => PBKDF2_HMAC_SHA256,
+		"pbkdf2_sha1" => PBKDF2_HMAC_SHA1,
+		_ => {
+			return Err(
+				Error::invalid_input(format!("Unsupported algorithm: {}", name))
+					.with_hint("use a pbkdf2_sha256 or pbkdf2_sha1 hash"),
+			)
+		}
+	};
+	let iterations = iterations
+		.parse::<u32>()
+		.ok()
+		.and_then(NonZeroU32::new)
+		.ok_or_else(invalid)?;
+	let expected = general_purpose::STANDARD
+		.decode(expected)
+		.ok()
+		.filter(|x| !x.is_empty())
+		.ok_or_else(invalid)?;
+	let password = base::secret_input(matches, "Password: ")?;
 
-	derive(algo, iterations, &salt, &secret, &mut result);
+	let result = with_progress(|| {
+		pbkdf2::verify(
+			algo,
+			iterations,
+			salt.as_bytes(),
+			password.as_bytes(),
+			&expected,
+		)
+	})
+	.is_ok();
 
-	let result = Hex::from(result).into();
+	if !result {
+		base::set_exit_code(exit_code::INVALID);
+	}
+	if matches.is_present("json") {
+		return Ok(Output::Structured(json!({ "match": result })));
+	}
+
+	Ok(Output::Lines(vec![format!("Match: {}", result)]))
+}
+
+/// The PBKDF2 of --hash, its name without the sha2_ of the older names and its digest length
+fn algorithm(name: &str) -> Result<(pbkdf2::Algorithm, &'static str, usize), Error> {
+	match name {
+		"sha1" => Ok((PBKDF2_HMAC_SHA1, "sha1", SHA1_OUTPUT_LEN)),
+		"sha256" | "sha2_256" => Ok((PBKDF2_HMAC_SHA256, "sha256", SHA256_OUTPUT_LEN)),
+		"sha384" | "sha2_384" => Ok((PBKDF2_HMAC_SHA384, "sha384", SHA384_OUTPUT_LEN)),
+		"sha512" | "sha2_512" => Ok((PBKDF2_HMAC_SHA512, "sha512", SHA512_OUTPUT_LEN)),
+		_ => Err("Invalid algorithm".into()),
+	}
+}
 
-	Ok(vec![result])
+fn django_salt() -> String {
+	rand::thread_rng()
+		.sample_iter(&Alphanumeric)
+		.take(DJANGO_SALT_LENGTH)
+		.map(char::from)
+		.collect()
+}
+
+/// Run f on a worker thread, showing the elapsed time on stderr once it takes more
+/// than a second, as millions of iterations do
+fn with_progress<T: Send>(f: impl FnOnce() -> T + Send) -> T {
+	let start = Instant::now();
+	let (sender, receiver) = mpsc::channel();
+	let result = thread::scope(|scope| {
+		let worker = scope.spawn(move || {
+			let result = f();
+			// The receiver outlives the worker
+			let _ = sender.send(());
+			result
+		});
+		// Disconnected too when f panics, join then resumes the panic
+		while let Err(RecvTimeoutError::Timeout) = receiver.recv_timeout(PROGRESS_DELAY) {
+			log::progress(Some(&format!(
+				"pbkdf2: {}s elapsed",
+				start.elapsed().as_secs()
+			)));
+		}
+		worker
+			.join()
+			.unwrap_or_else(|e| std::panic::resume_unwind(e))
+	});
+	if start.elapsed() >= PROGRESS_DELAY {
+		log::progress(None);
+	}
+	result
 }
 
 mod cases {
@@ -126,22 +314,265 @@ mod cases {
 	pub fn cases() -> LinkedHashMap<&'static str, Vec<Case>> {
 		vec![(
 			"pbkdf2",
-			vec![Case {
-				desc: "".to_string(),
-				input: vec![
-					"-a", "sha2_256", "-s", "0x646566", "-i", "2", "-l", "256", "0x616263",
-				]
-				.into_iter()
-				.map(Into::into)
-				.collect(),
-				output: vec!["0x51a30556d0d133d859d3f3da86f861b7b12546c4f9a193ebb374397467872514"]
+			vec![
+				Case {
+					desc: "".to_string(),
+					input: vec![
+						"-a", "sha2_256", "-s", "0x646566", "-i", "2", "-l", "256", "0x616263",
+					]
 					.into_iter()
 					.map(Into::into)
 					.collect(),
-				is_example: true,
-				is_test: true,
-				since: "0.5.0".to_string(),
-			}],
+					output: vec![
+						"0x51a30556d0d133d859d3f3da86f861b7b12546c4f9a193ebb374397467872514",
+					]
+					.into_iter()
+					.map(Into::into)
+					.collect(),
+					is_example: true,
+					is_test: true,
+					since: "0.5.0".to_string(),
+				},
+				Case {
+					desc: "RFC 6070 test vector 1".to_string(),
+					input: vec![
+						"--hash",
+						"sha1",
+						"--salt",
+						"salt",
+						"-i",
+						"1",
+						"--length",
+						"20",
+						"--password",
+						"password",
+					]
+					.into_iter()
+					.map(Into::into)
+					.collect(),
+					output: vec!["0x0c60c80f961f0e71f3a9b524af6012062fe037a6"]
+						.into_iter()
+						.map(Into::into)
+						.collect(),
+					is_example: true,
+					is_test: true,
+					since: "0.17.0".to_string(),
+				},
+				Case {
+					desc: "RFC 6070 test vector 2".to_string(),
+					input: vec![
+						"--hash",
+						"sha1",
+						"--salt",
+						"salt",
+						"-i",
+						"2",
+						"--length",
+						"20",
+						"--password",
+						"password",
+					]
+					.into_iter()
+					.map(Into::into)
+					.collect(),
+					output: vec!["0xea6c014dc72d6f8ccd1ed92ace1d41f0d8de8957"]
+						.into_iter()
+						.map(Into::into)
+						.collect(),
+					is_example: false,
+					is_test: true,
+					since: "0.17.0".to_string(),
+				},
+				Case {
+					desc: "RFC 6070 test vector 3".to_string(),
+					input: vec![
+						"--hash",
+						"sha1",
+						"--salt",
+						"salt",
+						"-i",
+						"4096",
+						"--length",
+						"20",
+						"--password",
+						"password",
+					]
+					.into_iter()
+					.map(Into::into)
+					.collect(),
+					output: vec!["0x4b007901b765489abead49d926f721d065a429c1"]
+						.into_iter()
+						.map(Into::into)
+						.collect(),
+					is_example: false,
+					is_test: true,
+					since: "0.17.0".to_string(),
+				},
+				Case {
+					desc: "RFC 6070 test vector 4, with a progress on stderr".to_string(),
+					input: vec![
+						"--hash",
+						"sha1",
+						"--salt",
+						"salt",
+						"-i",
+						"16777216",
+						"--length",
+						"20",
+						"--password",
+						"password",
+					]
+					.into_iter()
+					.map(Into::into)
+					.collect(),
+					output: vec!["0xeefe3d61cd4da4e4e9945b3d6ba2158c2634e984"]
+						.into_iter()
+						.map(Into::into)
+						.collect(),
+					is_example: true,
+					is_test: false, // Too slow for the tests
+					since: "0.17.0".to_string(),
+				},
+				Case {
+					desc: "RFC 6070 test vector 5".to_string(),
+					input: vec![
+						"--hash",
+						"sha1",
+						"--salt",
+						"saltSALTsaltSALTsaltSALTsaltSALTsalt",
+						"-i",
+						"4096",
+						"--length",
+						"25",
+						"--password",
+						"passwordPASSWORDpassword",
+					]
+					.into_iter()
+					.map(Into::into)
+					.collect(),
+					output: vec!["0x3d2eec4fe41c849b80c8d83662c0e44a8b291a964cf2f07038"]
+						.into_iter()
+						.map(Into::into)
+						.collect(),
+					is_example: false,
+					is_test: true,
+					since: "0.17.0".to_string(),
+				},
+				Case {
+					desc: "RFC 6070 test vector 6, with NUL bytes".to_string(),
+					input: vec![
+						"--hash",
+						"sha1",
+						"--salt-hex",
+						"0x7361006c74",
+						"-i",
+						"4096",
+						"--length",
+						"16",
+						"0x7061737300776f7264",
+					]
+					.into_iter()
+					.map(Into::into)
+					.collect(),
+					output: vec!["0x56fa6aa75548099dcc37d7f03425e0c3"]
+						.into_iter()
+						.map(Into::into)
+						.collect(),
+					is_example: false,
+					is_test: true,
+					since: "0.17.0".to_string(),
+				},
+				Case {
+					desc: "In base64".to_string(),
+					input: vec![
+						"--hash",
+						"sha1",
+						"--salt",
+						"salt",
+						"-i",
+						"2",
+						"--length",
+						"20",
+						"--b64",
+						"--password",
+						"password",
+					]
+					.into_iter()
+					.map(Into::into)
+					.collect(),
+					output: vec!["6mwBTcctb4zNHtkqzh1B8NjeiVc="]
+						.into_iter()
+						.map(Into::into)
+						.collect(),
+					is_example: true,
+					is_test: true,
+					since: "0.17.0".to_string(),
+				},
+				Case {
+					desc: "Django hash, the salt is random without --salt".to_string(),
+					input: vec!["--django", "-i", "1000", "--salt", "seasalt", "lètmein"]
+						.into_iter()
+						.map(Into::into)
+						.collect(),
+					output: vec![
+						"pbkdf2_sha256$1000$seasalt$JgZryXe2Ga8ysg6XbzkLpTdyPQrHqsinbL9BnnhgX4A=",
+					]
+					.into_iter()
+					.map(Into::into)
+					.collect(),
+					is_example: true,
+					is_test: true,
+					since: "0.17.0".to_string(),
+				},
+				Case {
+					desc: "".to_string(),
+					input: vec![
+						"--django", "--hash", "sha1", "-i", "1000", "--salt", "seasalt", "lètmein",
+					]
+					.into_iter()
+					.map(Into::into)
+					.collect(),
+					output: vec!["pbkdf2_sha1$1000$seasalt$ljleU4wBmTtz/MoG5YTwxpM0d7I="]
+						.into_iter()
+						.map(Into::into)
+						.collect(),
+					is_example: false,
+					is_test: true,
+					since: "0.17.0".to_string(),
+				},
+				Case {
+					desc: "Verify a Django hash".to_string(),
+					input: vec![
+						"--django",
+						"--verify",
+						"'pbkdf2_sha256$1000$seasalt$JgZryXe2Ga8ysg6XbzkLpTdyPQrHqsinbL9BnnhgX4A='",
+						"lètmein",
+					]
+					.into_iter()
+					.map(Into::into)
+					.collect(),
+					output: vec!["Match: true"].into_iter().map(Into::into).collect(),
+					is_example: true,
+					is_test: true,
+					since: "0.17.0".to_string(),
+				},
+				Case {
+					desc: "Wrong password, exits with 4".to_string(),
+					input: vec![
+						"--django",
+						"--verify",
+						"'pbkdf2_sha1$1000$seasalt$ljleU4wBmTtz/MoG5YTwxpM0d7I='",
+						"letmein",
+					]
+					.into_iter()
+					.map(Into::into)
+					.collect(),
+					output: vec!["Match: false"].into_iter().map(Into::into).collect(),
+					is_example: true,
+					is_test: true,
+					since: "0.17.0".to_string(),
+				},
+			],
 		)]
 		.into_iter()
 		.collect()
@@ -157,4 +588,60 @@ mod tests {
 	fn test_cases() {
 		test_module(module());
 	}
+
+	#[test]
+	fn test_django() {
+		let app = &commands()[0].app;
+		let run = |args: Vec<&str>| {
+			let matches = app
+				.clone()
+				.get_matches_from(vec!["pbkdf2", "--django"].into_iter().chain(args));
+			pbkdf2(&matches)
+		};
+
+		let hash = match run(vec!["-i", "10", "password"]) {
+			Ok(Output::Lines(lines)) => lines[0].clone(),
+			output => panic!("Unexpected output: {:?}", output),
+		};
+		let parts = hash.split('$').collect::<Vec<_>>();
+		assert_eq!(parts[..2], ["pbkdf2_sha256", "10"]);
+		assert_eq!(parts[2].len(), DJANGO_SALT_LENGTH);
+		assert_eq!(
+			run(vec!["--verify", hash.as_str(), "password"]),
+			Ok(Output::Lines(vec!["Match: true".to_string()]))
+		);
+
+		assert_eq!(
+			run(vec![
+				"--verify",
+				"argon2$argon2id$v=19$m=102400,t=2,p=8$c2FsdA$aGFzaA",
+				"a"
+			]),
+			Err(
+				Error::invalid_input("Unsupported algorithm: argon2".to_string())
+					.with_hint("use a pbkdf2_sha256 or pbkdf2_sha1 hash")
+			)
+		);
+		assert_eq!(
+			run(vec!["--verify", "pbkdf2_sha256$1000$seasalt", "a"]),
+			Err(Error::invalid_input(
+				"Invalid Django hash: algorithm$iterations$salt$hash is required".to_string()
+			))
+		);
+		assert_eq!(
+			run(vec!["--hash", "sha512", "-i", "1", "a"]),
+			Err(
+				Error::Usage("Django only supports sha256 and sha1".to_string())
+					.with_hint("use --hash sha256")
+			)
+		);
+	}
+
+	#[test]
+	fn test_with_progress() {
+		assert_eq!(with_progress(|| 1 + 1), 2);
+
+		let result = std::panic::catch_unwind(|| with_progress(|| panic!("worker")));
+		assert!(result.is_err());
+	}
 }
@@ -0,0 +1,195 @@
+use crate::app;
+use crate::modules::{base, write_output, Format, JsonStyle, Output};
+use clap::{App, Arg, ArgMatches, SubCommand};
+
+pub fn app<'a, 'b>() -> App<'a, 'b> {
+	SubCommand::with_name("pipe")
+		.about("Run sub commands chained by |, each stage takes the output of the previous one as input")
+		.arg(
+			Arg::with_name("PIPELINE")
+				.help("Sub commands separated by |, e.g. 's2h abc | h2b | b2h'")
+				.required(true)
+				.index(1),
+		)
+}
+
+pub fn run(matches: &ArgMatches) -> Result<Output, String> {
+	let pipeline = matches.value_of("PIPELINE").ok_or("Invalid pipeline")?;
+
+	let (app, module_manager) = app::build_app();
+
+	let mut input = None;
+	let mut output = Output::Lines(vec![]);
+	let stages = split_stages(pipeline)?;
+	for (i, stage) in stages.iter().enumerate() {
+		let stage_error = |e: String| format!("Stage {} ({}): {}", i + 1, stage.trim(), e);
+
+		let args = base::split_args(stage).map_err(stage_error)?;
+		match args.first().map(String::as_str) {
+			None => return Err(stage_error("Empty stage".to_string())),
+			Some("pipe") | Some("repl") => {
+				return Err(stage_error("Can not be used in pipe".to_string()))
+			}
+			_ => (),
+		}
+
+		let stage_matches = app
+			.clone()
+			.get_matches_from_safe(Some("dtool".to_string()).into_iter().chain(args))
+			.map_err(|e| stage_error(e.message))?;
+		let (name, stage_matches) = stage_matches.subcommand();
+		let stage_matches =
+			stage_matches.ok_or_else(|| stage_error("Invalid sub command".to_string()))?;
+
+		base::set_input_override(input.take());
+		let result = module_manager.execute(name, stage_matches);
+		base::set_input_override(None);
+		output = result.map_err(|e| stage_error(e.to_string()))?;
+
+		// Like tee, a stage with --output also writes its output to the file
+		if stage_matches.is_present("output") {
+			let format = Format::new(stage_matches);
+			write_output(
+				output.clone(),
+				&format,
+				stage_matches.value_of("output"),
+				stage_matches.is_present("force"),
+			)
+			.map_err(stage_error)?;
+			if i == stages.len() - 1 {
+				return Ok(Output::Lines(vec![]));
+			}
+		}
+
+		// Feed the next stage, bytes as is and text as joined lines, JSON is compact
+		// unless --json-style says otherwise, as it is not going to a terminal
+		let json = stage_matches.is_present("json");
+		let json_style = JsonStyle::new(stage_matches, false);
+		input = Some(match output {
+			Output::Bytes(ref bytes) if !json => bytes.clone(),
+			_ => output
+				.clone()
+				.render_styled(json, json_style)
+				.map_err(stage_error)?
+				.join("\n")
+				.into_bytes(),
+		});
+	}
+
+	Ok(output)
+}
+
+/// Split a pipeline on the | which are neither quoted nor escaped
+fn split_stages(pipeline: &str) -> Result<Vec<String>, String> {
+	let mut stages = vec![];
+	let mut stage = String::new();
+	let mut quote = None;
+	let mut chars = pipeline.chars();
+	while let Some(c) = chars.next() {
+		match (c, quote) {
+			('\\', Some('\'')) => stage.push(c),
+			('\\', _) => {
+				stage.push(c);
+				if let Some(c) = chars.next() {
+					stage.push(c);
+				}
+			}
+			('\'', None) | ('"', None) => {
+				quote = Some(c);
+				stage.push(c);
+			}
+			(c, Some(q)) if c == q => {
+				quote = None;
+				stage.push(c);
+			}
+			('|', None) => stages.push(std::mem::take(&mut stage)),
+			_ => stage.push(c),
+		}
+	}
+	if quote.is_some() {
+		return Err("Invalid quoting".to_string());
+	}
+	stages.push(stage);
+
+	Ok(stages)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn pipe(pipeline: &str) -> Result<Output, String> {
+		let matches = app().get_matches_from(vec!["pipe", pipeline]);
+		run(&matches)
+	}
+
+	#[test]
+	fn test_split_stages() {
+		assert_eq!(
+			split_stages(r#"s2h 'a|b' | h2s | re -p "\|""#),
+			Ok(vec![
+				"s2h 'a|b' ".to_string(),
+				" h2s ".to_string(),
+				r#" re -p "\|""#.to_string(),
+			])
+		);
+		assert_eq!(split_stages(r"s2h a\|b"), Ok(vec![r"s2h a\|b".to_string()]));
+		assert_eq!(split_stages("s2h 'a|b"), Err("Invalid quoting".to_string()));
+	}
+
+	#[test]
+	fn test_pipe() {
+		assert_eq!(
+			pipe("uuid_gen -v 5 -n dns -s example.com | s2h | h2s"),
+			Ok(Output::Lines(vec![
+				"cfbff0d1-9375-5685-968c-48ce8b15ae17".to_string()
+			]))
+		);
+		assert_eq!(
+			pipe("s2h 'a|b' | h2s"),
+			Ok(Output::Lines(vec!["a|b".to_string()]))
+		);
+	}
+
+	#[test]
+	fn test_pipe_bytes() {
+		assert_eq!(pipe("s2h abc | h2b"), Ok(Output::Bytes(b"abc".to_vec())));
+		assert_eq!(
+			pipe("h2b 0x00ff | b2h"),
+			Ok(Output::Lines(vec!["0x00ff".to_string()]))
+		);
+	}
+
+	#[test]
+	fn test_pipe_output_file() {
+		let path = std::env::temp_dir().join(format!("dtool_pipe_{}", std::process::id()));
+		let path = path.to_string_lossy().to_string();
+
+		let pipeline = format!("s2h abc | h2b --output {} | b2h", path);
+		assert_eq!(
+			pipe(&pipeline),
+			Ok(Output::Lines(vec!["0x616263".to_string()]))
+		);
+		assert_eq!(std::fs::read(&path).unwrap(), b"abc".to_vec());
+
+		let pipeline = format!("s2h abc | s2h --output {} --force", path);
+		assert_eq!(pipe(&pipeline), Ok(Output::Lines(vec![])));
+		assert_eq!(
+			std::fs::read(&path).unwrap(),
+			b"0x3078363136323633\n".to_vec()
+		);
+
+		std::fs::remove_file(&path).unwrap();
+	}
+
+	#[test]
+	fn test_pipe_error() {
+		assert_eq!(
+			pipe("s2h abc | h2s | h2s"),
+			Err("Stage 3 (h2s): Convert failed".to_string())
+		);
+		assert!(pipe("s2h abc | unknown")
+			.unwrap_err()
+			.starts_with("Stage 2 (unknown): "));
+	}
+}
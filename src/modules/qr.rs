@@ -1,10 +1,38 @@
-use crate::modules::{base, Command, Module};
+use crate::log;
+use crate::modules::{base, color, Command, Error, Module, Output};
 use clap::{Arg, ArgMatches, SubCommand};
-use image::Luma;
-use qrcode::QrCode;
-use std::io::{self, Cursor, Write};
+use image::codecs::jpeg::{JpegEncoder, PixelDensity};
+use image::codecs::webp::WebPEncoder;
+use image::{GrayImage, Luma};
+use qrcode::bits::Bits;
+use qrcode::canvas::{Canvas, MaskPattern};
+use qrcode::ec;
+use qrcode::render::unicode::Dense1x2;
+use qrcode::render::{Pixel, Renderer};
+use qrcode::types::{Mode, QrError, QrResult};
+use qrcode::{Color, EcLevel, QrCode, Version};
+use serde_json::json;
+use std::convert::TryFrom;
+use std::io::{self, IsTerminal};
+use std::ops::Index;
 
-pub fn module<'a, 'b>() -> Module<'a, 'b> {
+mod font;
+
+/// Capacity of the densest QR code, version 40 at level L, in bytes, alphanumeric
+/// chars and digits
+const MAX_BYTES: usize = 2953;
+const MAX_ALPHANUMERIC: usize = 4296;
+const MAX_NUMERIC: usize = 7089;
+
+/// Pixels per module
+const DEFAULT_SCALE: u32 = 8;
+/// Below this, the JPEG compression blurs the modules into each other
+const MIN_JPEG_SCALE: u32 = 4;
+const JPEG_QUALITY: u8 = 90;
+/// Light modules around the code, which scanners need to find it
+const QUIET_ZONE: usize = 4;
+
+pub(crate) fn module<'a, 'b>() -> Module<'a, 'b> {
 	Module {
 		desc: "QR Code generation".to_string(),
 		commands: commands(),
@@ -12,51 +40,945 @@ pub fn module<'a, 'b>() -> Module<'a, 'b> {
 	}
 }
 
-pub fn commands<'a, 'b>() -> Vec<Command<'a, 'b>> {
+pub(crate) fn commands<'a, 'b>() -> Vec<Command<'a, 'b>> {
 	vec![Command {
 		app: SubCommand::with_name("s2qr")
-			.about("Convert string to QR code (PNG)")
-			.arg(Arg::with_name("INPUT").required(false).index(1)),
+			.about("Convert string to QR code (PNG, WebP, JPEG or terminal)")
+			.arg(
+				Arg::with_name("FORMAT")
+					.long("format")
+					.help("Image format, webp is lossless, jpeg is lossy and needs a --scale of 4 or more, terminal draws the code as text")
+					.takes_value(true)
+					.possible_values(&["png", "webp", "jpeg", "terminal"])
+					.default_value("png"),
+			)
+			.arg(
+				Arg::with_name("FG")
+					.long("fg")
+					.help("Color of the dark modules in the terminal, monochrome blocks with --color never")
+					.takes_value(true)
+					.default_value("#000000"),
+			)
+			.arg(
+				Arg::with_name("BG")
+					.long("bg")
+					.help("Color of the light modules in the terminal")
+					.takes_value(true)
+					.default_value("#ffffff"),
+			)
+			.arg(
+				Arg::with_name("SCALE")
+					.long("scale")
+					.help("Pixels per module")
+					.takes_value(true)
+					.default_value("8"),
+			)
+			.arg(
+				Arg::with_name("DPI")
+					.long("dpi")
+					.help("Resolution stored in the PNG or JPEG, for the physical size when printed")
+					.takes_value(true)
+					.required(false),
+			)
+			.arg(
+				Arg::with_name("ECI")
+					.long("eci")
+					.help("Declare the charset of the input with an ECI segment")
+					.takes_value(true)
+					.possible_values(&["utf-8", "iso-8859-1", "shift_jis", "big5", "gb18030"])
+					.required(false),
+			)
+			.arg(
+				Arg::with_name("CAPTION")
+					.long("caption")
+					.help("Text drawn below the code, e.g. for a printed label")
+					.takes_value(true),
+			)
+			.arg(
+				Arg::with_name("MASK")
+					.long("mask")
+					.help("Mask pattern 0-7 rather than the one scanners read best, for a reproducible output")
+					.takes_value(true),
+			)
+			.arg(ec_auto_arg())
+			.arg(
+				Arg::with_name("ALLOW_EMPTY")
+					.long("allow-empty")
+					.help("Encode an empty input rather than failing"),
+			)
+			.arg(
+				Arg::with_name("TO_CLIPBOARD")
+					.long("to-clipboard")
+					.help("Copy the image to the clipboard rather than write it to stdout"),
+			)
+			.arg(Arg::with_name("INPUT").required(false).index(1))
+			.arg(base::file_arg()),
 		f: s2qr,
+		serial: false,
+		pure: true,
+	},
+	Command {
+		app: SubCommand::with_name("s2qr_info")
+			.about("Show the QR code version s2qr would use and how much of its capacity the input takes")
+			.arg(ec_auto_arg())
+			.arg(Arg::with_name("INPUT").required(false).index(1))
+			.arg(base::file_arg()),
+		f: s2qr_info,
+		serial: false,
+		pure: true,
 	},
 	Command {
 		app: SubCommand::with_name("qr2s")
 			.about("Convert QR code image to string")
+			.arg(
+				Arg::with_name("FROM_CLIPBOARD")
+					.long("from-clipboard")
+					.help("Decode the image on the clipboard rather than open the browser scanner"),
+			)
+			.arg(
+				Arg::with_name("PDF")
+					.long("pdf")
+					.help("Decode the QR codes of the first page of a PDF rather than open the browser scanner")
+					.takes_value(true)
+					.conflicts_with("FROM_CLIPBOARD"),
+			)
+			.arg(
+				Arg::with_name("ALL_PAGES")
+					.long("all-pages")
+					.help("With --pdf, decode every page rather than the first one")
+					.requires("PDF"),
+			)
 			.arg(Arg::with_name("INPUT").required(false).index(1)), // Kept for compatibility but we read from stdin
 		f: qr2s,
+		serial: true, // Interactive, waits for the browser scanner
+		pure: false,
 	}]
 }
 
-fn s2qr(matches: &ArgMatches) -> Result<Vec<String>, String> {
-	let input = base::input_string(matches)?;
+fn ec_auto_arg<'a, 'b>() -> Arg<'a, 'b> {
+	Arg::with_name("EC_AUTO")
+		.long("ec-auto")
+		.help("Error correction level H, Q or M, the strongest that keeps the version of level M")
+}
+
+/// Image format of `encode_image`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum QrFormat {
+	#[default]
+	Png,
+	/// Lossless
+	Webp,
+	/// Lossy, at a scale of 4 or more
+	Jpeg,
+}
+
+impl QrFormat {
+	fn name(self) -> &'static str {
+		match self {
+			QrFormat::Png => "PNG",
+			QrFormat::Webp => "WebP",
+			QrFormat::Jpeg => "JPEG",
+		}
+	}
+}
+
+/// Options of `encode_image` and `encode_png`
+#[derive(Debug, Clone, Default)]
+pub struct QrOptions {
+	/// Resolution stored in the PNG or JPEG, for the physical size when printed
+	pub dpi: Option<u32>,
+	/// Charset declared with an ECI segment: utf-8, iso-8859-1, shift_jis, big5 or gb18030
+	pub eci: Option<String>,
+	pub format: QrFormat,
+	/// Pixels per module, 8 by default
+	pub scale: Option<u32>,
+	/// Text drawn centered below the code, e.g. for a printed label
+	pub caption: Option<String>,
+	/// Mask pattern 0-7, the one of the fewest penalty points by default
+	pub mask: Option<u8>,
+	/// The strongest error correction level that fits the version of level M, rather
+	/// than M
+	pub ec_auto: bool,
+}
+
+/// The modules of a QR code: `QrCode` has no constructor for a forced mask pattern, so
+/// the symbol drawn by `masked` is kept as its colors
+struct Symbol {
+	colors: Vec<Color>,
+	width: usize,
+	version: Version,
+	level: EcLevel,
+}
+
+impl Symbol {
+	fn render<P: Pixel>(&self) -> Renderer<'_, P> {
+		Renderer::new(&self.colors, self.width, QUIET_ZONE as u32)
+	}
+
+	fn width(&self) -> usize {
+		self.width
+	}
+
+	fn version(&self) -> Version {
+		self.version
+	}
+
+	fn level(&self) -> EcLevel {
+		self.level
+	}
+}
+
+impl From<QrCode> for Symbol {
+	fn from(code: QrCode) -> Self {
+		Self {
+			width: code.width(),
+			version: code.version(),
+			level: code.error_correction_level(),
+			colors: code.into_colors(),
+		}
+	}
+}
+
+impl Index<(usize, usize)> for Symbol {
+	type Output = Color;
+
+	fn index(&self, (x, y): (usize, usize)) -> &Color {
+		&self.colors[y * self.width + x]
+	}
+}
+
+/// Encode data as a QR code image at level M, in a single numeric, alphanumeric or byte
+/// segment (byte with an ECI charset) in the smallest version it fits
+pub fn encode_image(data: &[u8], options: QrOptions) -> Result<Vec<u8>, String> {
+	let mask = options.mask.map(mask_pattern).transpose()?;
+	let code = encode(data, options.eci.as_deref(), mask, options.ec_auto)?;
+	render(&code, &options)
+}
+
+/// `encode_image` in PNG, whatever the format of the options
+pub fn encode_png(data: &[u8], options: QrOptions) -> Result<Vec<u8>, String> {
+	encode_image(
+		data,
+		QrOptions {
+			format: QrFormat::Png,
+			..options
+		},
+	)
+}
 
-	let code = QrCode::new(input.as_bytes()).map_err(|e| format!("Failed to generate QR code: {}", e))?;
+fn s2qr(matches: &ArgMatches) -> Result<Output, Error> {
+	let dpi = match matches.value_of("DPI") {
+		Some(dpi) => Some(dpi.parse::<u32>().map_err(|_| "Invalid dpi")?),
+		None => None,
+	};
+	let scale = matches
+		.value_of("SCALE")
+		.unwrap()
+		.parse::<u32>()
+		.ok()
+		.filter(|x| *x > 0)
+		.ok_or_else(|| Error::Usage("Invalid scale".to_string()))?;
+	let mask = match matches.value_of("MASK") {
+		Some(mask) => Some(
+			mask.parse::<u8>()
+				.ok()
+				.filter(|x| *x < 8)
+				.ok_or_else(|| Error::Usage("Invalid mask (should be 0-7)".to_string()))?,
+		),
+		None => None,
+	};
+	let format = match matches.value_of("FORMAT") {
+		Some("webp") => QrFormat::Webp,
+		Some("jpeg") => QrFormat::Jpeg,
+		_ => QrFormat::Png,
+	};
+	let options = QrOptions {
+		dpi,
+		eci: matches.value_of("ECI").map(String::from),
+		format,
+		scale: Some(scale),
+		caption: matches.value_of("CAPTION").map(String::from),
+		mask,
+		ec_auto: matches.is_present("EC_AUTO"),
+	};
+
+	let input = base::input_bytes_or_empty(matches, matches.is_present("ALLOW_EMPTY"))?;
+	let mask = options.mask.map(mask_pattern).transpose()?;
+	let code = encode(&input, options.eci.as_deref(), mask, options.ec_auto)?;
+	let mode = match options.eci {
+		Some(_) => Mode::Byte,
+		None => auto_mode(&input),
+	};
+	let version = match code.version() {
+		Version::Normal(n) | Version::Micro(n) => n,
+	};
+	log::debug(&format!(
+		"s2qr: version={}, ec={}, mode={}, scale={}, bytes={}, format={}",
+		version,
+		level_name(code.level()),
+		mode_name(mode),
+		scale,
+		input.len(),
+		matches.value_of("FORMAT").unwrap()
+	));
+
+	let terminal = matches.value_of("FORMAT") == Some("terminal");
+	if terminal && options.caption.is_some() {
+		return Err(Error::Usage(
+			"--caption needs an image format: png, webp or jpeg".to_string(),
+		));
+	}
+	let clipboard = matches.is_present("TO_CLIPBOARD");
+	if terminal && clipboard {
+		return Err(Error::Usage(
+			"--to-clipboard copies an image, it cannot be used with --format terminal".to_string(),
+		));
+	}
+	if let Some(output) = base::dry_run(matches, || {
+		let name = if terminal {
+			"for the terminal"
+		} else if clipboard {
+			"to the clipboard"
+		} else {
+			format.name()
+		};
+		format!("a {0}x{0} modules QR code {1}", code.width(), name)
+	}) {
+		return Ok(output);
+	}
+
+	if terminal {
+		let colors = if color::enabled(matches, io::stdout().is_terminal()) {
+			let parse = |name: &str| color::parse_rgb(matches.value_of(name).unwrap());
+			Some((
+				parse("FG").map_err(Error::Usage)?,
+				parse("BG").map_err(Error::Usage)?,
+			))
+		} else {
+			None
+		};
+		return Ok(Output::Lines(render_terminal(&code, colors)));
+	}
+
+	if clipboard {
+		to_clipboard(&render_image(&code, &options))?;
+		return Ok(Output::Lines(vec![]));
+	}
 
-	let image = code.render::<Luma<u8>>().build();
+	if format == QrFormat::Jpeg {
+		log::info("JPEG is lossy, which can hurt scannability, prefer png or webp");
+	}
+	Ok(Output::Bytes(render(&code, &options)?))
+}
+
+fn encode(
+	data: &[u8],
+	eci: Option<&str>,
+	mask: Option<MaskPattern>,
+	ec_auto: bool,
+) -> Result<Symbol, String> {
+	let mode = match eci {
+		Some(_) => Mode::Byte,
+		None => auto_mode(data),
+	};
+	let max = match mode {
+		Mode::Numeric => MAX_NUMERIC,
+		Mode::Alphanumeric => MAX_ALPHANUMERIC,
+		_ => MAX_BYTES,
+	};
+	if data.len() > max {
+		return Err(format!(
+			"Input too large for a single QR ({} bytes, max ~{}); consider --split",
+			data.len(),
+			max
+		));
+	}
+
+	let bits = match eci {
+		Some(charset) => eci_bits(data, eci_designator(charset)?),
+		None => mode_bits(data, mode),
+	};
+	bits.and_then(|mut bits| {
+		let level = match ec_auto {
+			true => strongest_level(&bits)?,
+			false => EcLevel::M,
+		};
+		bits.push_terminator(level)?;
+		match mask {
+			Some(mask) => masked(bits, mask, level),
+			None => QrCode::with_bits(bits, level).map(Symbol::from),
+		}
+	})
+	.map_err(|e| format!("Failed to generate QR code: {}", e))
+}
+
+/// The strongest level the data fits at its version of level M, which is found by
+/// `fit_bits`, e.g. Q for a payload that just spills over version 1
+fn strongest_level(bits: &Bits) -> QrResult<EcLevel> {
+	for level in [EcLevel::H, EcLevel::Q] {
+		if bits.len() <= bits.max_len(level)? {
+			return Ok(level);
+		}
+	}
+	Ok(EcLevel::M)
+}
+
+fn level_name(level: EcLevel) -> &'static str {
+	match level {
+		EcLevel::L => "L",
+		EcLevel::M => "M",
+		EcLevel::Q => "Q",
+		EcLevel::H => "H",
+	}
+}
+
+/// `QrCode::with_bits` with the given mask pattern in place of the best one
+fn masked(bits: Bits, mask: MaskPattern, level: EcLevel) -> QrResult<Symbol> {
+	let version = bits.version();
+	let (data, ec) = ec::construct_codewords(&bits.into_bytes(), version, level)?;
+	let mut canvas = Canvas::new(version, level);
+	canvas.draw_all_functional_patterns();
+	canvas.draw_data(&data, &ec);
+	canvas.apply_mask(mask);
+	Ok(Symbol {
+		colors: canvas.into_colors(),
+		width: version.width() as usize,
+		version,
+		level,
+	})
+}
+
+fn mask_pattern(mask: u8) -> Result<MaskPattern, String> {
+	let pattern = match mask {
+		0 => MaskPattern::Checkerboard,
+		1 => MaskPattern::HorizontalLines,
+		2 => MaskPattern::VerticalLines,
+		3 => MaskPattern::DiagonalLines,
+		4 => MaskPattern::LargeCheckerboard,
+		5 => MaskPattern::Fields,
+		6 => MaskPattern::Diamonds,
+		7 => MaskPattern::Meadow,
+		_ => return Err(format!("Invalid mask {} (should be 0-7)", mask)),
+	};
+	Ok(pattern)
+}
+
+fn render(code: &Symbol, options: &QrOptions) -> Result<Vec<u8>, String> {
+	let scale = options.scale.unwrap_or(DEFAULT_SCALE);
+	if options.format == QrFormat::Jpeg && scale < MIN_JPEG_SCALE {
+		return Err(format!(
+			"JPEG needs a scale of at least {}, its compression blurs smaller modules",
+			MIN_JPEG_SCALE
+		));
+	}
+	let image = render_image(code, options);
 
 	let mut buffer = Vec::new();
-	let mut cursor = Cursor::new(&mut buffer);
-	image
-		.write_to(&mut cursor, image::ImageFormat::Png)
-		.map_err(|e| format!("Failed to write image: {}", e))?;
+	match options.format {
+		QrFormat::Png => write_png(&image, options.dpi, &mut buffer)?,
+		QrFormat::Webp => {
+			if options.dpi.is_some() {
+				return Err("WebP does not store a dpi, use png or jpeg".to_string());
+			}
+			image
+				.write_with_encoder(WebPEncoder::new_lossless(&mut buffer))
+				.map_err(|e| format!("Failed to write image: {}", e))?;
+		}
+		QrFormat::Jpeg => {
+			let mut encoder = JpegEncoder::new_with_quality(&mut buffer, JPEG_QUALITY);
+			if let Some(dpi) = options.dpi {
+				let dpi = u16::try_from(dpi).map_err(|_| "Invalid dpi")?;
+				encoder.set_pixel_density(PixelDensity::dpi(dpi));
+			}
+			image
+				.write_with_encoder(encoder)
+				.map_err(|e| format!("Failed to write image: {}", e))?;
+		}
+	}
 
-	io::stdout()
-		.write_all(&buffer)
-		.map_err(|e| format!("Failed to write to stdout: {}", e))?;
+	Ok(buffer)
+}
 
-	Ok(vec![])
+/// The code drawn at the scale of the options, with their caption
+fn render_image(code: &Symbol, options: &QrOptions) -> GrayImage {
+	let scale = options.scale.unwrap_or(DEFAULT_SCALE);
+	let image = code
+		.render::<Luma<u8>>()
+		.module_dimensions(scale, scale)
+		.build();
+	match &options.caption {
+		Some(caption) => add_caption(&image, caption, scale),
+		None => image,
+	}
 }
 
-fn qr2s(_matches: &ArgMatches) -> Result<Vec<String>, String> {
-	// Use tokio runtime for async operations
-	let runtime = tokio::runtime::Runtime::new().map_err(|e| format!("Failed to create runtime: {}", e))?;
-	
-	runtime.block_on(async {
-		run_qr_scanner().await
+/// The image grown downwards with the caption centered below the quiet zone, in the
+/// largest font up to half a module per font pixel that fits the width, long captions
+/// wrapped at the smallest
+fn add_caption(image: &GrayImage, caption: &str, scale: u32) -> GrayImage {
+	let width = image.width();
+	let chars = caption.chars().count().max(1) as u32;
+	let px = (scale / 2).min(width / (chars * font::ADVANCE)).max(1);
+	let lines = wrap(caption, (width / (font::ADVANCE * px)).max(1) as usize);
+
+	let line_height = (font::HEIGHT + 2) * px;
+	let height = image.height() + lines.len() as u32 * line_height + scale;
+	let mut result = GrayImage::from_pixel(width, height, Luma([255]));
+	image::imageops::replace(&mut result, image, 0, 0);
+
+	for (i, line) in lines.iter().enumerate() {
+		let text_width = (line.chars().count() as u32 * font::ADVANCE).saturating_sub(1) * px;
+		let left = width.saturating_sub(text_width) / 2;
+		let top = image.height() + i as u32 * line_height;
+		for (j, c) in line.chars().enumerate() {
+			let char_left = left + j as u32 * font::ADVANCE * px;
+			for (x, y) in (0..font::WIDTH).flat_map(|x| (0..font::HEIGHT).map(move |y| (x, y))) {
+				if !font::is_set(c, x, y) {
+					continue;
+				}
+				for (dx, dy) in (0..px).flat_map(|dx| (0..px).map(move |dy| (dx, dy))) {
+					let (x, y) = (char_left + x * px + dx, top + y * px + dy);
+					if x < width {
+						result.put_pixel(x, y, Luma([0]));
+					}
+				}
+			}
+		}
+	}
+	result
+}
+
+/// Lines of at most `max` chars, broken between words, or in a word longer than a line
+fn wrap(text: &str, max: usize) -> Vec<String> {
+	let mut lines = vec![];
+	let mut line = String::new();
+	for word in text.split_whitespace() {
+		let mut word = word.chars().collect::<Vec<_>>();
+		let len = line.chars().count();
+		if len > 0 && len + 1 + word.len() <= max {
+			line.push(' ');
+			line.extend(word);
+			continue;
+		}
+		if len > 0 {
+			lines.push(line);
+		}
+		while word.len() > max {
+			lines.push(word.drain(..max).collect());
+		}
+		line = word.into_iter().collect();
+	}
+	if !line.is_empty() || lines.is_empty() {
+		lines.push(line);
+	}
+	lines
+}
+
+/// The code as text lines: two spaces per module on 24-bit backgrounds given the
+/// (dark, light) colors, half blocks otherwise
+fn render_terminal(code: &Symbol, colors: Option<([u8; 3], [u8; 3])>) -> Vec<String> {
+	let (dark, light) = match colors {
+		Some(colors) => colors,
+		// Blocks are drawn in the foreground color, light on most terminals
+		None => {
+			return code
+				.render::<Dense1x2>()
+				.dark_color(Dense1x2::Light)
+				.light_color(Dense1x2::Dark)
+				.build()
+				.lines()
+				.map(String::from)
+				.collect()
+		}
+	};
+
+	let width = code.width();
+	let size = width + 2 * QUIET_ZONE;
+	let is_dark = |x: usize, y: usize| {
+		(QUIET_ZONE..QUIET_ZONE + width).contains(&x)
+			&& (QUIET_ZONE..QUIET_ZONE + width).contains(&y)
+			&& code[(x - QUIET_ZONE, y - QUIET_ZONE)] == Color::Dark
+	};
+	(0..size)
+		.map(|y| {
+			let mut line = String::new();
+			let mut x = 0;
+			// One escape per run of modules of the same color
+			while x < size {
+				let start = x;
+				let run_dark = is_dark(x, y);
+				while x < size && is_dark(x, y) == run_dark {
+					x += 1;
+				}
+				let rgb = if run_dark { dark } else { light };
+				line.push_str(&color::background(&"  ".repeat(x - start), rgb));
+			}
+			line
+		})
+		.collect()
+}
+
+fn write_png(image: &GrayImage, dpi: Option<u32>, buffer: &mut Vec<u8>) -> Result<(), String> {
+	let mut encoder = png::Encoder::new(buffer, image.width(), image.height());
+	encoder.set_color(png::ColorType::Grayscale);
+	encoder.set_depth(png::BitDepth::Eight);
+	// pHYs chunk, PNG only stores pixels per meter
+	encoder.set_pixel_dims(dpi.map(|dpi| {
+		let ppm = (dpi as f64 / 0.0254).round() as u32;
+		png::PixelDimensions {
+			xppu: ppm,
+			yppu: ppm,
+			unit: png::Unit::Meter,
+		}
+	}));
+	encoder
+		.write_header()
+		.and_then(|mut writer| writer.write_image_data(image.as_raw()))
+		.map_err(|e| format!("Failed to write image: {}", e))
+}
+
+fn s2qr_info(matches: &ArgMatches) -> Result<Output, Error> {
+	let input = base::input_bytes(matches)?;
+
+	// The data as s2qr encodes it, measured without the terminator and padding
+	let mode = auto_mode(&input);
+	let bits = mode_bits(&input, mode).map_err(|e| format!("Failed to generate QR code: {}", e))?;
+	let used = bits.len().div_ceil(8);
+	let level = match matches.is_present("EC_AUTO") {
+		true => strongest_level(&bits),
+		false => Ok(EcLevel::M),
+	};
+	let level = level.map_err(|e| format!("Failed to generate QR code: {}", e))?;
+	let capacity = bits
+		.max_len(level)
+		.map_err(|e| format!("Failed to generate QR code: {}", e))?
+		/ 8;
+	let remaining = capacity - used;
+	let version = match bits.version() {
+		Version::Normal(n) | Version::Micro(n) => n,
+	};
+
+	if matches.is_present("json") {
+		return Ok(Output::Structured(json!({
+			"payload": input.len(),
+			"mode": mode_name(mode),
+			"version": version,
+			"level": level_name(level),
+			"capacity": capacity,
+			"used": used,
+			"remaining": remaining,
+		})));
+	}
+
+	Ok(Output::Lines(vec![
+		format!("Payload: {} bytes", input.len()),
+		format!("Mode: {}", mode_name(mode)),
+		format!(
+			"Capacity at version {} / level {}: {} bytes ({}% used)",
+			version,
+			level_name(level),
+			capacity,
+			(used as f64 * 100.0 / capacity as f64).round()
+		),
+		format!("Remaining: {} bytes", remaining),
+	]))
+}
+
+fn eci_designator(charset: &str) -> Result<u32, String> {
+	match charset {
+		"iso-8859-1" => Ok(3),
+		"shift_jis" => Ok(20),
+		"utf-8" => Ok(26),
+		"big5" => Ok(28),
+		"gb18030" => Ok(29),
+		_ => Err("Invalid eci".to_string()),
+	}
+}
+
+/// Numeric or alphanumeric mode when the whole input qualifies, byte mode otherwise
+fn auto_mode(data: &[u8]) -> Mode {
+	let is_alphanumeric =
+		|c: &u8| c.is_ascii_digit() || c.is_ascii_uppercase() || b" $%*+-./:".contains(c);
+	if data.is_empty() {
+		Mode::Byte
+	} else if data.iter().all(u8::is_ascii_digit) {
+		Mode::Numeric
+	} else if data.iter().all(is_alphanumeric) {
+		Mode::Alphanumeric
+	} else {
+		Mode::Byte
+	}
+}
+
+fn mode_name(mode: Mode) -> &'static str {
+	match mode {
+		Mode::Numeric => "numeric",
+		Mode::Alphanumeric => "alphanumeric",
+		Mode::Byte => "byte",
+		Mode::Kanji => "kanji",
+	}
+}
+
+/// Data in a single segment of the mode, in the smallest version it fits
+fn mode_bits(data: &[u8], mode: Mode) -> Result<Bits, QrError> {
+	fit_bits(|bits| match mode {
+		Mode::Numeric => bits.push_numeric_data(data),
+		Mode::Alphanumeric => bits.push_alphanumeric_data(data),
+		_ => bits.push_byte_data(data),
 	})
 }
 
-async fn run_qr_scanner() -> Result<Vec<String>, String> {
+/// Byte data behind an ECI segment, in the smallest version it fits
+fn eci_bits(data: &[u8], eci: u32) -> Result<Bits, QrError> {
+	fit_bits(|bits| {
+		bits.push_eci_designator(eci)?;
+		bits.push_byte_data(data)
+	})
+}
+
+/// The segments pushed by push in the smallest version they fit at level M,
+/// without the terminator
+fn fit_bits<F: Fn(&mut Bits) -> Result<(), QrError>>(push: F) -> Result<Bits, QrError> {
+	for version in 1..=40 {
+		let mut bits = Bits::new(Version::Normal(version));
+		match push(&mut bits) {
+			Ok(()) if bits.len() <= bits.max_len(EcLevel::M)? => return Ok(bits),
+			Ok(()) | Err(QrError::DataTooLong) => continue,
+			Err(e) => return Err(e),
+		}
+	}
+	Err(QrError::DataTooLong)
+}
+
+fn qr2s(matches: &ArgMatches) -> Result<Output, Error> {
+	if let Some(path) = matches.value_of("PDF") {
+		let result = from_pdf(path, matches.is_present("ALL_PAGES"))?;
+		if matches.is_present("json") {
+			return Ok(Output::Structured(json!({ "data": result })));
+		}
+		return Ok(Output::Lines(result));
+	}
+
+	let result = if matches.is_present("FROM_CLIPBOARD") {
+		from_clipboard()?
+	} else {
+		// Use tokio runtime for async operations
+		let runtime = tokio::runtime::Runtime::new()
+			.map_err(|e| format!("Failed to create runtime: {}", e))?;
+		runtime.block_on(async { run_qr_scanner().await })?
+	};
+
+	if matches.is_present("json") {
+		return Ok(Output::Structured(json!({ "data": result })));
+	}
+
+	Ok(Output::Lines(vec![result]))
+}
+
+/// Image source of `qr2s --from-clipboard` and sink of `s2qr --to-clipboard`, the tests
+/// stub the system clipboard
+#[cfg(feature = "clipboard")]
+trait ClipboardImage {
+	/// The image on the clipboard, None when it holds text or nothing
+	fn image(&mut self) -> Result<Option<GrayImage>, String>;
+	/// Replace the content of the clipboard with the image
+	fn set_image(&mut self, image: image::RgbaImage) -> Result<(), String>;
+}
+
+#[cfg(feature = "clipboard")]
+struct SystemClipboard(arboard::Clipboard);
+
+#[cfg(feature = "clipboard")]
+impl ClipboardImage for SystemClipboard {
+	fn image(&mut self) -> Result<Option<GrayImage>, String> {
+		let image = match self.0.get_image() {
+			Ok(image) => image,
+			Err(arboard::Error::ContentNotAvailable) => return Ok(None),
+			Err(e) => return Err(format!("Failed to read the clipboard: {}", e)),
+		};
+		let (width, height) = (image.width as u32, image.height as u32);
+		let rgba = image::RgbaImage::from_raw(width, height, image.bytes.into_owned())
+			.ok_or("Invalid clipboard image")?;
+		Ok(Some(image::DynamicImage::ImageRgba8(rgba).into_luma8()))
+	}
+
+	fn set_image(&mut self, image: image::RgbaImage) -> Result<(), String> {
+		let image = arboard::ImageData {
+			width: image.width() as usize,
+			height: image.height() as usize,
+			bytes: image.into_raw().into(),
+		};
+		self.0.set_image(image).map_err(|e| match e {
+			arboard::Error::ClipboardNotSupported => {
+				"The clipboard of this platform does not take images".to_string()
+			}
+			e => format!("Failed to write the clipboard: {}", e),
+		})
+	}
+}
+
+#[cfg(feature = "clipboard")]
+fn from_clipboard() -> Result<String, Error> {
+	let clipboard = arboard::Clipboard::new()
+		.map_err(|e| Error::external("Failed to open the clipboard", e))?;
+	decode_clipboard(&mut SystemClipboard(clipboard))
+}
+
+#[cfg(not(feature = "clipboard"))]
+fn from_clipboard() -> Result<String, Error> {
+	Err(
+		Error::Usage("--from-clipboard needs dtool built with the clipboard feature".to_string())
+			.with_hint("cargo install dtool --features clipboard"),
+	)
+}
+
+/// On Linux, the image stays on the clipboard after dtool exits only when a clipboard
+/// manager takes it over
+#[cfg(feature = "clipboard")]
+fn to_clipboard(image: &GrayImage) -> Result<(), Error> {
+	let clipboard = arboard::Clipboard::new()
+		.map_err(|e| Error::external("Failed to open the clipboard", e))
+		.map_err(no_clipboard_hint)?;
+	copy_clipboard(&mut SystemClipboard(clipboard), image).map_err(no_clipboard_hint)
+}
+
+#[cfg(feature = "clipboard")]
+fn no_clipboard_hint(e: Error) -> Error {
+	e.with_hint("without an image clipboard, e.g. over ssh, write the image to a file: dtool s2qr <input> > qr.png")
+}
+
+#[cfg(not(feature = "clipboard"))]
+fn to_clipboard(_image: &GrayImage) -> Result<(), Error> {
+	Err(
+		Error::Usage("--to-clipboard needs dtool built with the clipboard feature".to_string())
+			.with_hint("cargo install dtool --features clipboard"),
+	)
+}
+
+#[cfg(feature = "clipboard")]
+fn copy_clipboard(clipboard: &mut dyn ClipboardImage, image: &GrayImage) -> Result<(), Error> {
+	let rgba = image::DynamicImage::ImageLuma8(image.clone()).into_rgba8();
+	clipboard.set_image(rgba)?;
+	log::info(&format!(
+		"Copied a {}x{} QR code image to the clipboard",
+		image.width(),
+		image.height()
+	));
+	Ok(())
+}
+
+#[cfg(feature = "clipboard")]
+fn decode_clipboard(clipboard: &mut dyn ClipboardImage) -> Result<String, Error> {
+	let image = clipboard
+		.image()?
+		.ok_or_else(|| Error::Other("No image on clipboard".to_string()))?;
+
+	let not_found = || Error::invalid_input("No QR code found in the clipboard image".to_string());
+	decode_image(&image)
+		.into_iter()
+		.next()
+		.unwrap_or_else(|| Err(not_found()))
+}
+
+/// The content of each QR code found in the image
+#[cfg(any(feature = "clipboard", feature = "pdf"))]
+fn decode_image(image: &GrayImage) -> Vec<Result<String, Error>> {
+	let mut prepared = rqrr::PreparedImage::prepare_from_greyscale(
+		image.width() as usize,
+		image.height() as usize,
+		|x, y| image.get_pixel(x as u32, y as u32)[0],
+	);
+	prepared
+		.detect_grids()
+		.iter()
+		.map(|grid| {
+			let (_, content) = grid
+				.decode()
+				.map_err(|e| Error::invalid_input(format!("Failed to decode QR code: {}", e)))?;
+			Ok(content)
+		})
+		.collect()
+}
+
+/// Rendered at this resolution, the modules of a code printed on a page are several
+/// pixels wide
+#[cfg(feature = "pdf")]
+const PDF_DPI: f32 = 200.0;
+
+#[cfg(feature = "pdf")]
+fn from_pdf(path: &str, all_pages: bool) -> Result<Vec<String>, Error> {
+	use pdfium_render::prelude::{PdfRenderConfig, Pdfium, PdfiumError};
+
+	let bindings = Pdfium::bind_to_system_library().map_err(|e| {
+		Error::external("Failed to load the Pdfium library", e)
+			.with_hint("install libpdfium, e.g. from https://github.com/bblanchon/pdfium-binaries, on the library path")
+	})?;
+	let pdfium = Pdfium::new(bindings);
+	let document = pdfium.load_pdf_from_file(path, None).map_err(|e| match e {
+		PdfiumError::IoError(e) => Error::io(&format!("Failed to read {}", path), e),
+		e => Error::invalid_input(format!("Invalid PDF {}: {}", path, e)),
+	})?;
+
+	// Points are 1/72 inch
+	let config = PdfRenderConfig::new().scale_page_by_factor(PDF_DPI / 72.0);
+	let pages = match all_pages {
+		true => document.pages().len() as usize,
+		false => 1,
+	};
+	let images = document
+		.pages()
+		.iter()
+		.take(pages)
+		.map(|page| {
+			let bitmap = page
+				.render_with_config(&config)
+				.map_err(|e| Error::external(&format!("Failed to render {}", path), e))?;
+			Ok(bitmap.as_image().into_luma8())
+		})
+		.collect::<Result<Vec<_>, Error>>()?;
+
+	decode_pages(&images, all_pages)
+}
+
+#[cfg(not(feature = "pdf"))]
+fn from_pdf(_path: &str, _all_pages: bool) -> Result<Vec<String>, Error> {
+	Err(
+		Error::Usage("--pdf needs dtool built with the pdf feature".to_string())
+			.with_hint("cargo install dtool --features pdf"),
+	)
+}
+
+/// The content of the QR codes of the pages in order, a code that fails to decode is
+/// skipped as long as another one decodes
+#[cfg(feature = "pdf")]
+fn decode_pages(images: &[GrayImage], all_pages: bool) -> Result<Vec<String>, Error> {
+	let mut result = vec![];
+	let mut error = None;
+	for (i, image) in images.iter().enumerate() {
+		let codes = decode_image(image);
+		log::debug(&format!("Page {}: {} QR codes", i + 1, codes.len()));
+		for code in codes {
+			match code {
+				Ok(content) => result.push(content),
+				Err(e) => error = Some(e),
+			}
+		}
+	}
+
+	match (result.is_empty(), error) {
+		(true, Some(e)) => Err(e),
+		(true, None) => {
+			let error = Error::invalid_input("No QR code found in the PDF".to_string());
+			match all_pages {
+				true => Err(error),
+				false => Err(error.with_hint("only the first page is decoded without --all-pages")),
+			}
+		}
+		(false, _) => Ok(result),
+	}
+}
+
+async fn run_qr_scanner() -> Result<String, String> {
 	use axum::{
 		routing::{get, post},
 		Router,
@@ -190,11 +1112,14 @@ async fn run_qr_scanner() -> Result<Vec<String>, String> {
 		.map_err(|e| format!("Failed to get local address: {}", e))?;
 	
 	let url = format!("http://{}", addr);
-	eprintln!("QR Scanner running at: {}", url);
+	log::info(&format!("QR Scanner running at: {}", url));
 	
 	// Open browser
 	if let Err(e) = open_browser(&url) {
-		eprintln!("Failed to open browser: {}. Please open {} manually.", e, url);
+		log::info(&format!(
+			"Failed to open browser: {}. Please open {} manually.",
+			e, url
+		));
 	}
 	
 	// Convert std TcpListener to tokio
@@ -216,7 +1141,7 @@ async fn run_qr_scanner() -> Result<Vec<String>, String> {
 	// Abort server
 	server_handle.abort();
 	
-	Ok(vec![result])
+	Ok(result)
 }
 
 fn open_browser(url: &str) -> Result<(), String> {
@@ -255,25 +1180,136 @@ mod cases {
 		vec![
 			(
 				"s2qr",
-				vec![Case {
-					desc: "Generate QR code for 'hello'".to_string(),
-					input: vec!["hello".to_string()],
-					output: vec![],
-					is_example: true,
-					is_test: false, // Output is binary, hard to test with string comparison
-					since: "0.15.0".to_string(),
-				}],
+				vec![
+					Case {
+						desc: "Generate QR code for 'hello'".to_string(),
+						input: vec!["hello".to_string()],
+						output: vec![],
+						is_example: true,
+						is_test: false, // Output is binary, hard to test with string comparison
+						since: "0.15.0".to_string(),
+					},
+					Case {
+						desc: "Generate QR code for printing at 300 DPI".to_string(),
+						input: vec!["--dpi".to_string(), "300".to_string(), "hello".to_string()],
+						output: vec![],
+						is_example: true,
+						is_test: false, // Output is binary, covered by test_s2qr_dpi
+						since: "0.17.0".to_string(),
+					},
+					Case {
+						desc: "Generate QR code declaring UTF-8 for non-Latin text".to_string(),
+						input: vec!["--eci".to_string(), "utf-8".to_string(), "你好".to_string()],
+						output: vec![],
+						is_example: true,
+						is_test: false, // Output is binary, covered by test_s2qr_eci
+						since: "0.17.0".to_string(),
+					},
+					Case {
+						desc: "Generate a lossless WebP QR code".to_string(),
+						input: vec![
+							"--format".to_string(),
+							"webp".to_string(),
+							"hello".to_string(),
+						],
+						output: vec![],
+						is_example: true,
+						is_test: false, // Output is binary, covered by test_s2qr_formats
+						since: "0.17.0".to_string(),
+					},
+					Case {
+						desc: "Draw a colored QR code in the terminal".to_string(),
+						input: vec![
+							"--format".to_string(),
+							"terminal".to_string(),
+							"--fg".to_string(),
+							"#1a237e".to_string(),
+							"hello".to_string(),
+						],
+						output: vec![],
+						is_example: true,
+						is_test: false, // Depends on the terminal, covered by test_s2qr_terminal
+						since: "0.17.0".to_string(),
+					},
+					Case {
+						desc: "Label with the text below the code".to_string(),
+						input: vec![
+							"--caption".to_string(),
+							"'Asset 0042'".to_string(),
+							"https://example.com/assets/0042".to_string(),
+						],
+						output: vec![],
+						is_example: true,
+						is_test: false, // Output is binary, covered by test_s2qr_caption
+						since: "0.17.0".to_string(),
+					},
+					Case {
+						desc: "Fixed mask pattern, for a reproducible image".to_string(),
+						input: vec!["--mask".to_string(), "3".to_string(), "abc".to_string()],
+						output: vec![],
+						is_example: true,
+						is_test: false, // Output is binary, covered by test_s2qr_mask
+						since: "0.17.0".to_string(),
+					},
+				],
+			),
+			(
+				"s2qr_info",
+				vec![
+					Case {
+						desc: "".to_string(),
+						input: vec!["hello".to_string()],
+						output: vec![
+							"Payload: 5 bytes".to_string(),
+							"Mode: byte".to_string(),
+							"Capacity at version 1 / level M: 16 bytes (44% used)".to_string(),
+							"Remaining: 9 bytes".to_string(),
+						],
+						is_example: true,
+						is_test: true,
+						since: "0.17.0".to_string(),
+					},
+					Case {
+						desc: "The strongest level that keeps the version".to_string(),
+						input: vec!["--ec-auto".to_string(), "hello".to_string()],
+						output: vec![
+							"Payload: 5 bytes".to_string(),
+							"Mode: byte".to_string(),
+							"Capacity at version 1 / level H: 9 bytes (78% used)".to_string(),
+							"Remaining: 2 bytes".to_string(),
+						],
+						is_example: true,
+						is_test: true,
+						since: "0.17.0".to_string(),
+					},
+				],
 			),
 			(
 				"qr2s",
-				vec![Case {
-					desc: "Scan QR code from camera (interactive)".to_string(),
-					input: vec![],
-					output: vec![],
-					is_example: true,
-					is_test: false, // Interactive web-based command, cannot be tested automatically
-					since: "0.15.0".to_string(),
-				}],
+				vec![
+					Case {
+						desc: "Scan QR code from camera (interactive)".to_string(),
+						input: vec![],
+						output: vec![],
+						is_example: true,
+						is_test: false, // Interactive web-based command, cannot be tested automatically
+						since: "0.15.0".to_string(),
+					},
+					Case {
+						desc: "Decode the QR code of the first page of a PDF".to_string(),
+						input: vec!["--pdf", "invoice.pdf"]
+							.into_iter()
+							.map(Into::into)
+							.collect(),
+						output: vec!["https://example.com/invoice/INV-2024-0042"]
+							.into_iter()
+							.map(Into::into)
+							.collect(),
+						is_example: true,
+						is_test: false, // Needs the pdf feature and libpdfium, covered by test_qr2s_pdf
+						since: "0.17.0".to_string(),
+					},
+				],
 			),
 		]
 		.into_iter()
@@ -284,10 +1320,468 @@ mod cases {
 #[cfg(test)]
 mod tests {
 	use super::*;
+	use crate::app;
 	use crate::modules::base::test::test_module;
 
 	#[test]
 	fn test_cases() {
 		test_module(module());
 	}
+
+	#[test]
+	fn test_s2qr_dpi() {
+		let app = &commands()[0].app;
+
+		let matches = app
+			.clone()
+			.get_matches_from(vec!["s2qr", "--dpi", "300", "hello"]);
+		let png = match s2qr(&matches) {
+			Ok(Output::Bytes(png)) => png,
+			output => panic!("Unexpected output: {:?}", output),
+		};
+
+		let reader = png::Decoder::new(std::io::Cursor::new(png))
+			.read_info()
+			.unwrap();
+		let pixel_dims = reader.info().pixel_dims.unwrap();
+		assert_eq!(pixel_dims.xppu, 11811);
+		assert_eq!(pixel_dims.yppu, 11811);
+		assert_eq!(pixel_dims.unit, png::Unit::Meter);
+	}
+
+	#[test]
+	fn test_encode_png() {
+		let png = encode_png(
+			b"hello",
+			QrOptions {
+				dpi: Some(300),
+				..Default::default()
+			},
+		)
+		.unwrap();
+		let reader = png::Decoder::new(std::io::Cursor::new(png))
+			.read_info()
+			.unwrap();
+		assert_eq!(reader.info().pixel_dims.unwrap().xppu, 11811);
+
+		let options = QrOptions {
+			eci: Some("latin1".to_string()),
+			..Default::default()
+		};
+		assert_eq!(
+			encode_png(b"hello", options),
+			Err("Invalid eci".to_string())
+		);
+		assert_eq!(
+			encode_png(&[b'a'; MAX_BYTES + 1], QrOptions::default()),
+			Err(
+				"Input too large for a single QR (2954 bytes, max ~2953); consider --split"
+					.to_string()
+			)
+		);
+	}
+
+	#[test]
+	fn test_s2qr_terminal() {
+		let app = commands()[0].app.clone().args(&app::global_args());
+		let s2qr_terminal = |args: Vec<&str>| {
+			let matches = app
+				.clone()
+				.get_matches_from(vec!["s2qr", "--format", "terminal"].into_iter().chain(args));
+			s2qr(&matches)
+		};
+
+		let lines = match s2qr_terminal(vec![
+			"--color", "always", "--fg", "#112233", "--bg", "#fafafa", "hello",
+		]) {
+			Ok(Output::Lines(lines)) => lines,
+			output => panic!("Unexpected output: {:?}", output),
+		};
+		// Version 1, 21 modules and the quiet zone, a line per module
+		assert_eq!(lines.len(), 29);
+		assert!(lines[0].starts_with("\x1b[48;2;250;250;250m"));
+		assert!(lines[4].contains("\x1b[48;2;17;34;51m"));
+		assert!(lines.iter().all(|line| line.ends_with("\x1b[0m")));
+
+		let lines = match s2qr_terminal(vec!["--color", "never", "--fg", "#112233", "hello"]) {
+			Ok(Output::Lines(lines)) => lines,
+			output => panic!("Unexpected output: {:?}", output),
+		};
+		assert!(lines.iter().all(|line| !line.contains("\x1b[")));
+		assert!(lines.iter().any(|line| line.contains('\u{2588}')));
+		// Two modules per line
+		assert_eq!(lines.len(), 15);
+
+		assert_eq!(
+			s2qr_terminal(vec!["--color", "always", "--fg", "black", "hello"]),
+			Err(Error::Usage(
+				"Invalid color: black, e.g. #000000".to_string()
+			))
+		);
+	}
+
+	#[test]
+	fn test_s2qr_formats() {
+		let app = &commands()[0].app;
+		let s2qr_format = |args: Vec<&str>| {
+			let matches = app
+				.clone()
+				.get_matches_from(vec!["s2qr"].into_iter().chain(args));
+			match s2qr(&matches) {
+				Ok(Output::Bytes(image)) => image,
+				output => panic!("Unexpected output: {:?}", output),
+			}
+		};
+
+		let webp = s2qr_format(vec!["--format", "webp", "hello"]);
+		assert_eq!(&webp[..4], b"RIFF");
+		assert_eq!(&webp[8..12], b"WEBP");
+		// Lossless, the modules come back exactly
+		let decoded = image::load_from_memory_with_format(&webp, image::ImageFormat::WebP)
+			.unwrap()
+			.to_luma8();
+		let code = encode(b"hello", None, None, false).unwrap();
+		assert_eq!(decoded, code.render::<Luma<u8>>().build());
+
+		let jpeg = s2qr_format(vec!["--format", "jpeg", "--scale", "4", "hello"]);
+		assert_eq!(&jpeg[..3], b"\xff\xd8\xff");
+
+		let matches = app
+			.clone()
+			.get_matches_from(vec!["s2qr", "--format", "jpeg", "--scale", "2", "hello"]);
+		assert_eq!(
+			s2qr(&matches),
+			Err(Error::Other(
+				"JPEG needs a scale of at least 4, its compression blurs smaller modules"
+					.to_string()
+			))
+		);
+	}
+
+	#[test]
+	fn test_s2qr_caption() {
+		let app = &commands()[0].app;
+		let s2qr_png = |args: Vec<&str>| {
+			let matches = app
+				.clone()
+				.get_matches_from(vec!["s2qr"].into_iter().chain(args));
+			match s2qr(&matches) {
+				Ok(Output::Bytes(png)) => {
+					image::load_from_memory_with_format(&png, image::ImageFormat::Png)
+						.unwrap()
+						.to_luma8()
+				}
+				output => panic!("Unexpected output: {:?}", output),
+			}
+		};
+
+		let plain = s2qr_png(vec!["hello"]);
+		let captioned = s2qr_png(vec!["--caption", "Serial 0042", "hello"]);
+		assert_eq!(captioned.width(), plain.width());
+		assert!(captioned.height() > plain.height());
+		// The code and its quiet zone are left as is, the caption is below
+		let top = image::imageops::crop_imm(&captioned, 0, 0, plain.width(), plain.height());
+		assert_eq!(top.to_image(), plain);
+		assert!((plain.height()..captioned.height())
+			.any(|y| (0..captioned.width()).any(|x| captioned.get_pixel(x, y)[0] == 0)));
+
+		let mut prepared = rqrr::PreparedImage::prepare_from_greyscale(
+			captioned.width() as usize,
+			captioned.height() as usize,
+			|x, y| captioned.get_pixel(x as u32, y as u32)[0],
+		);
+		let grids = prepared.detect_grids();
+		assert_eq!(grids.len(), 1);
+		assert_eq!(grids[0].decode().unwrap().1, "hello");
+
+		let matches = app.clone().get_matches_from(vec![
+			"s2qr",
+			"--format",
+			"terminal",
+			"--caption",
+			"Serial 0042",
+			"hello",
+		]);
+		assert_eq!(
+			s2qr(&matches),
+			Err(Error::Usage(
+				"--caption needs an image format: png, webp or jpeg".to_string()
+			))
+		);
+	}
+
+	#[test]
+	fn test_s2qr_mask() {
+		let app = &commands()[0].app;
+		let s2qr_png = |args: Vec<&str>| {
+			let matches = app
+				.clone()
+				.get_matches_from(vec!["s2qr"].into_iter().chain(args));
+			s2qr(&matches)
+		};
+		let decode = |png: &[u8]| {
+			let image = image::load_from_memory_with_format(png, image::ImageFormat::Png)
+				.unwrap()
+				.to_luma8();
+			let mut prepared = rqrr::PreparedImage::prepare_from_greyscale(
+				image.width() as usize,
+				image.height() as usize,
+				|x, y| image.get_pixel(x as u32, y as u32)[0],
+			);
+			let grids = prepared.detect_grids();
+			assert_eq!(grids.len(), 1);
+			let (meta, content) = grids[0].decode().unwrap();
+			(meta.mask, content)
+		};
+
+		let mask0 = match s2qr_png(vec!["--mask", "0", "hello"]) {
+			Ok(Output::Bytes(png)) => png,
+			output => panic!("Unexpected output: {:?}", output),
+		};
+		let mask5 = match s2qr_png(vec!["--mask", "5", "hello"]) {
+			Ok(Output::Bytes(png)) => png,
+			output => panic!("Unexpected output: {:?}", output),
+		};
+		assert_ne!(mask0, mask5);
+		assert_eq!(decode(&mask0), (0, "hello".to_string()));
+		assert_eq!(decode(&mask5), (5, "hello".to_string()));
+		assert_eq!(
+			s2qr_png(vec!["--mask", "0", "hello"]),
+			Ok(Output::Bytes(mask0))
+		);
+
+		for mask in &["8", "256", "x"] {
+			assert_eq!(
+				s2qr_png(vec!["--mask", mask, "hello"]),
+				Err(Error::Usage("Invalid mask (should be 0-7)".to_string()))
+			);
+		}
+	}
+
+	#[test]
+	fn test_wrap() {
+		assert_eq!(wrap("a bb ccc", 4), vec!["a bb", "ccc"]);
+		assert_eq!(wrap("abcdefghij", 4), vec!["abcd", "efgh", "ij"]);
+		assert_eq!(wrap("a  b", 10), vec!["a b"]);
+		assert_eq!(wrap("", 4), vec![""]);
+	}
+
+	#[cfg(feature = "clipboard")]
+	#[test]
+	fn test_decode_clipboard() {
+		struct StubClipboard(Option<GrayImage>);
+		impl ClipboardImage for StubClipboard {
+			fn image(&mut self) -> Result<Option<GrayImage>, String> {
+				Ok(self.0.take())
+			}
+			fn set_image(&mut self, _image: image::RgbaImage) -> Result<(), String> {
+				unreachable!()
+			}
+		}
+
+		let code = encode(b"hello", None, None, false).unwrap();
+		let image = code.render::<Luma<u8>>().build();
+		assert_eq!(
+			decode_clipboard(&mut StubClipboard(Some(image))),
+			Ok("hello".to_string())
+		);
+		assert_eq!(
+			decode_clipboard(&mut StubClipboard(None)),
+			Err(Error::Other("No image on clipboard".to_string()))
+		);
+	}
+
+	#[cfg(feature = "pdf")]
+	#[test]
+	fn test_decode_pages() {
+		let image = |data: &[u8]| {
+			let code = encode(data, None, None, false).unwrap();
+			render_image(&code, &QrOptions::default())
+		};
+		let blank = GrayImage::from_pixel(100, 100, Luma([255]));
+
+		assert_eq!(
+			decode_pages(&[image(b"page 1"), blank.clone(), image(b"page 3")], true),
+			Ok(vec!["page 1".to_string(), "page 3".to_string()])
+		);
+		assert_eq!(
+			decode_pages(&[blank], false),
+			Err(
+				Error::invalid_input("No QR code found in the PDF".to_string())
+					.with_hint("only the first page is decoded without --all-pages")
+			)
+		);
+	}
+
+	/// Needs libpdfium on the library path
+	#[cfg(feature = "pdf")]
+	#[test]
+	fn test_qr2s_pdf() {
+		let path = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/data/qr_invoice.pdf");
+		let matches = commands()[2]
+			.app
+			.clone()
+			.get_matches_from(vec!["qr2s", "--pdf", path]);
+		assert_eq!(
+			qr2s(&matches).unwrap().render(false),
+			Ok(vec!["https://example.com/invoice/INV-2024-0042".to_string()])
+		);
+	}
+
+	#[cfg(feature = "clipboard")]
+	#[test]
+	fn test_copy_clipboard() {
+		struct StubClipboard(Option<image::RgbaImage>);
+		impl ClipboardImage for StubClipboard {
+			fn image(&mut self) -> Result<Option<GrayImage>, String> {
+				unreachable!()
+			}
+			fn set_image(&mut self, image: image::RgbaImage) -> Result<(), String> {
+				self.0 = Some(image);
+				Ok(())
+			}
+		}
+
+		let code = encode(b"hello", None, None, false).unwrap();
+		let options = QrOptions {
+			scale: Some(4),
+			..Default::default()
+		};
+		let image = render_image(&code, &options);
+		let mut clipboard = StubClipboard(None);
+		copy_clipboard(&mut clipboard, &image).unwrap();
+		let copied = clipboard.0.unwrap();
+		// Version 1 is 21 modules, plus a quiet zone of 4 on each side
+		assert_eq!(copied.dimensions(), ((21 + 8) * 4, (21 + 8) * 4));
+		assert_eq!(copied.dimensions(), image.dimensions());
+		assert_eq!(copied.get_pixel(0, 0), &image::Rgba([255, 255, 255, 255]));
+	}
+
+	#[test]
+	fn test_s2qr_eci() {
+		let text = "你好, QR";
+
+		// Decode the data segments back: ECI, then byte mode with an 8 bit count (version 1-9)
+		let bytes = eci_bits(text.as_bytes(), 26).unwrap().into_bytes();
+		let bit = |i: usize| (bytes[i / 8] >> (7 - i % 8)) & 1;
+		let number = |start: usize, len: usize| {
+			(start..start + len).fold(0usize, |acc, i| acc << 1 | bit(i) as usize)
+		};
+		assert_eq!(number(0, 4), 0b0111);
+		assert_eq!(number(4, 8), 26);
+		assert_eq!(number(12, 4), 0b0100);
+		let len = number(16, 8);
+		let data = (0..len).map(|i| number(24 + i * 8, 8) as u8).collect::<Vec<_>>();
+		assert_eq!(String::from_utf8(data), Ok(text.to_string()));
+
+		let app = &commands()[0].app;
+		let matches = app
+			.clone()
+			.get_matches_from(vec!["s2qr", "--eci", "utf-8", text]);
+		assert!(matches!(s2qr(&matches), Ok(Output::Bytes(_))));
+	}
+
+	#[test]
+	fn test_s2qr_info() {
+		let app = commands()[1].app.clone().args(&app::global_args());
+		let payload = "a".repeat(58);
+
+		let matches = app.get_matches_from(vec!["s2qr_info", "--json", payload.as_str()]);
+		let info = match s2qr_info(&matches) {
+			Ok(Output::Structured(info)) => info,
+			output => panic!("Unexpected output: {:?}", output),
+		};
+		assert_eq!(info["payload"], 58);
+		assert_eq!(info["mode"], "byte");
+		assert_eq!(info["version"], 4);
+		assert_eq!(
+			info["used"].as_u64().unwrap() + info["remaining"].as_u64().unwrap(),
+			info["capacity"].as_u64().unwrap()
+		);
+	}
+
+	#[test]
+	fn test_s2qr_ec_auto() {
+		// 24 bytes take version 2 at level M, Q only holds 20 bytes there
+		let code = encode(&[b'a'; 24], None, None, true).unwrap();
+		assert_eq!(code.version(), Version::Normal(2));
+		assert_eq!(code.level(), EcLevel::M);
+		let code = encode(&[b'a'; 24], None, Some(MaskPattern::Checkerboard), true).unwrap();
+		assert_eq!(code.level(), EcLevel::M);
+
+		// 15 bytes spill over version 1 at level M, version 2 holds them at level Q
+		let code = encode(&[b'a'; 15], None, None, true).unwrap();
+		assert_eq!(code.version(), Version::Normal(2));
+		assert_eq!(code.level(), EcLevel::Q);
+		let code = encode(&[b'a'; 15], None, None, false).unwrap();
+		assert_eq!(code.version(), Version::Normal(2));
+		assert_eq!(code.level(), EcLevel::M);
+
+		let app = commands()[1].app.clone().args(&app::global_args());
+		let info = |payload: &str| {
+			let matches =
+				app.clone()
+					.get_matches_from(vec!["s2qr_info", "--json", "--ec-auto", payload]);
+			match s2qr_info(&matches) {
+				Ok(Output::Structured(info)) => info,
+				output => panic!("Unexpected output: {:?}", output),
+			}
+		};
+		let info_24 = info(&"a".repeat(24));
+		assert_eq!(info_24["version"], 2);
+		assert_eq!(info_24["level"], "M");
+		let info_15 = info(&"a".repeat(15));
+		assert_eq!(info_15["version"], 2);
+		assert_eq!(info_15["level"], "Q");
+		// Data codewords of version 2 at level Q
+		assert_eq!(info_15["capacity"], 22);
+	}
+
+	#[test]
+	fn test_s2qr_too_large() {
+		let input = "a".repeat(3000);
+		let matches = commands()[0]
+			.app
+			.clone()
+			.get_matches_from(vec!["s2qr", input.as_str()]);
+		assert_eq!(
+			s2qr(&matches).map_err(String::from),
+			Err(
+				"Input too large for a single QR (3000 bytes, max ~2953); consider --split"
+					.to_string()
+			)
+		);
+	}
+
+	#[test]
+	fn test_s2qr_auto_mode() {
+		let app = commands()[1].app.clone().args(&app::global_args());
+		let info = |input: &str| {
+			let matches = app.clone().get_matches_from(vec!["s2qr_info", "--json", input]);
+			match s2qr_info(&matches) {
+				Ok(Output::Structured(info)) => info,
+				output => panic!("Unexpected output: {:?}", output),
+			}
+		};
+
+		let digits = info(&"1234567890".repeat(10));
+		let mixed = info(&"a1b2c3d4e5".repeat(10));
+		assert_eq!(digits["mode"], "numeric");
+		assert_eq!(mixed["mode"], "byte");
+		assert!(digits["version"].as_u64() < mixed["version"].as_u64());
+		assert_eq!(info("HTTPS://EXAMPLE.COM/A")["mode"], "alphanumeric");
+
+		// Numeric mode fits more digits than bytes in a single QR
+		let matches = commands()[0]
+			.app
+			.clone()
+			.args(&app::global_args())
+			.get_matches_from(vec!["s2qr", "--dry-run", &"7".repeat(5000)]);
+		assert_eq!(
+			s2qr(&matches),
+			Ok(Output::Lines(vec![
+				"OK: would produce a 169x169 modules QR code PNG".to_string()
+			]))
+		);
+	}
 }
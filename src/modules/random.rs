@@ -0,0 +1,988 @@
+use crate::log;
+use crate::modules::{Command, Error, Module, Output};
+use base64::{engine::general_purpose, Engine as _};
+use clap::{Arg, ArgMatches, SubCommand};
+use rand::rngs::{OsRng, StdRng};
+use rand::{Rng, RngCore, SeedableRng};
+use std::collections::HashSet;
+use std::fs;
+
+pub fn module<'a, 'b>() -> Module<'a, 'b> {
+	Module {
+		desc: "Random bytes and secrets".to_string(),
+		commands: commands(),
+		get_cases: cases::cases,
+	}
+}
+
+/// Bounds of --length, a MiB is more than any key or token needs
+const MAX_LENGTH: usize = 1024 * 1024;
+
+/// --preset: name, length and format
+const PRESETS: [(&str, usize, &str); 2] = [("api-key", 32, "base64url"), ("hex64", 32, "hex")];
+
+/// Bounds of the pwgen --length
+const MAX_PASSWORD_LENGTH: usize = 1024;
+
+/// Character classes of pwgen
+const CLASSES: [(&str, &str); 4] = [
+	("upper", "ABCDEFGHIJKLMNOPQRSTUVWXYZ"),
+	("lower", "abcdefghijklmnopqrstuvwxyz"),
+	("digit", "0123456789"),
+	("symbol", "!\"#$%&'()*+,-./:;<=>?@[\\]^_`{|}~"),
+];
+const CLASS_NAMES: [&str; 4] = ["upper", "lower", "digit", "symbol"];
+
+/// Left out by pwgen --exclude-ambiguous
+const AMBIGUOUS: &str = "Il1|O0o";
+
+/// Passwords drawn before pwgen gives up on the required classes
+const MAX_ATTEMPTS: usize = 10_000;
+
+pub fn commands<'a, 'b>() -> Vec<Command<'a, 'b>> {
+	vec![
+		Command {
+			app: SubCommand::with_name("rand_bytes")
+			.about("Generate random bytes, e.g. a key or an API token")
+			.arg(
+				Arg::with_name("LENGTH")
+					.long("length")
+					.short("l")
+					.help("Number of bytes, 1 to 1048576 [default: 32]")
+					.takes_value(true),
+			)
+			.arg(
+				Arg::with_name("FORMAT")
+					.long("format")
+					.help("Output format, raw bytes go to stdout as is [default: hex]")
+					.takes_value(true)
+					.possible_values(&["hex", "base64", "base64url", "raw"]),
+			)
+			.arg(
+				Arg::with_name("PRESET")
+					.long("preset")
+					.help("api-key: 32 bytes as base64url\nhex64: 32 bytes as hex")
+					.takes_value(true)
+					.possible_values(&["api-key", "hex64"])
+					.conflicts_with_all(&["LENGTH", "FORMAT"]),
+			)
+			.arg(count_arg())
+			.arg(seed_arg()),
+			f: rand_bytes,
+			serial: false,
+			pure: true,
+		},
+		Command {
+			app: SubCommand::with_name("pwgen")
+				.about("Generate passwords satisfying a composition policy")
+				.arg(
+					Arg::with_name("LENGTH")
+						.long("length")
+						.short("l")
+						.help("Number of characters, 1 to 1024")
+						.takes_value(true)
+						.default_value("16"),
+				)
+				.arg(
+					Arg::with_name("CLASSES")
+						.long("classes")
+						.help("Character classes to draw from: upper, lower, digit, symbol")
+						.takes_value(true)
+						.use_delimiter(true)
+						.possible_values(&CLASS_NAMES)
+						.default_value("upper,lower,digit,symbol"),
+				)
+				.arg(
+					Arg::with_name("REQUIRE")
+						.long("require")
+						.short("r")
+						.help("Character classes every password has at least one character of, drawn from too")
+						.takes_value(true)
+						.use_delimiter(true)
+						.possible_values(&CLASS_NAMES),
+				)
+				.arg(
+					Arg::with_name("EXCLUDE")
+						.long("exclude")
+						.help("Characters never to use")
+						.takes_value(true),
+				)
+				.arg(
+					Arg::with_name("EXCLUDE_AMBIGUOUS")
+						.long("exclude-ambiguous")
+						.help("Leave out the characters easily mistaken for others: Il1|O0o"),
+				)
+				.arg(
+					Arg::with_name("INFO")
+						.long("info")
+						.help("Print the entropy of the passwords in bits after them"),
+				)
+				.arg(count_arg())
+				.arg(seed_arg()),
+			f: pwgen,
+			serial: false,
+			pure: true,
+		},
+		Command {
+			app: SubCommand::with_name("passphrase")
+				.about("Generate diceware passphrases from a word list")
+				.arg(
+					Arg::with_name("WORDS")
+						.long("words")
+						.short("w")
+						.help("Number of words")
+						.takes_value(true)
+						.default_value("6"),
+				)
+				.arg(
+					Arg::with_name("SEPARATOR")
+						.long("word-separator")
+						.help("Between the words")
+						.takes_value(true)
+						.default_value("-"),
+				)
+				.arg(
+					Arg::with_name("CAPITALIZE")
+						.long("capitalize")
+						.help("Capitalize the first letter of each word"),
+				)
+				.arg(
+					Arg::with_name("WORDLIST")
+						.long("wordlist")
+						.help("Word list file, a word per line, optionally after its dice number like the EFF lists; blank lines, # comments and duplicates are skipped\nThe EFF lists are not embedded, download e.g. https://www.eff.org/files/2016/07/18/eff_large_wordlist.txt")
+						.takes_value(true)
+						.required(true),
+				)
+				.arg(
+					Arg::with_name("INFO")
+						.long("info")
+						.help("Print the entropy of the passphrases in bits after them"),
+				)
+				.arg(count_arg())
+				.arg(seed_arg()),
+			f: passphrase,
+			serial: false,
+			pure: true,
+		},
+	]
+}
+
+fn count_arg<'a, 'b>() -> Arg<'a, 'b> {
+	Arg::with_name("COUNT")
+		.long("count")
+		.short("c")
+		.help("Number of values, one per line")
+		.takes_value(true)
+		.default_value("1")
+}
+
+fn seed_arg<'a, 'b>() -> Arg<'a, 'b> {
+	Arg::with_name("SEED")
+		.long("seed")
+		.help("INSECURE, for tests only: seed of a deterministic generator instead of the OS one")
+		.takes_value(true)
+}
+
+fn rand_bytes(matches: &ArgMatches) -> Result<Output, Error> {
+	let (length, format) = match matches.value_of("PRESET") {
+		Some(preset) => PRESETS
+			.iter()
+			.find(|(name, _, _)| *name == preset)
+			.map(|(_, length, format)| (*length, *format))
+			.ok_or_else(|| Error::Usage(format!("Invalid preset: {}", preset)))?,
+		// No default_value, it would conflict with --preset
+		None => {
+			let length = matches.value_of("LENGTH").unwrap_or("32");
+			let length = length
+				.parse::<usize>()
+				.ok()
+				.filter(|x| (1..=MAX_LENGTH).contains(x))
+				.ok_or_else(|| {
+					Error::Usage(format!(
+						"Invalid length: {}, expected 1 to {}",
+						length, MAX_LENGTH
+					))
+				})?;
+			(length, matches.value_of("FORMAT").unwrap_or("hex"))
+		}
+	};
+	let count = count(matches)?;
+	if format == "raw" && count > 1 {
+		return Err(Error::Usage(
+			"--count can not be used with --format raw".to_string(),
+		));
+	}
+
+	let mut rng = rng(matches)?;
+	log::debug(&format!(
+		"rand_bytes: length={}, format={}, count={}",
+		length, format, count
+	));
+
+	let mut values = vec![];
+	for _ in 0..count {
+		let mut bytes = vec![0u8; length];
+		rng.try_fill_bytes(&mut bytes)
+			.map_err(|e| format!("Failed to generate random bytes: {}", e))?;
+		values.push(bytes);
+	}
+
+	let result = match format {
+		"raw" => return Ok(Output::Bytes(values.remove(0))),
+		"base64" => values
+			.iter()
+			.map(|x| general_purpose::STANDARD.encode(x))
+			.collect(),
+		"base64url" => values
+			.iter()
+			.map(|x| general_purpose::URL_SAFE_NO_PAD.encode(x))
+			.collect(),
+		_ => values.iter().map(hex::encode).collect(),
+	};
+
+	Ok(Output::Lines(result))
+}
+
+fn pwgen(matches: &ArgMatches) -> Result<Output, Error> {
+	let length = matches.value_of("LENGTH").unwrap_or_default();
+	let length = length
+		.parse::<usize>()
+		.ok()
+		.filter(|x| (1..=MAX_PASSWORD_LENGTH).contains(x))
+		.ok_or_else(|| {
+			Error::Usage(format!(
+				"Invalid length: {}, expected 1 to {}",
+				length, MAX_PASSWORD_LENGTH
+			))
+		})?;
+	let count = count(matches)?;
+
+	let mut excluded = matches.value_of("EXCLUDE").unwrap_or_default().to_string();
+	if matches.is_present("EXCLUDE_AMBIGUOUS") {
+		excluded.push_str(AMBIGUOUS);
+	}
+	let mut required = matches
+		.values_of("REQUIRE")
+		.into_iter()
+		.flatten()
+		.collect::<Vec<_>>();
+	required.sort_unstable();
+	required.dedup();
+	let policy = Policy::new(
+		matches.values_of("CLASSES").into_iter().flatten(),
+		&required,
+		&excluded,
+	)?;
+	if required.len() > length {
+		return Err(Error::Usage(format!(
+			"A password of {} characters can not hold the {} required classes",
+			length,
+			required.len()
+		)));
+	}
+
+	let mut rng = rng(matches)?;
+	log::debug(&format!(
+		"pwgen: length={}, alphabet={}, required={}, count={}",
+		length,
+		policy.alphabet.len(),
+		required.join(","),
+		count
+	));
+
+	let mut result = (0..count)
+		.map(|_| policy.generate(length, &mut rng))
+		.collect::<Result<Vec<_>, _>>()?;
+	if matches.is_present("INFO") {
+		result.push(format!(
+			"Entropy: {:.1} bits ({} characters of {})",
+			policy.entropy(length),
+			length,
+			policy.alphabet.len()
+		));
+	}
+
+	Ok(Output::Lines(result))
+}
+
+fn passphrase(matches: &ArgMatches) -> Result<Output, Error> {
+	let words = matches
+		.value_of("WORDS")
+		.unwrap_or_default()
+		.parse::<usize>()
+		.ok()
+		.filter(|x| *x > 0)
+		.ok_or_else(|| Error::Usage("Invalid number of words".to_string()))?;
+	let count = count(matches)?;
+	let separator = matches.value_of("SEPARATOR").unwrap_or_default();
+
+	let path = matches.value_of("WORDLIST").unwrap_or_default();
+	if let "eff-long" | "eff-short" = path {
+		return Err(
+			Error::Unsupported(format!("The {} list is not embedded", path))
+				.with_hint("download the EFF list and pass its path to --wordlist"),
+		);
+	}
+	let list = fs::read_to_string(path)
+		.map_err(|e| Error::io(&format!("Failed to read file {}", path), e))?;
+	let list = parse_wordlist(&list);
+	if list.len() < 2 {
+		return Err(Error::invalid_input(format!(
+			"The word list {} has {} distinct words, it needs at least 2",
+			path,
+			list.len()
+		)));
+	}
+
+	let mut rng = rng(matches)?;
+	log::debug(&format!(
+		"passphrase: words={}, list={}, count={}",
+		words,
+		list.len(),
+		count
+	));
+
+	let mut result = (0..count)
+		.map(|_| {
+			(0..words)
+				// gen_range is unbiased, unlike a random number modulo the length
+				.map(|_| list[rng.gen_range(0..list.len())])
+				.map(|word| match matches.is_present("CAPITALIZE") {
+					true => capitalize(word),
+					false => word.to_string(),
+				})
+				.collect::<Vec<_>>()
+				.join(separator)
+		})
+		.collect::<Vec<_>>();
+	if matches.is_present("INFO") {
+		result.push(format!(
+			"Entropy: {:.1} bits ({} words of {})",
+			words as f64 * (list.len() as f64).log2(),
+			words,
+			list.len()
+		));
+	}
+
+	Ok(Output::Lines(result))
+}
+
+/// The distinct words of a word list in their order: a word per line, after its dice
+/// number in the EFF lists, blank lines and # comments skipped; a duplicate would be
+/// drawn more often and overstate the entropy
+fn parse_wordlist(list: &str) -> Vec<&str> {
+	let mut seen = HashSet::new();
+	list.lines()
+		.map(str::trim)
+		.filter(|line| !line.is_empty() && !line.starts_with('#'))
+		.map(|line| match line.split_once(char::is_whitespace) {
+			Some((dice, word)) if dice.chars().all(|c| c.is_ascii_digit()) => word.trim(),
+			_ => line,
+		})
+		.filter(|word| seen.insert(*word))
+		.collect()
+}
+
+fn capitalize(word: &str) -> String {
+	let mut chars = word.chars();
+	match chars.next() {
+		Some(first) => first.to_uppercase().chain(chars).collect(),
+		None => String::new(),
+	}
+}
+
+/// The characters of pwgen and the classes a password needs a character of
+struct Policy {
+	alphabet: Vec<char>,
+	required: Vec<Vec<char>>,
+}
+
+impl Policy {
+	fn new<'c>(
+		classes: impl Iterator<Item = &'c str>,
+		required: &[&'c str],
+		excluded: &str,
+	) -> Result<Self, Error> {
+		let chars = |name: &str| -> Vec<char> {
+			let chars = CLASSES
+				.iter()
+				.find(|(x, _)| *x == name)
+				.map(|(_, chars)| *chars)
+				.unwrap_or_default();
+			chars.chars().filter(|c| !excluded.contains(*c)).collect()
+		};
+
+		let classes = classes.chain(required.iter().copied()).collect::<Vec<_>>();
+		// In the order of CLASSES, each once
+		let alphabet = CLASS_NAMES
+			.iter()
+			.filter(|x| classes.contains(*x))
+			.flat_map(|x| chars(x))
+			.collect::<Vec<_>>();
+		if alphabet.is_empty() {
+			return Err(Error::Usage("No characters left to draw from".to_string()));
+		}
+
+		let required = required
+			.iter()
+			.map(|name| match chars(name) {
+				chars if chars.is_empty() => Err(Error::Usage(format!(
+					"No characters left in the required class {}",
+					name
+				))),
+				chars => Ok(chars),
+			})
+			.collect::<Result<Vec<_>, _>>()?;
+
+		Ok(Self { alphabet, required })
+	}
+
+	/// Uniform among the passwords of the policy: every character is drawn uniformly from
+	/// the whole alphabet and a password missing a required class is drawn again, so no
+	/// position is more likely than another to hold the character of a class
+	fn generate<R: Rng>(&self, length: usize, rng: &mut R) -> Result<String, Error> {
+		for _ in 0..MAX_ATTEMPTS {
+			let password = (0..length)
+				.map(|_| self.alphabet[rng.gen_range(0..self.alphabet.len())])
+				.collect::<Vec<_>>();
+			let satisfied = self
+				.required
+				.iter()
+				.all(|class| password.iter().any(|c| class.contains(c)));
+			if satisfied {
+				return Ok(password.into_iter().collect());
+			}
+		}
+		Err(Error::Usage(
+			"The required classes are too unlikely at this length, use a longer one".to_string(),
+		))
+	}
+
+	/// log2 of the number of passwords of the policy: by inclusion-exclusion, the
+	/// passwords of the alphabet less those missing a required class, computed as a
+	/// fraction of all the passwords so that long ones do not overflow
+	fn entropy(&self, length: usize) -> f64 {
+		let n = self.alphabet.len() as f64;
+		let mut fraction = 0.0;
+		for subset in 0..1usize << self.required.len() {
+			let missing = (0..self.required.len())
+				.filter(|i| subset & (1 << i) != 0)
+				.map(|i| self.required[i].len())
+				.sum::<usize>();
+			let sign = match subset.count_ones() % 2 {
+				0 => 1.0,
+				_ => -1.0,
+			};
+			fraction += sign * ((n - missing as f64) / n).powi(length as i32);
+		}
+		length as f64 * n.log2() + fraction.log2()
+	}
+}
+
+/// --count, at least one
+fn count(matches: &ArgMatches) -> Result<usize, Error> {
+	matches
+		.value_of("COUNT")
+		.unwrap_or_default()
+		.parse::<usize>()
+		.ok()
+		.filter(|x| *x > 0)
+		.ok_or_else(|| Error::Usage("Invalid count".to_string()))
+}
+
+/// The OS generator, or with --seed a deterministic one for the tests
+fn rng(matches: &ArgMatches) -> Result<Box<dyn RngCore>, Error> {
+	match matches.value_of("SEED") {
+		Some(seed) => {
+			let seed = seed
+				.parse::<u64>()
+				.map_err(|_| Error::Usage(format!("Invalid seed: {}", seed)))?;
+			log::info("--seed is not random, never use its output as a secret");
+			Ok(Box::new(StdRng::seed_from_u64(seed)))
+		}
+		None => Ok(Box::new(OsRng)),
+	}
+}
+
+mod cases {
+	use crate::modules::Case;
+	use linked_hash_map::LinkedHashMap;
+
+	pub fn cases() -> LinkedHashMap<&'static str, Vec<Case>> {
+		vec![
+			(
+				"rand_bytes",
+				vec![
+					Case {
+						desc: "32-byte key as hex".to_string(),
+						input: vec![].into_iter().map(|s: &str| s.to_string()).collect(),
+						output: vec![
+							"9f4c0e5a3b1d7f2e8a6c4b0d2f1e3a5c7b9d0f2e4a6c8b1d3f5e7a9c0b2d4f6e",
+						]
+						.into_iter()
+						.map(Into::into)
+						.collect(),
+						is_example: true,
+						is_test: false, // Random
+						since: "0.17.0".to_string(),
+					},
+					Case {
+						desc: "API key, 32 bytes as base64url".to_string(),
+						input: vec!["--preset", "api-key"]
+							.into_iter()
+							.map(Into::into)
+							.collect(),
+						output: vec!["n0wOWjsdfy6KbEsNLx46XHudDy5KbIsdP156nAstT24"]
+							.into_iter()
+							.map(Into::into)
+							.collect(),
+						is_example: true,
+						is_test: false, // Random
+						since: "0.17.0".to_string(),
+					},
+					Case {
+						desc: "Three 16-byte values as base64".to_string(),
+						input: vec!["-l", "16", "--format", "base64", "-c", "3"]
+							.into_iter()
+							.map(Into::into)
+							.collect(),
+						output: vec![
+							"xR3nJ0vQk2Lw8pZb6tYcAg==",
+							"3mWq9s1FhKdP0aZt7yUeBQ==",
+							"Vb8kT2cN5rXj1oLm4gHsIw==",
+						]
+						.into_iter()
+						.map(Into::into)
+						.collect(),
+						is_example: true,
+						is_test: false, // Random
+						since: "0.17.0".to_string(),
+					},
+				],
+			),
+			(
+				"pwgen",
+				vec![
+					Case {
+						desc: "Password for a common corporate policy".to_string(),
+						input: vec![
+							"--length",
+							"20",
+							"--require",
+							"upper,lower,digit,symbol",
+							"--exclude-ambiguous",
+						]
+						.into_iter()
+						.map(Into::into)
+						.collect(),
+						output: vec!["q7#Vd@Kz2^mWr9!tBx4&"]
+							.into_iter()
+							.map(Into::into)
+							.collect(),
+						is_example: true,
+						is_test: false, // Random
+						since: "0.17.0".to_string(),
+					},
+					Case {
+						desc: "Three PINs with their entropy".to_string(),
+						input: vec!["-l", "6", "--classes", "digit", "-c", "3", "--info"]
+							.into_iter()
+							.map(Into::into)
+							.collect(),
+						output: vec![
+							"480913",
+							"027465",
+							"915382",
+							"Entropy: 19.9 bits (6 characters of 10)",
+						]
+						.into_iter()
+						.map(Into::into)
+						.collect(),
+						is_example: true,
+						is_test: false, // Random
+						since: "0.17.0".to_string(),
+					},
+				],
+			),
+			(
+				"passphrase",
+				vec![
+					Case {
+						desc: "Six words of a word list file, e.g. the EFF long list".to_string(),
+						input: vec!["--wordlist", "tests/data/words.txt"]
+							.into_iter()
+							.map(Into::into)
+							.collect(),
+						output: vec!["delta-charlie-golf-charlie-juliett-foxtrot"]
+							.into_iter()
+							.map(Into::into)
+							.collect(),
+						is_example: true,
+						is_test: false, // Random
+						since: "0.17.0".to_string(),
+					},
+					Case {
+						desc: "Four capitalized words with their entropy".to_string(),
+						input: vec![
+							"--wordlist",
+							"tests/data/words.txt",
+							"-w",
+							"4",
+							"--capitalize",
+							"--word-separator",
+							"' '",
+							"--info",
+						]
+						.into_iter()
+						.map(Into::into)
+						.collect(),
+						output: vec![
+							"Bravo Delta Golf Foxtrot",
+							"Entropy: 16.0 bits (4 words of 16)",
+						]
+						.into_iter()
+						.map(Into::into)
+						.collect(),
+						is_example: true,
+						is_test: false, // Random
+						since: "0.17.0".to_string(),
+					},
+					Case {
+						desc: "".to_string(),
+						input: vec![
+							"--wordlist",
+							"tests/data/words.txt",
+							"--seed",
+							"7",
+							"-w",
+							"4",
+							"--info",
+						]
+						.into_iter()
+						.map(Into::into)
+						.collect(),
+						output: vec![
+							"alpha-charlie-echo-papa",
+							"Entropy: 16.0 bits (4 words of 16)",
+						]
+						.into_iter()
+						.map(Into::into)
+						.collect(),
+						is_example: false,
+						is_test: true,
+						since: "0.17.0".to_string(),
+					},
+				],
+			),
+		]
+		.into_iter()
+		.collect()
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::app;
+	use crate::modules::base::test::test_module;
+
+	#[test]
+	fn test_cases() {
+		test_module(module());
+	}
+
+	fn run(args: Vec<&str>) -> Result<Output, Error> {
+		let app = commands()[0].app.clone().args(&app::global_args());
+		rand_bytes(&app.get_matches_from(Some("rand_bytes").into_iter().chain(args)))
+	}
+
+	fn lines(args: Vec<&str>) -> Vec<String> {
+		run(args).unwrap().render(false).unwrap()
+	}
+
+	#[test]
+	fn test_rand_bytes() {
+		let values = lines(vec!["-l", "16", "-c", "3"]);
+		assert_eq!(values.len(), 3);
+		assert!(values
+			.iter()
+			.all(|x| x.len() == 32 && hex::decode(x).is_ok()));
+		assert_ne!(values[0], values[1]);
+
+		let api_key = lines(vec!["--preset", "api-key"]);
+		assert_eq!(api_key[0].len(), 43);
+		assert_eq!(
+			general_purpose::URL_SAFE_NO_PAD
+				.decode(&api_key[0])
+				.unwrap()
+				.len(),
+			32
+		);
+		assert_eq!(lines(vec!["--preset", "hex64"])[0].len(), 64);
+		assert_eq!(lines(vec!["-l", "1", "--format", "base64"])[0].len(), 4);
+
+		match run(vec!["-l", "5", "--format", "raw"]) {
+			Ok(Output::Bytes(bytes)) => assert_eq!(bytes.len(), 5),
+			output => panic!("Unexpected output: {:?}", output),
+		}
+	}
+
+	#[test]
+	fn test_seed() {
+		let seeded = |seed: &str| lines(vec!["--seed", seed, "-l", "8", "-c", "2"]);
+		assert_eq!(seeded("42"), seeded("42"));
+		assert_ne!(seeded("42"), seeded("43"));
+		let values = seeded("42");
+		assert_ne!(values[0], values[1]);
+	}
+
+	#[test]
+	fn test_invalid_args() {
+		let error = |x: &str| -> Result<Output, Error> {
+			Err(Error::Usage(format!(
+				"Invalid length: {}, expected 1 to 1048576",
+				x
+			)))
+		};
+		assert_eq!(run(vec!["-l", "0"]), error("0"));
+		assert_eq!(run(vec!["-l", "1048577"]), error("1048577"));
+		assert_eq!(run(vec!["-l", "abc"]), error("abc"));
+		assert_eq!(
+			run(vec!["-c", "0"]),
+			Err(Error::Usage("Invalid count".to_string()))
+		);
+		assert_eq!(
+			run(vec!["--format", "raw", "-c", "2"]),
+			Err(Error::Usage(
+				"--count can not be used with --format raw".to_string()
+			))
+		);
+		assert_eq!(
+			run(vec!["--seed", "abc"]),
+			Err(Error::Usage("Invalid seed: abc".to_string()))
+		);
+
+		// Unknown formats and presets are refused by the args
+		let app = commands()[0].app.clone();
+		assert!(app
+			.clone()
+			.get_matches_from_safe(vec!["rand_bytes", "--format", "base32"])
+			.is_err());
+		assert!(app
+			.clone()
+			.get_matches_from_safe(vec!["rand_bytes", "--preset", "uuid"])
+			.is_err());
+		assert!(app
+			.get_matches_from_safe(vec!["rand_bytes", "--preset", "hex64", "-l", "8"])
+			.is_err());
+	}
+
+	fn pwgen_lines(args: Vec<&str>) -> Result<Vec<String>, Error> {
+		let app = commands()[1].app.clone().args(&app::global_args());
+		let matches = app.get_matches_from(Some("pwgen").into_iter().chain(args));
+		pwgen(&matches).and_then(|x| x.render(false).map_err(Error::from))
+	}
+
+	#[test]
+	fn test_pwgen_classes() {
+		let passwords = pwgen_lines(vec![
+			"--seed",
+			"1",
+			"-l",
+			"4",
+			"--require",
+			"upper,lower,digit,symbol",
+			"--exclude-ambiguous",
+			"-c",
+			"500",
+		])
+		.unwrap();
+		assert_eq!(passwords.len(), 500);
+		for password in &passwords {
+			assert_eq!(password.chars().count(), 4);
+			for (_, chars) in CLASSES.iter() {
+				assert!(password.chars().any(|c| chars.contains(c)), "{}", password);
+			}
+			assert!(!password.chars().any(|c| AMBIGUOUS.contains(c)));
+		}
+
+		// No position is more likely than another to hold the required digit
+		let passwords = pwgen_lines(vec![
+			"--seed",
+			"2",
+			"-l",
+			"8",
+			"--classes",
+			"upper,lower",
+			"--require",
+			"digit",
+			"-c",
+			"4000",
+		])
+		.unwrap();
+		let digits = (0..8)
+			.map(|i| {
+				passwords
+					.iter()
+					.filter(|x| x.as_bytes()[i].is_ascii_digit())
+					.count()
+			})
+			.collect::<Vec<_>>();
+		let mean = digits.iter().sum::<usize>() / 8;
+		assert!(
+			digits.iter().all(|x| x * 4 > mean * 3 && x * 4 < mean * 5),
+			"{:?}",
+			digits
+		);
+
+		let passwords = pwgen_lines(vec!["-l", "32", "--exclude", "abc", "-c", "20"]).unwrap();
+		assert!(passwords.iter().all(|x| !x.contains(|c| "abc".contains(c))));
+	}
+
+	#[test]
+	fn test_pwgen_entropy() {
+		let entropy = |classes: Vec<&str>, required: Vec<&str>, excluded: &str, length: usize| {
+			let policy = Policy::new(classes.into_iter(), &required, excluded).unwrap();
+			policy.entropy(length)
+		};
+		// 10^2
+		assert!((entropy(vec!["digit"], vec![], "", 2) - 100f64.log2()).abs() < 1e-9);
+		// 36^2 - 26^2 - 10^2
+		assert!((entropy(vec![], vec!["upper", "digit"], "", 2) - 520f64.log2()).abs() < 1e-9);
+		// 94 printable chars, 16 of them
+		assert!((entropy(CLASS_NAMES.to_vec(), vec![], "", 16) - 16.0 * 94f64.log2()).abs() < 1e-9);
+		// 36^3 - 26^3, upper with a required digit
+		assert!((entropy(vec!["upper"], vec!["digit"], "", 3) - 29080f64.log2()).abs() < 1e-9);
+		// 32^3 - 24^3, without I, O, 0 and 1
+		assert!(
+			(entropy(vec!["upper"], vec!["digit"], "Il1|O0o", 3) - 18944f64.log2()).abs() < 1e-9
+		);
+
+		assert_eq!(
+			pwgen_lines(vec![
+				"-l",
+				"6",
+				"--classes",
+				"digit",
+				"--info",
+				"--seed",
+				"1"
+			])
+			.unwrap()[1],
+			"Entropy: 19.9 bits (6 characters of 10)"
+		);
+	}
+
+	#[test]
+	fn test_pwgen_policy_errors() {
+		assert_eq!(
+			pwgen_lines(vec!["-l", "3", "--require", "upper,lower,digit,symbol"]),
+			Err(Error::Usage(
+				"A password of 3 characters can not hold the 4 required classes".to_string()
+			))
+		);
+		assert_eq!(
+			pwgen_lines(vec!["--require", "digit", "--exclude", "0123456789"]),
+			Err(Error::Usage(
+				"No characters left in the required class digit".to_string()
+			))
+		);
+		assert_eq!(
+			pwgen_lines(vec!["--classes", "digit", "--exclude", "0123456789"]),
+			Err(Error::Usage("No characters left to draw from".to_string()))
+		);
+		assert_eq!(
+			pwgen_lines(vec!["-l", "0"]),
+			Err(Error::Usage(
+				"Invalid length: 0, expected 1 to 1024".to_string()
+			))
+		);
+	}
+
+	#[test]
+	fn test_passphrase() {
+		let path = std::env::temp_dir().join(format!("dtool_random_words_{}", std::process::id()));
+		fs::write(
+			&path,
+			"# fixture\n11111\talpha\nbravo\n\n  charlie  \nalpha\n11114 delta\n",
+		)
+		.unwrap();
+		let path = path.to_string_lossy().to_string();
+		let run = |args: Vec<&str>| {
+			let app = commands()[2].app.clone().args(&app::global_args());
+			let matches = app.get_matches_from(
+				vec!["passphrase", "--wordlist", path.as_str()]
+					.into_iter()
+					.chain(args),
+			);
+			passphrase(&matches).and_then(|x| x.render(false).map_err(Error::from))
+		};
+
+		let seeded = run(vec!["--seed", "7", "-c", "50"]).unwrap();
+		assert_eq!(seeded, run(vec!["--seed", "7", "-c", "50"]).unwrap());
+		assert_ne!(seeded, run(vec!["--seed", "8", "-c", "50"]).unwrap());
+		let words = ["alpha", "bravo", "charlie", "delta"];
+		for passphrase in &seeded {
+			let parts = passphrase.split('-').collect::<Vec<_>>();
+			assert_eq!(parts.len(), 6);
+			assert!(parts.iter().all(|x| words.contains(x)), "{}", passphrase);
+		}
+		// Every word of the list comes up
+		assert!(words
+			.iter()
+			.all(|word| seeded.iter().any(|x| x.contains(word))));
+
+		let capitalized = run(vec![
+			"--seed",
+			"7",
+			"-w",
+			"3",
+			"--capitalize",
+			"--word-separator",
+			" ",
+			"--info",
+		])
+		.unwrap();
+		assert_eq!(capitalized.len(), 2);
+		let parts = capitalized[0].split(' ').collect::<Vec<_>>();
+		assert_eq!(parts.len(), 3);
+		assert!(parts.iter().all(
+			|x| x.starts_with(char::is_uppercase) && words.contains(&x.to_lowercase().as_str())
+		));
+		// 4 distinct words, 2 bits each
+		assert_eq!(capitalized[1], "Entropy: 6.0 bits (3 words of 4)");
+
+		assert_eq!(
+			run(vec!["-w", "0"]),
+			Err(Error::Usage("Invalid number of words".to_string()))
+		);
+		let app = commands()[2].app.clone();
+		let matches = app.get_matches_from(vec!["passphrase", "--wordlist", "eff-short"]);
+		assert_eq!(
+			passphrase(&matches),
+			Err(
+				Error::Unsupported("The eff-short list is not embedded".to_string())
+					.with_hint("download the EFF list and pass its path to --wordlist")
+			)
+		);
+		fs::write(&path, "# nothing but\nalpha\nalpha\n").unwrap();
+		assert_eq!(
+			run(vec![]),
+			Err(Error::invalid_input(format!(
+				"The word list {} has 1 distinct words, it needs at least 2",
+				path
+			)))
+		);
+		fs::remove_file(&path).unwrap();
+	}
+
+	#[test]
+	fn test_parse_wordlist() {
+		assert_eq!(
+			parse_wordlist("# comment\n11111\tabacus\n11112 abdomen\n\nabacus\n  zebra \n"),
+			vec!["abacus", "abdomen", "zebra"]
+		);
+		assert_eq!(capitalize("élan"), "Élan");
+		assert_eq!(capitalize(""), "");
+	}
+}
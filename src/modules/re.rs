@@ -1,4 +1,4 @@
-use crate::modules::{base, Command, Module};
+use crate::modules::{base, Command, Error, Module, Output};
 use clap::{Arg, ArgMatches, SubCommand};
 use regex::Regex;
 
@@ -22,12 +22,15 @@ pub fn commands<'a, 'b>() -> Vec<Command<'a, 'b>> {
 					.takes_value(true)
 					.required(true),
 			)
-			.arg(Arg::with_name("INPUT").required(false).index(1)),
+			.arg(Arg::with_name("INPUT").required(false).index(1))
+			.arg(base::file_arg()),
 		f: re,
+		serial: false,
+		pure: true,
 	}]
 }
 
-fn re(matches: &ArgMatches) -> Result<Vec<String>, String> {
+fn re(matches: &ArgMatches) -> Result<Output, Error> {
 	let input = base::input_string(matches)?;
 
 	let pattern = matches.value_of("PATTERN").ok_or("Invalid pattern")?;
@@ -46,7 +49,7 @@ fn re(matches: &ArgMatches) -> Result<Vec<String>, String> {
 		}
 	}
 
-	Ok(result)
+	Ok(Output::Lines(result))
 }
 
 mod cases {
@@ -0,0 +1,154 @@
+use crate::app;
+use crate::log;
+use crate::modules::{base, Format, ModuleManager};
+use clap::{App, ArgMatches, ErrorKind, SubCommand};
+use rustyline::completion::Completer;
+use rustyline::error::ReadlineError;
+use rustyline::highlight::Highlighter;
+use rustyline::hint::Hinter;
+use rustyline::history::DefaultHistory;
+use rustyline::validate::Validator;
+use rustyline::{Context, Editor, Helper};
+use std::io::{self, Write};
+
+pub fn app<'a, 'b>() -> App<'a, 'b> {
+	SubCommand::with_name("repl").about("Interactive mode, run sub commands until exit or Ctrl-D")
+}
+
+pub fn run(_matches: &ArgMatches) -> Result<Vec<String>, String> {
+	let (app, module_manager) = app::build_app();
+
+	let helper = ReplHelper {
+		names: module_manager
+			.apps()
+			.iter()
+			.map(|x| x.get_name().to_string())
+			.chain(vec!["exit".to_string()])
+			.collect(),
+	};
+	let mut editor = Editor::<ReplHelper, DefaultHistory>::new().map_err(|e| e.to_string())?;
+	editor.set_helper(Some(helper));
+
+	loop {
+		let line = match editor.readline("dtool> ") {
+			Ok(line) => line,
+			Err(ReadlineError::Interrupted) => continue,
+			Err(ReadlineError::Eof) => break,
+			Err(e) => return Err(e.to_string()),
+		};
+		if line.trim().is_empty() {
+			continue;
+		}
+		let _ = editor.add_history_entry(line.as_str());
+
+		match execute_line(&app, &module_manager, &line, &mut io::stdout()) {
+			Ok(true) => (),
+			Ok(false) => break,
+			Err(e) => log::error(&e),
+		}
+	}
+
+	Ok(vec![])
+}
+
+/// Run the sub command of a line, returns false when the session should end
+fn execute_line<'a, 'b, W: Write>(
+	app: &App<'a, 'b>,
+	module_manager: &ModuleManager<'a, 'b>,
+	line: &str,
+	out: &mut W,
+) -> Result<bool, String> {
+	let args = base::split_args(line)?;
+
+	match args.first().map(String::as_str) {
+		None => return Ok(true),
+		Some("exit") | Some("quit") => return Ok(false),
+		Some("repl") => return Err("Already in repl".to_string()),
+		_ => (),
+	}
+
+	let matches = match app
+		.clone()
+		.get_matches_from_safe(Some("dtool".to_string()).into_iter().chain(args))
+	{
+		Ok(matches) => matches,
+		Err(e) if e.kind == ErrorKind::HelpDisplayed || e.kind == ErrorKind::VersionDisplayed => {
+			writeln!(out, "{}", e.message).map_err(|e| e.to_string())?;
+			return Ok(true);
+		}
+		Err(e) => return Err(e.message),
+	};
+	let (name, matches) = matches.subcommand();
+	let matches = matches.ok_or("Invalid sub command")?;
+
+	let result = module_manager
+		.execute(name, matches)
+		.map_err(String::from)
+		.and_then(|output| output.write(&Format::new(matches), out));
+
+	// Exit codes of single commands do not end the session
+	base::set_exit_code(0);
+
+	result.map(|_| true)
+}
+
+struct ReplHelper {
+	names: Vec<String>,
+}
+
+impl Completer for ReplHelper {
+	type Candidate = String;
+
+	fn complete(
+		&self,
+		line: &str,
+		pos: usize,
+		_ctx: &Context<'_>,
+	) -> rustyline::Result<(usize, Vec<String>)> {
+		let prefix = &line[..pos];
+		// Only the sub command name is completed
+		if prefix.contains(char::is_whitespace) {
+			return Ok((pos, vec![]));
+		}
+		let candidates = self
+			.names
+			.iter()
+			.filter(|x| x.starts_with(prefix))
+			.cloned()
+			.collect();
+		Ok((0, candidates))
+	}
+}
+
+impl Hinter for ReplHelper {
+	type Hint = String;
+}
+
+impl Highlighter for ReplHelper {}
+
+impl Validator for ReplHelper {}
+
+impl Helper for ReplHelper {}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_execute_line() {
+		let (app, module_manager) = app::build_app();
+		let execute = |line: &str| {
+			let mut out = vec![];
+			execute_line(&app, &module_manager, line, &mut out)
+				.map(|x| (x, String::from_utf8(out).unwrap()))
+		};
+
+		assert_eq!(execute("s2h 'a b'"), Ok((true, "0x612062\n".to_string())));
+		assert_eq!(
+			execute("uuid_gen -v 5 -n dns -s example.com"),
+			Ok((true, "cfbff0d1-9375-5685-968c-48ce8b15ae17\n".to_string()))
+		);
+		assert_eq!(execute("h2s xyz"), Err("Convert failed".to_string()));
+		assert_eq!(execute("exit"), Ok((false, "".to_string())));
+	}
+}
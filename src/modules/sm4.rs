@@ -1,6 +1,6 @@
 use self::Mode::CTR;
 use crate::modules::base::Hex;
-use crate::modules::{base, Command, Module};
+use crate::modules::{base, Command, Error, Module, Output};
 use clap::{Arg, ArgMatches, SubCommand};
 use yogcrypt::sm4;
 
@@ -23,6 +23,7 @@ pub fn commands<'a, 'b>() -> Vec<Command<'a, 'b>> {
 						.required(false)
 						.index(1),
 				)
+				.arg(base::file_arg())
 				.arg(
 					Arg::with_name("MODE")
 						.long("mode")
@@ -49,6 +50,8 @@ pub fn commands<'a, 'b>() -> Vec<Command<'a, 'b>> {
 						.required(false),
 				),
 			f: sm4_enc,
+			serial: false,
+			pure: true,
 		},
 		Command {
 			app: SubCommand::with_name("sm4_dec")
@@ -59,6 +62,7 @@ pub fn commands<'a, 'b>() -> Vec<Command<'a, 'b>> {
 						.required(false)
 						.index(1),
 				)
+				.arg(base::file_arg())
 				.arg(
 					Arg::with_name("MODE")
 						.long("mode")
@@ -85,6 +89,8 @@ pub fn commands<'a, 'b>() -> Vec<Command<'a, 'b>> {
 						.required(false),
 				),
 			f: sm4_dec,
+			serial: false,
+			pure: true,
 		},
 	]
 }
@@ -97,7 +103,7 @@ enum KeySize {
 	KeySize128,
 }
 
-fn sm4_enc(matches: &ArgMatches) -> Result<Vec<String>, String> {
+fn sm4_enc(matches: &ArgMatches) -> Result<Output, Error> {
 	let (key_size, key, mode, input) = get_common_arg(matches)?;
 
 	// cipher
@@ -106,10 +112,10 @@ fn sm4_enc(matches: &ArgMatches) -> Result<Vec<String>, String> {
 	}?;
 	let result = Hex::from(result).into();
 
-	Ok(vec![result])
+	Ok(Output::Lines(vec![result]))
 }
 
-fn sm4_dec(matches: &ArgMatches) -> Result<Vec<String>, String> {
+fn sm4_dec(matches: &ArgMatches) -> Result<Output, Error> {
 	let (key_size, key, mode, input) = get_common_arg(matches)?;
 
 	// plain
@@ -118,7 +124,7 @@ fn sm4_dec(matches: &ArgMatches) -> Result<Vec<String>, String> {
 	}?;
 	let result = Hex::from(result).into();
 
-	Ok(vec![result])
+	Ok(Output::Lines(vec![result]))
 }
 
 fn get_common_arg(matches: &ArgMatches) -> Result<(KeySize, Vec<u8>, Mode, Vec<u8>), String> {
@@ -1,5 +1,5 @@
 use crate::modules::base::Hex;
-use crate::modules::{base, Command, Module};
+use crate::modules::{base, Command, Error, Module, Output};
 use clap::{Arg, ArgMatches, SubCommand};
 
 mod sr25519;
@@ -23,6 +23,8 @@ pub fn commands<'a, 'b>() -> Vec<Command<'a, 'b>> {
 			app: SubCommand::with_name("sr_gk")
 				.about("sr25519 generate key pair (Mini secret key, Public key)"),
 			f: sr_gk,
+			serial: false,
+			pure: true,
 		},
 		Command {
 			app: SubCommand::with_name("sr_sign")
@@ -33,6 +35,7 @@ pub fn commands<'a, 'b>() -> Vec<Command<'a, 'b>> {
 						.required(false)
 						.index(1),
 				)
+				.arg(base::file_arg())
 				.arg(
 					Arg::with_name("MINI_SECRET_KEY")
 						.long("mini-secret-key")
@@ -50,6 +53,8 @@ pub fn commands<'a, 'b>() -> Vec<Command<'a, 'b>> {
 						.required(false),
 				),
 			f: sr_sign,
+			serial: false,
+			pure: true,
 		},
 		Command {
 			app: SubCommand::with_name("sr_verify")
@@ -60,6 +65,7 @@ pub fn commands<'a, 'b>() -> Vec<Command<'a, 'b>> {
 						.required(false)
 						.index(1),
 				)
+				.arg(base::file_arg())
 				.arg(
 					Arg::with_name("PUBLIC_KEY")
 						.long("public-key")
@@ -77,6 +83,8 @@ pub fn commands<'a, 'b>() -> Vec<Command<'a, 'b>> {
 						.required(true),
 				),
 			f: sr_verify,
+			serial: false,
+			pure: true,
 		},
 		Command {
 			app: SubCommand::with_name("sr_sk")
@@ -91,6 +99,8 @@ pub fn commands<'a, 'b>() -> Vec<Command<'a, 'b>> {
 				),
 
 			f: sr_sk,
+			serial: false,
+			pure: true,
 		},
 		Command {
 			app: SubCommand::with_name("sr_pk")
@@ -113,11 +123,13 @@ pub fn commands<'a, 'b>() -> Vec<Command<'a, 'b>> {
 				),
 
 			f: sr_pk,
+			serial: false,
+			pure: true,
 		},
 	]
 }
 
-fn sr_gk(_matches: &ArgMatches) -> Result<Vec<String>, String> {
+fn sr_gk(_matches: &ArgMatches) -> Result<Output, Error> {
 	let (private_key, public_key) = sr25519::sr_gk_sr25519()?;
 
 	let (private_key, public_key): (String, String) =
@@ -125,10 +137,10 @@ fn sr_gk(_matches: &ArgMatches) -> Result<Vec<String>, String> {
 
 	let result = format!("({}, {})", private_key, public_key);
 
-	Ok(vec![result])
+	Ok(Output::Lines(vec![result]))
 }
 
-fn sr_sign(matches: &ArgMatches) -> Result<Vec<String>, String> {
+fn sr_sign(matches: &ArgMatches) -> Result<Output, Error> {
 	let secret_key = get_alt_secret_key(matches)?;
 
 	let input = base::input_string(matches)?;
@@ -138,10 +150,10 @@ fn sr_sign(matches: &ArgMatches) -> Result<Vec<String>, String> {
 
 	let result = Hex::from(sig).into();
 
-	Ok(vec![result])
+	Ok(Output::Lines(vec![result]))
 }
 
-fn sr_verify(matches: &ArgMatches) -> Result<Vec<String>, String> {
+fn sr_verify(matches: &ArgMatches) -> Result<Output, Error> {
 	let public_key = matches.value_of("PUBLIC_KEY").ok_or("Invalid public key")?;
 	let public_key: Vec<u8> = public_key
 		.parse::<Hex>()
@@ -158,10 +170,10 @@ fn sr_verify(matches: &ArgMatches) -> Result<Vec<String>, String> {
 
 	let result = "true".to_string();
 
-	Ok(vec![result])
+	Ok(Output::Lines(vec![result]))
 }
 
-fn sr_sk(matches: &ArgMatches) -> Result<Vec<String>, String> {
+fn sr_sk(matches: &ArgMatches) -> Result<Output, Error> {
 	let mini_secret_key = matches
 		.value_of("MINI_SECRET_KEY")
 		.ok_or("Invalid mini secret key")?;
@@ -174,17 +186,17 @@ fn sr_sk(matches: &ArgMatches) -> Result<Vec<String>, String> {
 
 	let result = Hex::from(secret_key).into();
 
-	Ok(vec![result])
+	Ok(Output::Lines(vec![result]))
 }
 
-fn sr_pk(matches: &ArgMatches) -> Result<Vec<String>, String> {
+fn sr_pk(matches: &ArgMatches) -> Result<Output, Error> {
 	let secret_key = get_alt_secret_key(matches)?;
 
 	let public_key = sr25519::sr_pk_sr25519(secret_key)?;
 
 	let result = Hex::from(public_key).into();
 
-	Ok(vec![result])
+	Ok(Output::Lines(vec![result]))
 }
 
 fn get_alt_secret_key(matches: &ArgMatches) -> Result<AltSecretKey, String> {
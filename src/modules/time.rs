@@ -1,6 +1,6 @@
-use crate::modules::{base, Command, Module};
+use crate::modules::{base, Command, Error, Module, Output};
 use chrono::offset::TimeZone;
-use chrono::{DateTime, FixedOffset, Local, NaiveDateTime};
+use chrono::{DateTime, FixedOffset, Local, NaiveDateTime, Offset, SecondsFormat, Utc};
 use clap::{Arg, ArgMatches, SubCommand};
 
 pub fn module<'a, 'b>() -> Module<'a, 'b> {
@@ -29,8 +29,11 @@ pub fn commands<'a, 'b>() -> Vec<Command<'a, 'b>> {
 						.takes_value(true)
 						.required(false),
 				)
-				.arg(Arg::with_name("INPUT").required(false).index(1)),
+				.arg(Arg::with_name("INPUT").required(false).index(1))
+				.arg(base::file_arg()),
 			f: ts2d,
+			serial: false,
+			pure: true,
 		},
 		Command {
 			app: SubCommand::with_name("d2ts")
@@ -43,17 +46,52 @@ pub fn commands<'a, 'b>() -> Vec<Command<'a, 'b>> {
 						.takes_value(true)
 						.required(false),
 				)
-				.arg(Arg::with_name("INPUT").required(false).index(1)),
+				.arg(Arg::with_name("INPUT").required(false).index(1))
+				.arg(base::file_arg()),
 			f: d2ts,
+			serial: false,
+			pure: true,
+		},
+		Command {
+			app: SubCommand::with_name("ts2date")
+				.about("Convert epoch seconds or milliseconds to ISO-8601 date")
+				.arg(tz_arg())
+				.arg(unit_arg().help(
+					"Unit of the timestamp [default: milliseconds from 100000000000 on, else seconds]",
+				))
+				.arg(Arg::with_name("INPUT").required(false).index(1))
+				.arg(base::file_arg()),
+			f: ts2date,
+			serial: false,
+			pure: true,
+		},
+		Command {
+			app: SubCommand::with_name("date2ts")
+				.about("Convert ISO-8601 date to epoch seconds or milliseconds")
+				.arg(tz_arg().help(
+					"Time zone of a date without offset, e.g. +08:00 or -5 [default: UTC]",
+				))
+				.arg(
+					unit_arg()
+						.help("Unit of the timestamp")
+						.default_value("s"),
+				)
+				.arg(Arg::with_name("INPUT").required(false).index(1))
+				.arg(base::file_arg()),
+			f: date2ts,
+			serial: false,
+			pure: true,
 		},
 		Command {
 			app: SubCommand::with_name("ts").about("Current timestamp"),
 			f: ts,
+			serial: false,
+			pure: true,
 		},
 	]
 }
 
-fn ts2d(matches: &ArgMatches) -> Result<Vec<String>, String> {
+fn ts2d(matches: &ArgMatches) -> Result<Output, Error> {
 	let input = base::input_string(matches)?;
 
 	let timestamp: i64 = input.parse().map_err(|_| "Invalid input")?;
@@ -64,7 +102,7 @@ fn ts2d(matches: &ArgMatches) -> Result<Vec<String>, String> {
 		Some(timezone) => {
 			let timezone: i32 = timezone.parse().map_err(|_| "Invalid input")?;
 			if timezone > 12 || timezone < -12 {
-				return Err("Invalid timezone".to_string());
+				return Err("Invalid timezone".into());
 			}
 			FixedOffset::east_opt(timezone * 3600)
 				.expect("")
@@ -82,10 +120,10 @@ fn ts2d(matches: &ArgMatches) -> Result<Vec<String>, String> {
 			.to_string(),
 	};
 
-	Ok(vec![result])
+	Ok(Output::Lines(vec![result]))
 }
 
-fn d2ts(matches: &ArgMatches) -> Result<Vec<String>, String> {
+fn d2ts(matches: &ArgMatches) -> Result<Output, Error> {
 	let input = base::input_string(matches)?;
 
 	let timezone = matches.value_of("TIMEZONE");
@@ -101,16 +139,145 @@ fn d2ts(matches: &ArgMatches) -> Result<Vec<String>, String> {
 
 	let result = format!("{}", result);
 
-	Ok(vec![result])
+	Ok(Output::Lines(vec![result]))
+}
+
+fn tz_arg<'a, 'b>() -> Arg<'a, 'b> {
+	Arg::with_name("TZ")
+		.long("tz")
+		.help("Time zone of the date, e.g. +08:00 or -5 [default: UTC]")
+		.takes_value(true)
+		.allow_hyphen_values(true)
+		.required(false)
+}
+
+fn unit_arg<'a, 'b>() -> Arg<'a, 'b> {
+	Arg::with_name("UNIT")
+		.long("unit")
+		.takes_value(true)
+		.possible_values(&["s", "ms"])
+		.required(false)
+}
+
+/// Unit of an epoch timestamp
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Unit {
+	Seconds,
+	Millis,
+}
+
+impl Unit {
+	fn from_arg(unit: Option<&str>) -> Option<Self> {
+		match unit {
+			Some("s") => Some(Unit::Seconds),
+			Some("ms") => Some(Unit::Millis),
+			_ => None,
+		}
+	}
+
+	/// Milliseconds from 1e11 on, as seconds that is after the year 5000 while as
+	/// milliseconds it is March 1973
+	fn detect(timestamp: i64) -> Self {
+		if timestamp.abs() >= 100_000_000_000 {
+			Unit::Millis
+		} else {
+			Unit::Seconds
+		}
+	}
+}
+
+fn ts2date(matches: &ArgMatches) -> Result<Output, Error> {
+	let input = base::input_string(matches)?;
+	let timestamp = input
+		.trim()
+		.parse::<i64>()
+		.map_err(|_| Error::invalid_input(format!("Invalid timestamp: {}", input.trim())))?;
+	let offset = matches.value_of("TZ").map(parse_tz).transpose()?;
+
+	let unit = Unit::from_arg(matches.value_of("UNIT")).unwrap_or_else(|| Unit::detect(timestamp));
+	let (secs, nanos) = match unit {
+		Unit::Seconds => (timestamp, 0),
+		Unit::Millis => (
+			timestamp.div_euclid(1000),
+			timestamp.rem_euclid(1000) as u32 * 1_000_000,
+		),
+	};
+
+	let result = iso_8601(secs, nanos, offset).map_err(Error::ValidationFailed)?;
+	Ok(Output::Lines(vec![result]))
 }
 
-fn ts(_matches: &ArgMatches) -> Result<Vec<String>, String> {
+fn date2ts(matches: &ArgMatches) -> Result<Output, Error> {
+	let input = base::input_string(matches)?;
+	let input = input.trim();
+	let offset = match matches.value_of("TZ") {
+		Some(tz) => parse_tz(tz)?,
+		None => Utc.fix(),
+	};
+
+	let time = DateTime::parse_from_rfc3339(input)
+		.ok()
+		.or_else(|| {
+			// A date without offset is at --tz
+			["%Y-%m-%dT%H:%M:%S%.f", "%Y-%m-%d %H:%M:%S%.f"]
+				.iter()
+				.find_map(|format| NaiveDateTime::parse_from_str(input, format).ok())
+				.and_then(|time| offset.from_local_datetime(&time).single())
+		})
+		.ok_or_else(|| Error::invalid_input(format!("Invalid ISO-8601 date: {}", input)))?;
+
+	let result = match Unit::from_arg(matches.value_of("UNIT")) {
+		Some(Unit::Millis) => time.timestamp_millis(),
+		_ => time.timestamp(),
+	};
+	Ok(Output::Lines(vec![result.to_string()]))
+}
+
+/// ISO-8601 date of an epoch timestamp, in UTC with a Z unless at the offset, with as
+/// many digits of the fraction of second as needed
+pub fn iso_8601(secs: i64, nanos: u32, offset: Option<FixedOffset>) -> Result<String, String> {
+	let offset = offset.unwrap_or_else(|| Utc.fix());
+	offset
+		.timestamp_opt(secs, nanos)
+		.single()
+		.map(|x| x.to_rfc3339_opts(SecondsFormat::AutoSi, true))
+		.ok_or_else(|| format!("Invalid timestamp: {}", secs))
+}
+
+/// Time zone as an offset: UTC or Z, +08:00 or -0530, or whole hours like 8 or -5
+fn parse_tz(tz: &str) -> Result<FixedOffset, Error> {
+	let invalid = || Error::Usage(format!("Invalid time zone: {}", tz));
+	if tz.eq_ignore_ascii_case("utc") || tz == "Z" {
+		return Ok(Utc.fix());
+	}
+	let (sign, rest) = match tz.strip_prefix('-') {
+		Some(rest) => (-1, rest),
+		None => (1, tz.strip_prefix('+').unwrap_or(tz)),
+	};
+	let digits = rest.replace(':', "");
+	if !digits.bytes().all(|x| x.is_ascii_digit()) {
+		return Err(invalid());
+	}
+	let (hours, minutes) = match digits.len() {
+		1 | 2 if !rest.contains(':') => (digits.as_str(), "0"),
+		4 => digits.split_at(2),
+		_ => return Err(invalid()),
+	};
+	let hours = hours.parse::<i32>().map_err(|_| invalid())?;
+	let minutes = minutes.parse::<i32>().map_err(|_| invalid())?;
+	if hours > 14 || minutes >= 60 {
+		return Err(invalid());
+	}
+	FixedOffset::east_opt(sign * (hours * 3600 + minutes * 60)).ok_or_else(invalid)
+}
+
+fn ts(_matches: &ArgMatches) -> Result<Output, Error> {
 	let now = Local::now();
 	let result = now.timestamp();
 
 	let result = format!("{}", result);
 
-	Ok(vec![result])
+	Ok(Output::Lines(vec![result]))
 }
 
 fn parse_standard(input: &str, timezone: Option<&str>) -> Result<Time, String> {
@@ -233,6 +400,77 @@ mod cases {
 					},
 				],
 			),
+			(
+				"ts2date",
+				vec![
+					Case {
+						desc: "".to_string(),
+						input: vec!["1516239022"].into_iter().map(Into::into).collect(),
+						output: vec!["2018-01-18T01:30:22Z"]
+							.into_iter()
+							.map(Into::into)
+							.collect(),
+						is_example: true,
+						is_test: true,
+						since: "0.17.0".to_string(),
+					},
+					Case {
+						desc: "Milliseconds are detected".to_string(),
+						input: vec!["--tz", "+08:00", "1516239022123"]
+							.into_iter()
+							.map(Into::into)
+							.collect(),
+						output: vec!["2018-01-18T09:30:22.123+08:00"]
+							.into_iter()
+							.map(Into::into)
+							.collect(),
+						is_example: true,
+						is_test: true,
+						since: "0.17.0".to_string(),
+					},
+					Case {
+						desc: "".to_string(),
+						input: vec!["--unit", "ms", "999"]
+							.into_iter()
+							.map(Into::into)
+							.collect(),
+						output: vec!["1970-01-01T00:00:00.999Z"]
+							.into_iter()
+							.map(Into::into)
+							.collect(),
+						is_example: false,
+						is_test: true,
+						since: "0.17.0".to_string(),
+					},
+				],
+			),
+			(
+				"date2ts",
+				vec![
+					Case {
+						desc: "".to_string(),
+						input: vec!["2018-01-18T01:30:22Z"]
+							.into_iter()
+							.map(Into::into)
+							.collect(),
+						output: vec!["1516239022"].into_iter().map(Into::into).collect(),
+						is_example: true,
+						is_test: true,
+						since: "0.17.0".to_string(),
+					},
+					Case {
+						desc: "Date without offset in a time zone".to_string(),
+						input: vec!["--tz", "8", "--unit", "ms", "2018-01-18T09:30:22.123"]
+							.into_iter()
+							.map(Into::into)
+							.collect(),
+						output: vec!["1516239022123"].into_iter().map(Into::into).collect(),
+						is_example: true,
+						is_test: true,
+						since: "0.17.0".to_string(),
+					},
+				],
+			),
 			(
 				"ts",
 				vec![Case {
@@ -259,4 +497,56 @@ mod tests {
 	fn test_cases() {
 		test_module(module());
 	}
+
+	fn run(args: Vec<&str>) -> Result<Output, Error> {
+		let command = commands()
+			.into_iter()
+			.find(|x| x.app.get_name() == args[0])
+			.unwrap();
+		(command.f)(&command.app.get_matches_from(args))
+	}
+
+	#[test]
+	fn test_round_trip() {
+		let lines = |x: &str| Ok::<_, Error>(Output::Lines(vec![x.to_string()]));
+		assert_eq!(
+			run(vec!["ts2date", "1516239022"]),
+			lines("2018-01-18T01:30:22Z")
+		);
+		assert_eq!(
+			run(vec!["date2ts", "2018-01-18T01:30:22Z"]),
+			lines("1516239022")
+		);
+
+		assert_eq!(
+			run(vec!["ts2date", "--tz", "-05:30", "1516239022123"]),
+			lines("2018-01-17T20:00:22.123-05:30")
+		);
+		assert_eq!(
+			run(vec![
+				"date2ts",
+				"--unit",
+				"ms",
+				"2018-01-17T20:00:22.123-05:30"
+			]),
+			lines("1516239022123")
+		);
+	}
+
+	#[test]
+	fn test_parse_tz() {
+		assert_eq!(parse_tz("UTC"), Ok(Utc.fix()));
+		assert_eq!(parse_tz("8").unwrap().local_minus_utc(), 8 * 3600);
+		assert_eq!(
+			parse_tz("-0530").unwrap().local_minus_utc(),
+			-(5 * 3600 + 30 * 60)
+		);
+		assert_eq!(parse_tz("+14:00").unwrap().local_minus_utc(), 14 * 3600);
+		for tz in ["+15", "8:00", "+08:60", "+0é"] {
+			assert_eq!(
+				parse_tz(tz),
+				Err(Error::Usage(format!("Invalid time zone: {}", tz)))
+			);
+		}
+	}
 }
@@ -1,4 +1,4 @@
-use crate::modules::{base, Command, Module};
+use crate::modules::{base, Command, Error, Module, Output};
 use clap::{Arg, ArgMatches, SubCommand};
 use std::char::EscapeUnicode;
 
@@ -29,19 +29,25 @@ pub fn commands<'a, 'b>() -> Vec<Command<'a, 'b>> {
 						.takes_value(true)
 						.required(false),
 				)
-				.arg(Arg::with_name("INPUT").required(false).index(1)),
+				.arg(Arg::with_name("INPUT").required(false).index(1))
+				.arg(base::long_file_arg()),
 			f: s2u,
+			serial: false,
+			pure: true,
 		},
 		Command {
 			app: SubCommand::with_name("u2s")
 				.about("Unicode to UTF-8 string")
-				.arg(Arg::with_name("INPUT").required(false).index(1)),
+				.arg(Arg::with_name("INPUT").required(false).index(1))
+				.arg(base::file_arg()),
 			f: u2s,
+			serial: false,
+			pure: true,
 		},
 	]
 }
 
-fn s2u(matches: &ArgMatches) -> Result<Vec<String>, String> {
+fn s2u(matches: &ArgMatches) -> Result<Output, Error> {
 	let input = base::input_string(matches)?;
 
 	let format = match matches.value_of("FORMAT") {
@@ -59,10 +65,10 @@ fn s2u(matches: &ArgMatches) -> Result<Vec<String>, String> {
 
 	let result = result.join("");
 
-	Ok(vec![result])
+	Ok(Output::Lines(vec![result]))
 }
 
-fn u2s(matches: &ArgMatches) -> Result<Vec<String>, String> {
+fn u2s(matches: &ArgMatches) -> Result<Output, Error> {
 	let input = base::input_string(matches)?;
 
 	let format = match input {
@@ -91,7 +97,7 @@ fn u2s(matches: &ArgMatches) -> Result<Vec<String>, String> {
 			.collect::<Result<String, String>>(),
 	}?;
 
-	Ok(vec![result])
+	Ok(Output::Lines(vec![result]))
 }
 
 fn format_html(data: EscapeUnicode) -> Result<String, String> {
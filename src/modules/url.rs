@@ -1,4 +1,4 @@
-use crate::modules::{base, Command, Module};
+use crate::modules::{base, Command, Error, Module, Output};
 use clap::{Arg, ArgMatches, SubCommand};
 
 pub fn module<'a, 'b>() -> Module<'a, 'b> {
@@ -14,32 +14,38 @@ pub fn commands<'a, 'b>() -> Vec<Command<'a, 'b>> {
 		Command {
 			app: SubCommand::with_name("ue")
 				.about("URL encode")
-				.arg(Arg::with_name("INPUT").required(false).index(1)),
+				.arg(Arg::with_name("INPUT").required(false).index(1))
+				.arg(base::file_arg()),
 			f: ue,
+			serial: false,
+			pure: true,
 		},
 		Command {
 			app: SubCommand::with_name("ud")
 				.about("URL decode")
-				.arg(Arg::with_name("INPUT").required(false).index(1)),
+				.arg(Arg::with_name("INPUT").required(false).index(1))
+				.arg(base::file_arg()),
 			f: ud,
+			serial: false,
+			pure: true,
 		},
 	]
 }
 
-fn ue(matches: &ArgMatches) -> Result<Vec<String>, String> {
+fn ue(matches: &ArgMatches) -> Result<Output, Error> {
 	let input = base::input_string(matches)?;
 
 	let result = urlencoding::encode(&input).into_owned();
 
-	Ok(vec![result])
+	Ok(Output::Lines(vec![result]))
 }
 
-fn ud(matches: &ArgMatches) -> Result<Vec<String>, String> {
+fn ud(matches: &ArgMatches) -> Result<Output, Error> {
 	let input = base::input_string(matches)?;
 
 	let result = urlencoding::decode(&input).map_err(|_| "Decode failed")?.into_owned();
 
-	Ok(vec![result])
+	Ok(Output::Lines(vec![result]))
 }
 
 mod cases {
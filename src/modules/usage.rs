@@ -1,9 +1,10 @@
-use crate::modules::Module;
+use crate::modules::{Module, Output};
 use clap::{App, Arg, ArgMatches, SubCommand};
 use linked_hash_map::LinkedHashMap;
 use madato::mk_table;
 use prettytable::{format, Cell, Row, Table};
 use regex::Captures;
+use serde_json::{json, Value};
 use std::cmp::min;
 
 pub fn app<'a, 'b>() -> App<'a, 'b> {
@@ -17,13 +18,39 @@ pub fn app<'a, 'b>() -> App<'a, 'b> {
 		.arg(
 			Arg::with_name("SEARCH")
 				.long("search")
-				.short("s").help("")
+				.short("s").help("Show only the examples containing the keyword (case insensitive)")
+				.takes_value(true)
+				.required(false))
+		.arg(
+			Arg::with_name("MODULE")
+				.long("module")
+				.short("m")
+				.help("Show only the modules whose description contains the keyword, e.g. jwt")
 				.takes_value(true)
 				.required(false))
 }
 
-pub fn run(matches: &ArgMatches, modules: &[Module]) -> Result<Vec<String>, String> {
-	let usage_info = get_usage_info(modules);
+pub fn run(matches: &ArgMatches, modules: &[Module]) -> Result<Output, String> {
+	let modules = match matches.value_of("MODULE") {
+		Some(module) => {
+			let module = module.to_lowercase();
+			modules
+				.iter()
+				.filter(|x| x.desc.to_lowercase().contains(&module))
+				.cloned()
+				.collect::<Vec<_>>()
+		}
+		None => modules.to_vec(),
+	};
+
+	if matches.is_present("json") {
+		return Ok(Output::Structured(json_output(
+			&modules,
+			matches.value_of("SEARCH"),
+		)));
+	}
+
+	let usage_info = get_usage_info(&modules);
 
 	let search = matches.value_of("SEARCH");
 
@@ -61,6 +88,42 @@ pub fn run(matches: &ArgMatches, modules: &[Module]) -> Result<Vec<String>, Stri
 		Some("plain") => term_plain_output(usage_info),
 		_ => term_table_output(usage_info),
 	}
+	.map(Output::Lines)
+}
+
+/// The example cases as they are, with the command line to run them
+fn json_output(modules: &[Module], search: Option<&str>) -> Value {
+	let search = search.map(str::to_lowercase);
+
+	let mut result = vec![];
+	for module in modules {
+		let cases = (module.get_cases)();
+		for command in &module.commands {
+			let name = command.app.get_name();
+			for case in cases.get(name).into_iter().flatten() {
+				let command_line = command_line(name, &case.input);
+				let found = match &search {
+					Some(search) => vec![name, &case.desc, &command_line]
+						.into_iter()
+						.chain(case.output.iter().map(String::as_str))
+						.any(|x| x.to_lowercase().contains(search)),
+					None => true,
+				};
+				if case.is_example && found {
+					result.push(json!({
+						"module": module.desc,
+						"command": name,
+						"desc": case.desc,
+						"input": case.input,
+						"output": case.output,
+						"since": case.since,
+						"command_line": command_line,
+					}));
+				}
+			}
+		}
+	}
+	Value::Array(result)
 }
 
 fn term_plain_output(
@@ -238,11 +301,7 @@ fn get_usage_info(
 						let example = {
 							let input = case.input.clone();
 							let output = case.output.clone();
-							let input = vec!["$ dtool".to_string(), name.to_string()]
-								.into_iter()
-								.chain(input)
-								.collect::<Vec<String>>()
-								.join(" ");
+							let input = format!("$ {}", command_line(name, &input));
 							vec![input]
 								.into_iter()
 								.chain(output)
@@ -260,7 +319,27 @@ fn get_usage_info(
 	result
 }
 
-fn get_about(app: &App) -> String {
+/// The command line running a case, the args are quoted for the shell
+pub fn command_line(name: &str, input: &[String]) -> String {
+	vec!["dtool".to_string(), name.to_string()]
+		.into_iter()
+		.chain(input.iter().map(|x| shell_quote(x)))
+		.collect::<Vec<String>>()
+		.join(" ")
+}
+
+/// Single quote an arg unless it only has safe chars, some case inputs are
+/// already quoted, the quotes are stripped first as the tests do
+fn shell_quote(arg: &str) -> String {
+	let arg = arg.trim_start_matches('\'').trim_end_matches('\'');
+	let safe = |c: char| c.is_ascii_alphanumeric() || "-_./:=,@%+".contains(c);
+	if !arg.is_empty() && arg.chars().all(safe) {
+		return arg.to_string();
+	}
+	format!("'{}'", arg.replace('\'', r"'\''"))
+}
+
+pub fn get_about(app: &App) -> String {
 	let mut help = Vec::new();
 	let _ = app.write_help(&mut help);
 
@@ -295,3 +374,45 @@ fn anchor(title: &str) -> String {
 		})
 		.collect::<String>()
 }
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_shell_quote() {
+		assert_eq!(shell_quote("abc"), "abc");
+		assert_eq!(shell_quote("-a"), "-a");
+		assert_eq!(shell_quote("0x616263"), "0x616263");
+		assert_eq!(shell_quote(""), "''");
+		assert_eq!(shell_quote("good tool"), "'good tool'");
+		assert_eq!(shell_quote("'good tool'"), "'good tool'");
+		assert_eq!(shell_quote(r#"{"sub":"1"}"#), r#"'{"sub":"1"}'"#);
+		assert_eq!(shell_quote("it's"), r"'it'\''s'");
+		assert_eq!(shell_quote("$HOME"), "'$HOME'");
+		assert_eq!(shell_quote("a|b"), "'a|b'");
+	}
+
+	#[test]
+	fn test_command_line() {
+		let input = vec!["-a", "HS256", r#"{"sub":"1234567890"}"#, "'a b'"]
+			.into_iter()
+			.map(Into::into)
+			.collect::<Vec<String>>();
+		assert_eq!(
+			command_line("jwt_encode", &input),
+			r#"dtool jwt_encode -a HS256 '{"sub":"1234567890"}' 'a b'"#
+		);
+
+		// Round trips through a shell split
+		let line = command_line("s2h", &["it's \\ $x".to_string()]);
+		assert_eq!(
+			shlex::split(&line),
+			Some(vec![
+				"dtool".to_string(),
+				"s2h".to_string(),
+				"it's \\ $x".to_string()
+			])
+		);
+	}
+}
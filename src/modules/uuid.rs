@@ -1,8 +1,15 @@
-use crate::modules::{base, Command, Module};
+use crate::log;
+use crate::modules::base::Hex;
+use crate::modules::{base, exit_code, hash, time, Command, Error, Module, Output};
 use clap::{Arg, ArgMatches, SubCommand};
-use uuid::{Timestamp, Uuid};
+use serde_json::json;
+use std::cmp::Ordering;
+use std::convert::TryInto;
+use std::time::{SystemTime, UNIX_EPOCH};
+use uuid::timestamp::context::ContextV7;
+use uuid::{Builder, Timestamp, Uuid};
 
-pub fn module<'a, 'b>() -> Module<'a, 'b> {
+pub(crate) fn module<'a, 'b>() -> Module<'a, 'b> {
 	Module {
 		desc: "UUID generation and parsing".to_string(),
 		commands: commands(),
@@ -10,7 +17,7 @@ pub fn module<'a, 'b>() -> Module<'a, 'b> {
 	}
 }
 
-pub fn commands<'a, 'b>() -> Vec<Command<'a, 'b>> {
+pub(crate) fn commands<'a, 'b>() -> Vec<Command<'a, 'b>> {
 	vec![
 		Command {
 			app: SubCommand::with_name("uuid_gen")
@@ -21,117 +28,953 @@ pub fn commands<'a, 'b>() -> Vec<Command<'a, 'b>> {
 						.long("version")
 						.takes_value(true)
 						.default_value("4")
-						.help("UUID version: 1, 4, 5, 7"),
+						.help("UUID version: 1, 3, 4, 5, 7, 8"),
 				)
 				.arg(
 					Arg::with_name("namespace")
 						.short("n")
 						.long("namespace")
 						.takes_value(true)
-						.help("Namespace for v5: dns, url, oid, x500"),
+						.help("Namespace for v3 and v5: dns, url, oid, x500 or a UUID"),
+				)
+				.arg(
+					Arg::with_name("namespace_hex")
+						.long("namespace-hex")
+						.takes_value(true)
+						.conflicts_with("namespace")
+						.help("Namespace for v3 and v5 as 16 raw bytes (Hex)"),
 				)
 				.arg(
 					Arg::with_name("name")
 						.short("s")
 						.long("name")
 						.takes_value(true)
-						.help("Name for v5 UUID"),
+						.help("Name for v3 and v5 UUID"),
+				)
+				.arg(
+					Arg::with_name("count")
+						.short("c")
+						.long("count")
+						.takes_value(true)
+						.default_value("1")
+						.help("Number of UUIDs to generate"),
+				)
+				.arg(
+					Arg::with_name("bytes")
+						.short("b")
+						.long("bytes")
+						.takes_value(true)
+						.help("16 custom bytes for v8 UUID (Hex)"),
+				)
+				.arg(
+					Arg::with_name("node")
+						.long("node")
+						.takes_value(true)
+						.help("6 node bytes for v1 UUID (Hex), or random"),
+				)
+				.arg(
+					Arg::with_name("format_file")
+						.long("format-file")
+						.takes_value(true)
+						.possible_values(&["txt", "csv", "sql"])
+						.default_value("txt")
+						.help("Format of the UUIDs: txt (one per line), csv (with an id header) or sql (INSERT statements)"),
+				)
+				.arg(
+					Arg::with_name("table")
+						.long("table")
+						.takes_value(true)
+						.help("Table name of the sql format"),
+				)
+				.arg(
+					Arg::with_name("out_file")
+						.long("out-file")
+						.takes_value(true)
+						.help("Write the UUIDs to a file, ready to import"),
 				),
 			f: uuid_gen,
+			serial: false,
+			pure: true,
 		},
 		Command {
 			app: SubCommand::with_name("uuid_parse")
 				.about("Parse UUID and show details")
-				.arg(Arg::with_name("INPUT").required(false).index(1)),
+				.arg(
+					Arg::with_name("RAW_FIELDS")
+						.long("raw-fields")
+						.help("Show the 128-bit integer in decimal and hex, and the bytes of each field"),
+				)
+				.arg(Arg::with_name("INPUT").required(false).index(1))
+				.arg(base::file_arg()),
 			f: uuid_parse,
+			serial: false,
+			pure: true,
+		},
+		Command {
+			app: SubCommand::with_name("uuid_compare")
+				.about("Compare two UUIDs (exit code: 0 equal, 10 A < B, 11 A > B)")
+				.arg(Arg::with_name("A").required(true).index(1))
+				.arg(Arg::with_name("B").required(true).index(2)),
+			f: uuid_compare,
+			serial: false,
+			pure: true,
+		},
+		Command {
+			app: SubCommand::with_name("uuid_validate")
+				.about("Validate UUID (exit code: 0 valid, 4 invalid)")
+				.arg(
+					Arg::with_name("require_version")
+						.long("require-version")
+						.takes_value(true)
+						.help("Require the UUID version, e.g. 4"),
+				)
+				.arg(Arg::with_name("INPUT").required(false).index(1))
+				.arg(base::file_arg()),
+			f: uuid_validate,
+			serial: false,
+			pure: true,
+		},
+		Command {
+			app: SubCommand::with_name("uuid_to_crockford")
+				.about("Encode UUID in Crockford base32 (26 chars)")
+				.arg(Arg::with_name("INPUT").required(false).index(1))
+				.arg(base::file_arg()),
+			f: uuid_to_crockford,
+			serial: false,
+			pure: true,
+		},
+		Command {
+			app: SubCommand::with_name("uuid_from_crockford")
+				.about("Decode UUID from Crockford base32, case insensitive, I and L read as 1, O as 0")
+				.arg(Arg::with_name("INPUT").required(false).index(1))
+				.arg(base::file_arg()),
+			f: uuid_from_crockford,
+			serial: false,
+			pure: true,
+		},
+		Command {
+			app: SubCommand::with_name("uuid_normalize")
+				.about("Normalize UUIDs to the lowercase hyphenated form, one per line of the input")
+				.arg(Arg::with_name("INPUT").required(false).index(1))
+				.arg(base::file_arg()),
+			f: uuid_normalize,
+			serial: false,
+			pure: true,
+		},
+		Command {
+			app: SubCommand::with_name("uuid_info")
+				.about("Summarize what a UUID tells: time-sortable, MAC address, deterministic and creation time")
+				.arg(Arg::with_name("INPUT").required(false).index(1))
+				.arg(base::file_arg()),
+			f: uuid_info,
+			serial: false,
+			pure: true,
 		},
 	]
 }
 
-fn uuid_gen(matches: &ArgMatches) -> Result<Vec<String>, String> {
+fn uuid_gen(matches: &ArgMatches) -> Result<Output, Error> {
 	let version = matches.value_of("version").unwrap();
+	let count = matches
+		.value_of("count")
+		.unwrap()
+		.parse::<usize>()
+		.map_err(|_| Error::Usage("Invalid count".to_string()))?;
+
+	// The namespace and bytes are validated by generating one
+	gen_uuid(matches, version)?;
+	let format = matches.value_of("format_file").unwrap();
+	let table = matches.value_of("table");
+	if format == "sql" {
+		check_table(table)?;
+	}
+	log::debug(&format!(
+		"uuid_gen: version={}, namespace={}, name={}, count={}",
+		version,
+		matches
+			.value_of("namespace")
+			.or_else(|| matches.value_of("namespace_hex"))
+			.unwrap_or("-"),
+		matches.value_of("name").unwrap_or("-"),
+		count
+	));
+	if let Some(output) = base::dry_run(matches, || format!("{} UUID v{}", count, version)) {
+		return Ok(output);
+	}
+
+	let result = match version {
+		"7" => generate_v7(count),
+		_ => (0..count)
+			.map(|_| gen_uuid(matches, version))
+			.collect::<Result<Vec<Uuid>, Error>>()?,
+	};
+
+	let lines = format_uuids(&result, format, table.unwrap_or_default());
+	match matches.value_of("out_file") {
+		Some(path) => {
+			let content = lines.iter().map(|x| format!("{}\n", x)).collect::<String>();
+			base::write_file_atomic(path, content.as_bytes(), matches.is_present("force"))
+				.map_err(Error::Io)?;
+			Ok(Output::Lines(vec![format!(
+				"{} UUIDs written to: {}",
+				count, path
+			)]))
+		}
+		None => Ok(Output::Lines(lines)),
+	}
+}
+
+/// The table name of the sql format, required and limited to identifiers, e.g. public.users
+fn check_table(table: Option<&str>) -> Result<(), Error> {
+	let table = table.ok_or_else(|| {
+		Error::Usage("Table (--table) is required for sql".to_string())
+			.with_hint("e.g. --table users")
+	})?;
+	let valid = table
+		.split('.')
+		.all(|x| !x.is_empty() && x.chars().all(|c| c.is_ascii_alphanumeric() || c == '_'));
+	match valid {
+		true => Ok(()),
+		false => Err(Error::Usage(format!("Invalid table name: {}", table))),
+	}
+}
+
+/// UUIDs as the lines of a txt, csv or sql file
+fn format_uuids(uuids: &[Uuid], format: &str, table: &str) -> Vec<String> {
+	let header = match format {
+		"csv" => Some("id".to_string()),
+		_ => None,
+	};
+	let lines = uuids.iter().map(|uuid| match format {
+		"sql" => format!("INSERT INTO {} (id) VALUES ('{}');", table, uuid),
+		_ => uuid.to_string(),
+	});
+	header.into_iter().chain(lines).collect()
+}
+
+fn gen_uuid(matches: &ArgMatches, version: &str) -> Result<Uuid, Error> {
+	let version = version
+		.parse()
+		.map_err(|_| Error::Unsupported(format!("Unsupported UUID version: {}", version)))?;
+	let options = GenerateOptions {
+		namespace: match matches.value_of("namespace_hex") {
+			Some(bytes) => Some(namespace_hex(bytes)?),
+			None => matches.value_of("namespace").map(namespace).transpose()?,
+		},
+		name: matches.value_of("name").map(String::from),
+		bytes: matches.value_of("bytes").map(custom_bytes).transpose()?,
+		node: node(matches)?,
+	};
 
+	generate(version, &options)
+}
+
+/// Options of `generate`, the inputs some versions require
+#[derive(Debug, Clone, Default)]
+pub struct GenerateOptions {
+	/// Namespace of v3 and v5
+	pub namespace: Option<Uuid>,
+	/// Name of v3 and v5
+	pub name: Option<String>,
+	/// Custom bytes of v8, the version and variant bits are forced
+	pub bytes: Option<[u8; 16]>,
+	/// Node of v1, 010203040506 by default
+	pub node: Option<[u8; 6]>,
+}
+
+/// Generate a UUID of the version: 1, 3, 4, 5, 7 or 8
+pub fn generate(version: usize, options: &GenerateOptions) -> Result<Uuid, Error> {
 	let uuid = match version {
-		"1" => {
+		1 => {
 			// Generate v1 UUID (timestamp-based)
 			let ts = Timestamp::now(uuid::timestamp::context::NoContext);
-			Uuid::new_v1(ts, &[1, 2, 3, 4, 5, 6])
+			Uuid::new_v1(ts, &options.node.unwrap_or([1, 2, 3, 4, 5, 6]))
 		}
-		"4" => {
+		3 => {
+			// Generate v3 UUID (namespace + name, MD5)
+			let (namespace, name) = name_based_input(options, version)?;
+			let hash = hash::md5([namespace.as_bytes(), name.as_bytes()].concat())?;
+			Builder::from_md5_bytes(first_16_bytes(&hash)).into_uuid()
+		}
+		4 => {
 			// Generate v4 UUID (random)
 			Uuid::new_v4()
 		}
-		"5" => {
-			// Generate v5 UUID (namespace + name)
-			let namespace_str = matches
-				.value_of("namespace")
-				.ok_or("Namespace (-n) is required for v5")?;
-			let name = matches
-				.value_of("name")
-				.ok_or("Name (-s) is required for v5")?;
-
-			let namespace = match namespace_str.to_lowercase().as_str() {
-				"dns" => uuid::Uuid::NAMESPACE_DNS,
-				"url" => uuid::Uuid::NAMESPACE_URL,
-				"oid" => uuid::Uuid::NAMESPACE_OID,
-				"x500" => uuid::Uuid::NAMESPACE_X500,
-				_ => {
-					return Err(format!(
-						"Invalid namespace: {}. Use dns, url, oid, or x500",
-						namespace_str
-					))
-				}
-			};
+		5 => {
+			// Generate v5 UUID (namespace + name, SHA-1)
+			let (namespace, name) = name_based_input(options, version)?;
+			let hash = hash::sha1([namespace.as_bytes(), name.as_bytes()].concat())?;
+			Builder::from_sha1_bytes(first_16_bytes(&hash)).into_uuid()
+		}
+		7 => {
+			// Generate v7 UUID (timestamp-based, sortable), ordered with the others of the process
+			Uuid::now_v7()
+		}
+		8 => {
+			// Generate v8 UUID (custom, version and variant bits are forced)
+			let bytes = options.bytes.ok_or_else(|| {
+				Error::Usage("Bytes (-b) is required for v8".to_string())
+					.with_hint("e.g. -b 0x00112233445566778899aabbccddeeff")
+			})?;
 
-			Uuid::new_v5(&namespace, name.as_bytes())
+			Uuid::new_v8(bytes)
 		}
-		"7" => {
-			// Generate v7 UUID (timestamp-based, sortable)
-			let ts = Timestamp::now(uuid::timestamp::context::NoContext);
-			Uuid::new_v7(ts)
+		_ => {
+			return Err(Error::Unsupported(format!(
+				"Unsupported UUID version: {}",
+				version
+			)))
 		}
-		_ => return Err(format!("Unsupported UUID version: {}", version)),
 	};
 
-	Ok(vec![uuid.to_string()])
+	Ok(uuid)
+}
+
+/// Generate count v7 UUIDs sharing one context, whose counter keeps them strictly
+/// increasing within the same millisecond
+pub fn generate_v7(count: usize) -> Vec<Uuid> {
+	let context = ContextV7::new();
+	(0..count)
+		.map(|_| Uuid::new_v7(Timestamp::now(&context)))
+		.collect()
+}
+
+fn custom_bytes(bytes: &str) -> Result<[u8; 16], Error> {
+	let bytes: Vec<u8> = bytes
+		.parse::<Hex>()
+		.map_err(|_| Error::invalid_input("Invalid bytes".to_string()))?
+		.into();
+	bytes.try_into().map_err(|_| {
+		Error::invalid_input("Invalid bytes: exactly 16 bytes are required".to_string())
+	})
+}
+
+fn node(matches: &ArgMatches) -> Result<Option<[u8; 6]>, Error> {
+	match matches.value_of("node") {
+		None => Ok(None),
+		Some("random") => {
+			// The multicast bit marks the node as not being a MAC address (RFC 4122 4.5)
+			let mut node = rand::random::<[u8; 6]>();
+			node[0] |= 0x01;
+			Ok(Some(node))
+		}
+		Some(node) => {
+			let node: Vec<u8> = node
+				.parse::<Hex>()
+				.map_err(|_| Error::invalid_input("Invalid node".to_string()))?
+				.into();
+			node.try_into().map(Some).map_err(|_| {
+				Error::invalid_input("Invalid node: exactly 6 bytes are required".to_string())
+			})
+		}
+	}
+}
+
+fn namespace(namespace: &str) -> Result<Uuid, Error> {
+	match namespace.to_lowercase().as_str() {
+		"dns" => Ok(uuid::Uuid::NAMESPACE_DNS),
+		"url" => Ok(uuid::Uuid::NAMESPACE_URL),
+		"oid" => Ok(uuid::Uuid::NAMESPACE_OID),
+		"x500" => Ok(uuid::Uuid::NAMESPACE_X500),
+		_ => Uuid::parse_str(namespace).map_err(|_| {
+			Error::Usage(format!("Invalid namespace: {}", namespace))
+				.with_hint("use dns, url, oid, x500 or a UUID")
+		}),
+	}
+}
+
+/// Namespace defined as 16 raw bytes rather than a formatted UUID
+fn namespace_hex(bytes: &str) -> Result<Uuid, Error> {
+	let bytes: Vec<u8> = bytes
+		.parse::<Hex>()
+		.map_err(|_| Error::invalid_input("Invalid namespace hex".to_string()))?
+		.into();
+	Uuid::from_slice(&bytes).map_err(|_| {
+		Error::invalid_input("Invalid namespace hex: exactly 16 bytes are required".to_string())
+	})
+}
+
+fn name_based_input(options: &GenerateOptions, version: usize) -> Result<(Uuid, &str), Error> {
+	let namespace = options.namespace.ok_or_else(|| {
+		Error::Usage(format!("Namespace (-n) is required for v{}", version))
+			.with_hint("e.g. -n dns -s example.com")
+	})?;
+	let name = options
+		.name
+		.as_deref()
+		.ok_or_else(|| Error::Usage(format!("Name (-s) is required for v{}", version)))?;
+
+	Ok((namespace, name))
+}
+
+fn first_16_bytes(hash: &[u8]) -> [u8; 16] {
+	let mut bytes = [0u8; 16];
+	bytes.copy_from_slice(&hash[..16]);
+	bytes
+}
+
+fn uuid_parse(matches: &ArgMatches) -> Result<Output, Error> {
+	let now = SystemTime::now()
+		.duration_since(UNIX_EPOCH)
+		.map_err(|e| e.to_string())?
+		.as_secs();
+	uuid_parse_at(matches, now)
 }
 
-fn uuid_parse(matches: &ArgMatches) -> Result<Vec<String>, String> {
+/// uuid_parse with the current time in seconds since the epoch, for the Age line
+fn uuid_parse_at(matches: &ArgMatches, now: u64) -> Result<Output, Error> {
 	let input = base::input_string(matches)?;
-	let uuid_str = input.trim();
 
-	let uuid = Uuid::parse_str(uuid_str).map_err(|e| format!("Invalid UUID: {}", e))?;
+	let (uuid, form) = parse(&input)?;
+
+	let timestamp = unix_timestamp(&uuid);
+
+	let age = timestamp.map(|(secs, _)| humanize_age(secs, now));
+	let date = timestamp
+		.map(|(secs, nanos)| time::iso_8601(secs as i64, nanos, None))
+		.transpose()?;
+
+	if matches.is_present("json") {
+		let mut result = json!({
+			"input_form": form,
+			"version": get_version_name(uuid.get_version_num()),
+			"variant": get_variant_name(&uuid),
+			"valid": true,
+		});
+		if let Some((secs, nanos)) = timestamp {
+			result["timestamp"] = json!({ "seconds": secs, "nanoseconds": nanos });
+		}
+		if let Some(date) = &date {
+			result["date"] = json!(date);
+		}
+		if let Some(age) = age {
+			result["age"] = json!(age);
+		}
+		if matches.is_present("RAW_FIELDS") {
+			// As strings, a JSON number can not hold 128 bits
+			result["integer"] = json!({
+				"dec": uuid.as_u128().to_string(),
+				"hex": format!("0x{:032x}", uuid.as_u128()),
+			});
+			result["fields"] = fields(&uuid)
+				.iter()
+				.map(|(name, value)| (name.to_string(), json!(value)))
+				.collect();
+		}
+		return Ok(Output::Structured(result));
+	}
 
 	let mut result = Vec::new();
 
+	// Input form
+	result.push(format!("Input form: {}", form));
+
 	// Version
 	result.push(format!("Version: {}", get_version_name(uuid.get_version_num())));
 
 	// Variant
 	result.push(format!("Variant: {}", get_variant_name(&uuid)));
 
-	// Timestamp (for v1 and v7)
-	if let Some(version) = uuid.get_version() {
-		match version {
-			uuid::Version::Mac => {
-				if let Some(ts) = uuid.get_timestamp() {
-					let (secs, nanos) = ts.to_unix();
-					result.push(format!("Timestamp: {} seconds, {} nanoseconds", secs, nanos));
-				}
+	if let Some((secs, nanos)) = timestamp {
+		result.push(format!(
+			"Timestamp: {} seconds, {} nanoseconds",
+			secs, nanos
+		));
+	}
+
+	if let Some(date) = date {
+		result.push(format!("Date: {}", date));
+	}
+
+	if let Some(age) = age {
+		result.push(format!("Age: {}", age));
+	}
+
+	if matches.is_present("RAW_FIELDS") {
+		result.push(format!("Integer (dec): {}", uuid.as_u128()));
+		result.push(format!("Integer (hex): 0x{:032x}", uuid.as_u128()));
+		let fields = fields(&uuid)
+			.iter()
+			.map(|(name, value)| format!("{}={}", name, value))
+			.collect::<Vec<_>>();
+		result.push(format!("Fields: {}", fields.join(" ")));
+	}
+
+	result.push(format!("Valid: true"));
+
+	Ok(Output::Lines(result))
+}
+
+/// Seconds and nanoseconds since the Unix epoch of v1, v6 and v7
+fn unix_timestamp(uuid: &Uuid) -> Option<(u64, u32)> {
+	match uuid.get_version() {
+		Some(uuid::Version::Mac) | Some(uuid::Version::SortMac) | Some(uuid::Version::SortRand) => {
+			uuid.get_timestamp().map(|ts| ts.to_unix())
+		}
+		_ => None,
+	}
+}
+
+fn uuid_info(matches: &ArgMatches) -> Result<Output, Error> {
+	let input = base::input_string(matches)?;
+	let (uuid, _) = parse(&input)?;
+
+	let version = get_version_name(uuid.get_version_num());
+	let info = match info(&uuid) {
+		Ok(info) => info,
+		Err(summary) => {
+			if matches.is_present("json") {
+				return Ok(Output::Structured(json!({
+					"summary": summary,
+					"version": version,
+				})));
 			}
-			uuid::Version::SortRand => {
-				if let Some(ts) = uuid.get_timestamp() {
-					let (secs, nanos) = ts.to_unix();
-					result.push(format!("Timestamp: {} seconds, {} nanoseconds", secs, nanos));
-				}
+			return Ok(Output::Lines(vec![
+				format!("Summary: {}", summary),
+				format!("Version: {}", version),
+			]));
+		}
+	};
+
+	let mac = info.node.filter(is_mac).map(|node| {
+		node.iter()
+			.map(|x| format!("{:02x}", x))
+			.collect::<Vec<_>>()
+			.join(":")
+	});
+	let created = info
+		.timestamp
+		.map(|(secs, nanos)| time::iso_8601(secs as i64, nanos, None))
+		.transpose()?;
+
+	if matches.is_present("json") {
+		return Ok(Output::Structured(json!({
+			"summary": info.summary(),
+			"version": version,
+			"sortable": info.sortable,
+			"mac_address": mac,
+			"deterministic": info.hash.is_some(),
+			"created": created,
+		})));
+	}
+
+	let yes_no = |x: bool| match x {
+		true => "yes",
+		false => "no",
+	};
+	Ok(Output::Lines(vec![
+		format!("Summary: {}", info.summary()),
+		format!("Version: {}", version),
+		format!("Time-sortable: {}", yes_no(info.sortable)),
+		format!(
+			"MAC address: {}",
+			match (&mac, info.node) {
+				(Some(mac), _) => mac.clone(),
+				(None, Some(_)) => "no (random node)".to_string(),
+				(None, None) => "no".to_string(),
 			}
+		),
+		format!(
+			"Deterministic: {}",
+			match info.hash {
+				Some(hash) => format!("yes ({} of a namespace and a name)", hash),
+				None => "no".to_string(),
+			}
+		),
+		format!(
+			"Created: {}",
+			created.as_deref().unwrap_or("unknown (no timestamp)")
+		),
+	]))
+}
+
+/// What the version of a UUID tells about it
+struct Info {
+	/// The order of the bytes is the order of creation
+	sortable: bool,
+	/// The node of v1, v2 and v6
+	node: Option<[u8; 6]>,
+	/// The hash of the namespace and name of v3 and v5
+	hash: Option<&'static str>,
+	timestamp: Option<(u64, u32)>,
+}
+
+impl Info {
+	/// e.g. "random, not sortable, no timestamp"
+	fn summary(&self) -> String {
+		let mut parts = vec![];
+		match (self.hash, self.node, self.timestamp) {
+			(Some(hash), _, _) => parts.push(format!(
+				"deterministic ({} of a namespace and a name)",
+				hash
+			)),
+			(None, None, None) => parts.push("random".to_string()),
 			_ => {}
 		}
+		parts.push(
+			match self.sortable {
+				true => "time-sortable",
+				false => "not sortable",
+			}
+			.to_string(),
+		);
+		if let Some(node) = self.node {
+			parts.push(
+				match is_mac(&node) {
+					true => "embeds a MAC address",
+					false => "random node",
+				}
+				.to_string(),
+			);
+		}
+		parts.push(
+			match self.timestamp {
+				Some(_) => "embeds creation time",
+				None => "no timestamp",
+			}
+			.to_string(),
+		);
+		parts.join(", ")
 	}
+}
 
-	result.push(format!("Valid: true"));
+/// The info of a UUID of RFC 4122 / RFC 9562, or else the summary of what it is
+fn info(uuid: &Uuid) -> Result<Info, String> {
+	if uuid.is_nil() {
+		return Err("nil UUID, all zeros".to_string());
+	}
+	if uuid.is_max() {
+		return Err("max UUID, all ones".to_string());
+	}
+	if uuid.get_variant() != uuid::Variant::RFC4122 {
+		return Err(format!(
+			"{} variant, the version bits mean nothing",
+			get_variant_name(uuid)
+		));
+	}
+
+	let node = || Some(first_6_bytes(&uuid.as_bytes()[10..]));
+	let (sortable, node, hash) = match uuid.get_version() {
+		Some(uuid::Version::Mac) | Some(uuid::Version::Dce) => (false, node(), None),
+		Some(uuid::Version::Md5) => (false, None, Some("MD5")),
+		Some(uuid::Version::Random) => (false, None, None),
+		Some(uuid::Version::Sha1) => (false, None, Some("SHA-1")),
+		Some(uuid::Version::SortMac) => (true, node(), None),
+		Some(uuid::Version::SortRand) => (true, None, None),
+		Some(uuid::Version::Custom) => {
+			return Err("custom (v8), the layout is up to its creator".to_string())
+		}
+		_ => return Err("unknown version".to_string()),
+	};
+
+	Ok(Info {
+		sortable,
+		node,
+		hash,
+		// None for v2, the low bits of its time are replaced by a local id
+		timestamp: unix_timestamp(uuid),
+	})
+}
+
+/// A node is a MAC address unless its multicast bit is set (RFC 4122 4.5)
+fn is_mac(node: &[u8; 6]) -> bool {
+	node[0] & 0x01 == 0
+}
+
+fn first_6_bytes(bytes: &[u8]) -> [u8; 6] {
+	let mut node = [0u8; 6];
+	node.copy_from_slice(&bytes[..6]);
+	node
+}
+
+/// The bytes of the 4-2-2-2-6 fields of RFC 4122 in hex, whatever the version
+fn fields(uuid: &Uuid) -> [(&'static str, String); 5] {
+	let bytes = uuid.as_bytes();
+	[
+		("time_low", hex::encode(&bytes[0..4])),
+		("time_mid", hex::encode(&bytes[4..6])),
+		("time_hi_and_version", hex::encode(&bytes[6..8])),
+		("clock_seq", hex::encode(&bytes[8..10])),
+		("node", hex::encode(&bytes[10..16])),
+	]
+}
+
+fn uuid_compare(matches: &ArgMatches) -> Result<Output, Error> {
+	let (a, _) = parse(matches.value_of("A").ok_or("Invalid UUID A")?)?;
+	let (b, _) = parse(matches.value_of("B").ok_or("Invalid UUID B")?)?;
+
+	// Hyphenated lowercase strings sort the same as the raw bytes
+	let lexicographic = a.as_bytes().cmp(b.as_bytes());
+
+	let timestamp = match (a.get_timestamp(), b.get_timestamp()) {
+		(Some(ta), Some(tb)) if a.get_version_num() == b.get_version_num() => {
+			Some(ta.to_unix().cmp(&tb.to_unix()))
+		}
+		_ => None,
+	};
+
+	let differing_bytes = a
+		.as_bytes()
+		.iter()
+		.zip(b.as_bytes().iter())
+		.enumerate()
+		.filter(|(_, (x, y))| x != y)
+		.map(|(i, _)| i)
+		.collect::<Vec<usize>>();
+
+	base::set_exit_code(match lexicographic {
+		Ordering::Equal => 0,
+		Ordering::Less => exit_code::LESS,
+		Ordering::Greater => exit_code::GREATER,
+	});
+
+	if matches.is_present("json") {
+		let result = json!({
+			"a": a.to_string(),
+			"b": b.to_string(),
+			"equal": a == b,
+			"lexicographic": ordering_name(lexicographic),
+			"timestamp": timestamp.map(ordering_name),
+			"differing_bytes": differing_bytes,
+		});
+		return Ok(Output::Structured(result));
+	}
+
+	let timestamp = match timestamp {
+		Some(Ordering::Equal) => "A same time as B".to_string(),
+		Some(Ordering::Less) => "A earlier than B".to_string(),
+		Some(Ordering::Greater) => "A later than B".to_string(),
+		None => format!(
+			"not applicable (v{} vs v{})",
+			a.get_version_num(),
+			b.get_version_num()
+		),
+	};
+
+	let differing_bytes = match differing_bytes.len() {
+		0 => "none".to_string(),
+		n => format!(
+			"{} of 16 at [{}]",
+			n,
+			differing_bytes
+				.iter()
+				.map(|x| x.to_string())
+				.collect::<Vec<String>>()
+				.join(", ")
+		),
+	};
+
+	Ok(Output::Lines(vec![
+		format!("A: {}", a),
+		format!("B: {}", b),
+		format!("Equal: {}", a == b),
+		format!(
+			"Lexicographic: A {} B",
+			match lexicographic {
+				Ordering::Equal => "=",
+				Ordering::Less => "<",
+				Ordering::Greater => ">",
+			}
+		),
+		format!("Timestamp: {}", timestamp),
+		format!("Differing bytes: {}", differing_bytes),
+	]))
+}
+
+fn uuid_validate(matches: &ArgMatches) -> Result<Output, Error> {
+	let require_version = match matches.value_of("require_version") {
+		Some(version) => Some(
+			version
+				.parse::<usize>()
+				.map_err(|_| Error::Usage("Invalid version".to_string()))?,
+		),
+		None => None,
+	};
+	let input = base::input_string(matches)?;
+
+	let (_, body) = split_uuid_form(input.trim());
+	let result = match Uuid::parse_str(body) {
+		Ok(uuid) => match require_version {
+			Some(expected) if uuid.get_version_num() != expected => Err(format!(
+				"wrong version: expected {}, got {}",
+				expected,
+				uuid.get_version_num()
+			)),
+			_ => Ok(()),
+		},
+		Err(e) => Err(e.to_string()),
+	};
+
+	base::set_exit_code(match result {
+		Ok(()) => 0,
+		Err(_) => exit_code::INVALID,
+	});
+
+	if matches.is_present("json") {
+		return Ok(Output::Structured(match result {
+			Ok(()) => json!({ "valid": true }),
+			Err(reason) => json!({ "valid": false, "reason": reason }),
+		}));
+	}
+
+	Ok(Output::Lines(vec![match result {
+		Ok(()) => "valid".to_string(),
+		Err(reason) => format!("invalid: {}", reason),
+	}]))
+}
+
+/// Humanize the distance between a timestamp and now, e.g. "3h 12m ago" or "in 5s"
+fn humanize_age(timestamp: u64, now: u64) -> String {
+	let (distance, future) = if timestamp > now {
+		(timestamp - now, true)
+	} else {
+		(now - timestamp, false)
+	};
+
+	let units = [("d", 86400), ("h", 3600), ("m", 60), ("s", 1)];
+	let mut parts = units
+		.iter()
+		.scan(distance, |rest, (name, size)| {
+			let value = *rest / size;
+			*rest %= size;
+			Some((value, name))
+		})
+		.skip_while(|(value, _)| *value == 0)
+		.take(2)
+		.filter(|(value, _)| *value > 0)
+		.map(|(value, name)| format!("{}{}", value, name))
+		.collect::<Vec<String>>();
+	if parts.is_empty() {
+		parts.push("0s".to_string());
+	}
+	let distance = parts.join(" ");
+
+	if future {
+		format!("in {}", distance)
+	} else {
+		format!("{} ago", distance)
+	}
+}
+
+fn ordering_name(ordering: Ordering) -> &'static str {
+	match ordering {
+		Ordering::Equal => "equal",
+		Ordering::Less => "less",
+		Ordering::Greater => "greater",
+	}
+}
+
+fn uuid_to_crockford(matches: &ArgMatches) -> Result<Output, Error> {
+	let input = base::input_string(matches)?;
+	let (uuid, _) = parse(&input)?;
+	Ok(Output::Lines(vec![to_crockford(&uuid)]))
+}
+
+fn uuid_from_crockford(matches: &ArgMatches) -> Result<Output, Error> {
+	let input = base::input_string(matches)?;
+	let uuid = from_crockford(input.trim())?;
+	Ok(Output::Lines(vec![uuid.hyphenated().to_string()]))
+}
+
+/// Every form `parse` accepts, so that `sort | uniq` finds the duplicates
+fn uuid_normalize(matches: &ArgMatches) -> Result<Output, Error> {
+	let input = base::input_string(matches)?;
+	let lines = input
+		.lines()
+		.enumerate()
+		.filter(|(_, line)| !line.trim().is_empty())
+		.collect::<Vec<_>>();
+
+	let result = lines
+		.iter()
+		.map(|(i, line)| {
+			let (uuid, _) = parse(line).map_err(|e| match lines.len() {
+				1 => e,
+				_ => Error::ValidationFailed(format!(
+					"Invalid UUID on line {}: {}",
+					i + 1,
+					line.trim()
+				)),
+			})?;
+			Ok(uuid.hyphenated().to_string())
+		})
+		.collect::<Result<Vec<_>, Error>>()?;
+
+	Ok(Output::Lines(result))
+}
+
+/// Crockford base32 alphabet, without I, L, O and U
+const CROCKFORD: &[u8; 32] = b"0123456789ABCDEFGHJKMNPQRSTVWXYZ";
+
+/// The 128 bits in 26 chars of 5 bits, the first one only holds 3
+pub fn to_crockford(uuid: &Uuid) -> String {
+	let value = uuid.as_u128();
+	(0..26)
+		.rev()
+		.map(|i| CROCKFORD[((value >> (i * 5)) & 0x1f) as usize] as char)
+		.collect()
+}
+
+/// Decode the 26 chars of `to_crockford`, case insensitive, with I and L read as 1 and
+/// O as 0, the hyphens are ignored
+pub fn from_crockford(input: &str) -> Result<Uuid, Error> {
+	let invalid =
+		|reason: String| Error::invalid_input(format!("Invalid Crockford base32: {}", reason));
+
+	let digits = input
+		.chars()
+		.filter(|x| *x != '-')
+		.map(|x| {
+			let c = match x.to_ascii_uppercase() {
+				'I' | 'L' => '1',
+				'O' => '0',
+				c => c,
+			};
+			CROCKFORD
+				.iter()
+				.position(|y| *y as char == c)
+				.ok_or_else(|| invalid(format!("unexpected char '{}'", x)))
+		})
+		.collect::<Result<Vec<_>, Error>>()?;
+	if digits.len() != 26 {
+		return Err(invalid(format!("expected 26 chars, got {}", digits.len())));
+	}
+	if digits[0] > 7 {
+		return Err(invalid("the first char must be 0 to 7".to_string()));
+	}
+
+	let value = digits
+		.iter()
+		.fold(0u128, |value, digit| (value << 5) | *digit as u128);
+	Ok(Uuid::from_u128(value))
+}
+
+/// Parse a UUID given in hyphenated, simple, braced or URN form
+/// Returns the UUID and the name of the detected input form
+pub fn parse(input: &str) -> Result<(Uuid, &'static str), Error> {
+	let (form, body) = split_uuid_form(input.trim());
+
+	let uuid = Uuid::parse_str(body)
+		.map_err(|e| Error::ValidationFailed(format!("Invalid UUID: {}", e)))?;
+
+	Ok((uuid, form))
+}
 
-	Ok(result)
+/// The name of the input form and the UUID without braces or URN prefix
+fn split_uuid_form(input: &str) -> (&'static str, &str) {
+	if input.starts_with('{') && input.ends_with('}') {
+		("braced", &input[1..input.len() - 1])
+	} else if input
+		.get(..9)
+		.map(|x| x.eq_ignore_ascii_case("urn:uuid:"))
+		.unwrap_or(false)
+	{
+		("urn", &input[9..])
+	} else if input.len() == 32 {
+		("simple", input)
+	} else {
+		("hyphenated", input)
+	}
 }
 
 fn get_version_name(version: usize) -> String {
@@ -178,6 +1021,20 @@ mod cases {
 						is_test: false, // Skip test for random UUID
 						since: "0.16.0".to_string(),
 					},
+					Case {
+						desc: "Generate UUID v3 with DNS namespace".to_string(),
+						input: vec!["-v", "3", "-n", "dns", "-s", "example.com"]
+							.into_iter()
+							.map(Into::into)
+							.collect(),
+						output: vec!["9073926b-929f-31c2-abc9-fad77ae3e8eb"]
+							.into_iter()
+							.map(Into::into)
+							.collect(),
+						is_example: true,
+						is_test: true,
+						since: "0.17.0".to_string(),
+					},
 					Case {
 						desc: "Generate UUID v5 with DNS namespace".to_string(),
 						input: vec!["-v", "5", "-n", "dns", "-s", "example.com"]
@@ -192,42 +1049,992 @@ mod cases {
 						is_test: true,
 						since: "0.16.0".to_string(),
 					},
-				],
-			),
-			(
-				"uuid_parse",
-				vec![Case {
-					desc: "Parse UUID v4".to_string(),
-					input: vec!["550e8400-e29b-41d4-a716-446655440000"]
+					Case {
+						desc: "Generate UUID v5 as an SQL INSERT statement".to_string(),
+						input: vec![
+							"-v",
+							"5",
+							"-n",
+							"dns",
+							"-s",
+							"example.com",
+							"--format-file",
+							"sql",
+							"--table",
+							"users",
+						]
 						.into_iter()
 						.map(Into::into)
 						.collect(),
-					output: vec![
-						"Version: 4 (Random)",
-						"Variant: RFC 4122",
-						"Valid: true",
-					]
-					.into_iter()
-					.map(Into::into)
-					.collect(),
-					is_example: true,
-					is_test: true,
-					since: "0.16.0".to_string(),
-				}],
-			),
-		]
-		.into_iter()
-		.collect()
-	}
-}
-
-#[cfg(test)]
-mod tests {
-	use super::*;
-	use crate::modules::base::test::test_module;
-
-	#[test]
-	fn test_cases() {
-		test_module(module());
+						output: vec!["INSERT INTO users (id) VALUES ('cfbff0d1-9375-5685-968c-48ce8b15ae17');"]
+							.into_iter()
+							.map(Into::into)
+							.collect(),
+						is_example: true,
+						is_test: true,
+						since: "0.17.0".to_string(),
+					},
+					Case {
+						desc: "Generate 1000 UUIDs to a CSV file with an id header".to_string(),
+						input: vec!["-c", "1000", "--format-file", "csv", "--out-file", "ids.csv"]
+							.into_iter()
+							.map(Into::into)
+							.collect(),
+						output: vec!["1000 UUIDs written to: ids.csv"]
+							.into_iter()
+							.map(Into::into)
+							.collect(),
+						is_example: true,
+						is_test: false, // Writes a file
+						since: "0.17.0".to_string(),
+					},
+					Case {
+						desc: "Generate UUID v5 with a UUID namespace".to_string(),
+						input: vec![
+							"-v",
+							"5",
+							"-n",
+							"6ba7b810-9dad-11d1-80b4-00c04fd430c8",
+							"-s",
+							"example.com",
+						]
+						.into_iter()
+						.map(Into::into)
+						.collect(),
+						output: vec!["cfbff0d1-9375-5685-968c-48ce8b15ae17"]
+							.into_iter()
+							.map(Into::into)
+							.collect(),
+						is_example: false,
+						is_test: true,
+						since: "0.17.0".to_string(),
+					},
+					Case {
+						desc: "Generate UUID v5 with a namespace of 16 raw bytes".to_string(),
+						input: vec![
+							"-v",
+							"5",
+							"--namespace-hex",
+							"6ba7b8109dad11d180b400c04fd430c8",
+							"-s",
+							"example.com",
+						]
+						.into_iter()
+						.map(Into::into)
+						.collect(),
+						output: vec!["cfbff0d1-9375-5685-968c-48ce8b15ae17"]
+							.into_iter()
+							.map(Into::into)
+							.collect(),
+						is_example: true,
+						is_test: true,
+						since: "0.17.0".to_string(),
+					},
+					Case {
+						desc: "Generate 2 UUID v5 with DNS namespace".to_string(),
+						input: vec!["-v", "5", "-n", "dns", "-s", "example.com", "-c", "2"]
+							.into_iter()
+							.map(Into::into)
+							.collect(),
+						output: vec![
+							"cfbff0d1-9375-5685-968c-48ce8b15ae17",
+							"cfbff0d1-9375-5685-968c-48ce8b15ae17",
+						]
+						.into_iter()
+						.map(Into::into)
+						.collect(),
+						is_example: false,
+						is_test: true,
+						since: "0.17.0".to_string(),
+					},
+					Case {
+						desc: "Validate the arguments without generating".to_string(),
+						input: vec![
+							"--dry-run",
+							"-v",
+							"5",
+							"-n",
+							"dns",
+							"-s",
+							"example.com",
+							"-c",
+							"1000000",
+						]
+						.into_iter()
+						.map(Into::into)
+						.collect(),
+						output: vec!["OK: would produce 1000000 UUID v5"]
+							.into_iter()
+							.map(Into::into)
+							.collect(),
+						is_example: false,
+						is_test: true,
+						since: "0.17.0".to_string(),
+					},
+					Case {
+						desc: "Generate UUID v8 from custom bytes".to_string(),
+						input: vec!["-v", "8", "-b", "0x00112233445566778899aabbccddeeff"]
+							.into_iter()
+							.map(Into::into)
+							.collect(),
+						output: vec!["00112233-4455-8677-8899-aabbccddeeff"]
+							.into_iter()
+							.map(Into::into)
+							.collect(),
+						is_example: true,
+						is_test: true,
+						since: "0.17.0".to_string(),
+					},
+				],
+			),
+			(
+				"uuid_parse",
+				vec![
+					Case {
+						desc: "Parse UUID v4".to_string(),
+						input: vec!["550e8400-e29b-41d4-a716-446655440000"]
+							.into_iter()
+							.map(Into::into)
+							.collect(),
+						output: vec![
+							"Input form: hyphenated",
+							"Version: 4 (Random)",
+							"Variant: RFC 4122",
+							"Valid: true",
+						]
+						.into_iter()
+						.map(Into::into)
+						.collect(),
+						is_example: true,
+						is_test: true,
+						since: "0.16.0".to_string(),
+					},
+					Case {
+						desc: "Parse braced UUID".to_string(),
+						input: vec!["{550e8400-e29b-41d4-a716-446655440000}"]
+							.into_iter()
+							.map(Into::into)
+							.collect(),
+						output: vec![
+							"Input form: braced",
+							"Version: 4 (Random)",
+							"Variant: RFC 4122",
+							"Valid: true",
+						]
+						.into_iter()
+						.map(Into::into)
+						.collect(),
+						is_example: true,
+						is_test: true,
+						since: "0.17.0".to_string(),
+					},
+					Case {
+						desc: "Parse URN UUID".to_string(),
+						input: vec!["URN:UUID:550e8400-e29b-41d4-a716-446655440000"]
+							.into_iter()
+							.map(Into::into)
+							.collect(),
+						output: vec![
+							"Input form: urn",
+							"Version: 4 (Random)",
+							"Variant: RFC 4122",
+							"Valid: true",
+						]
+						.into_iter()
+						.map(Into::into)
+						.collect(),
+						is_example: true,
+						is_test: true,
+						since: "0.17.0".to_string(),
+					},
+					Case {
+						desc: "Parse simple UUID".to_string(),
+						input: vec!["550e8400e29b41d4a716446655440000"]
+							.into_iter()
+							.map(Into::into)
+							.collect(),
+						output: vec![
+							"Input form: simple",
+							"Version: 4 (Random)",
+							"Variant: RFC 4122",
+							"Valid: true",
+						]
+						.into_iter()
+						.map(Into::into)
+						.collect(),
+						is_example: false,
+						is_test: true,
+						since: "0.17.0".to_string(),
+					},
+					Case {
+						desc: "Show the integer and the fields".to_string(),
+						input: vec!["--raw-fields", "550e8400-e29b-41d4-a716-446655440000"]
+							.into_iter()
+							.map(Into::into)
+							.collect(),
+						output: vec![
+							"Input form: hyphenated",
+							"Version: 4 (Random)",
+							"Variant: RFC 4122",
+							"Integer (dec): 113059749145936325402354257176981405696",
+							"Integer (hex): 0x550e8400e29b41d4a716446655440000",
+							"Fields: time_low=550e8400 time_mid=e29b time_hi_and_version=41d4 clock_seq=a716 node=446655440000",
+							"Valid: true",
+						]
+						.into_iter()
+						.map(Into::into)
+						.collect(),
+						is_example: true,
+						is_test: true,
+						since: "0.17.0".to_string(),
+					},
+				],
+			),
+			(
+				"uuid_compare",
+				vec![
+					Case {
+						desc: "Compare two v7 UUIDs".to_string(),
+						input: vec![
+							"01890a5d-ac96-774b-bcce-b302099a8057",
+							"01890a5d-ac97-774b-bcce-b302099a8057",
+						]
+						.into_iter()
+						.map(Into::into)
+						.collect(),
+						output: vec![
+							"A: 01890a5d-ac96-774b-bcce-b302099a8057",
+							"B: 01890a5d-ac97-774b-bcce-b302099a8057",
+							"Equal: false",
+							"Lexicographic: A < B",
+							"Timestamp: A earlier than B",
+							"Differing bytes: 1 of 16 at [5]",
+						]
+						.into_iter()
+						.map(Into::into)
+						.collect(),
+						is_example: true,
+						is_test: true,
+						since: "0.17.0".to_string(),
+					},
+					Case {
+						desc: "Compare UUIDs of mixed versions".to_string(),
+						input: vec![
+							"550e8400-e29b-41d4-a716-446655440000",
+							"01890a5d-ac96-774b-bcce-b302099a8057",
+						]
+						.into_iter()
+						.map(Into::into)
+						.collect(),
+						output: vec![
+							"A: 550e8400-e29b-41d4-a716-446655440000",
+							"B: 01890a5d-ac96-774b-bcce-b302099a8057",
+							"Equal: false",
+							"Lexicographic: A > B",
+							"Timestamp: not applicable (v4 vs v7)",
+							"Differing bytes: 16 of 16 at [0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15]",
+						]
+						.into_iter()
+						.map(Into::into)
+						.collect(),
+						is_example: false,
+						is_test: true,
+						since: "0.17.0".to_string(),
+					},
+					Case {
+						desc: "Compare as JSON".to_string(),
+						input: vec![
+							"--json",
+							"550e8400-e29b-41d4-a716-446655440000",
+							"{550E8400-E29B-41D4-A716-446655440000}",
+						]
+						.into_iter()
+						.map(Into::into)
+						.collect(),
+						output: vec![
+							r#"{
+  "a": "550e8400-e29b-41d4-a716-446655440000",
+  "b": "550e8400-e29b-41d4-a716-446655440000",
+  "differing_bytes": [],
+  "equal": true,
+  "lexicographic": "equal",
+  "timestamp": null
+}"#,
+						]
+						.into_iter()
+						.map(Into::into)
+						.collect(),
+						is_example: true,
+						is_test: true,
+						since: "0.17.0".to_string(),
+					},
+				],
+			),
+			(
+				"uuid_validate",
+				vec![
+					Case {
+						desc: "Validate UUID".to_string(),
+						input: vec!["550e8400-e29b-41d4-a716-446655440000"]
+							.into_iter()
+							.map(Into::into)
+							.collect(),
+						output: vec!["valid"].into_iter().map(Into::into).collect(),
+						is_example: true,
+						is_test: true,
+						since: "0.17.0".to_string(),
+					},
+					Case {
+						desc: "Validate UUID of a required version".to_string(),
+						input: vec![
+							"--require-version",
+							"7",
+							"550e8400-e29b-41d4-a716-446655440000",
+						]
+						.into_iter()
+						.map(Into::into)
+						.collect(),
+						output: vec!["invalid: wrong version: expected 7, got 4"]
+							.into_iter()
+							.map(Into::into)
+							.collect(),
+						is_example: true,
+						is_test: true,
+						since: "0.17.0".to_string(),
+					},
+				],
+			),
+			(
+				"uuid_to_crockford",
+				vec![Case {
+					desc: "".to_string(),
+					input: vec!["550e8400-e29b-41d4-a716-446655440000"]
+						.into_iter()
+						.map(Into::into)
+						.collect(),
+					output: vec!["2N1T201RMV87AAE5J4CSAM8000"]
+						.into_iter()
+						.map(Into::into)
+						.collect(),
+					is_example: true,
+					is_test: true,
+					since: "0.17.0".to_string(),
+				}],
+			),
+			(
+				"uuid_from_crockford",
+				vec![
+					Case {
+						desc: "".to_string(),
+						input: vec!["2N1T201RMV87AAE5J4CSAM8000"]
+							.into_iter()
+							.map(Into::into)
+							.collect(),
+						output: vec!["550e8400-e29b-41d4-a716-446655440000"]
+							.into_iter()
+							.map(Into::into)
+							.collect(),
+						is_example: true,
+						is_test: true,
+						since: "0.17.0".to_string(),
+					},
+					Case {
+						desc: "Ambiguous chars".to_string(),
+						input: vec!["2nlt2o1rmv87aae5j4csam8ooo"]
+							.into_iter()
+							.map(Into::into)
+							.collect(),
+						output: vec!["550e8400-e29b-41d4-a716-446655440000"]
+							.into_iter()
+							.map(Into::into)
+							.collect(),
+						is_example: true,
+						is_test: true,
+						since: "0.17.0".to_string(),
+					},
+				],
+			),
+			(
+				"uuid_normalize",
+				vec![
+					Case {
+						desc: "Braced and uppercase".to_string(),
+						input: vec!["{550E8400-E29B-41D4-A716-446655440000}"]
+							.into_iter()
+							.map(Into::into)
+							.collect(),
+						output: vec!["550e8400-e29b-41d4-a716-446655440000"]
+							.into_iter()
+							.map(Into::into)
+							.collect(),
+						is_example: true,
+						is_test: true,
+						since: "0.17.0".to_string(),
+					},
+					Case {
+						desc: "URN".to_string(),
+						input: vec!["urn:uuid:550e8400-e29b-41d4-a716-446655440000"]
+							.into_iter()
+							.map(Into::into)
+							.collect(),
+						output: vec!["550e8400-e29b-41d4-a716-446655440000"]
+							.into_iter()
+							.map(Into::into)
+							.collect(),
+						is_example: false,
+						is_test: true,
+						since: "0.17.0".to_string(),
+					},
+				],
+			),
+			(
+				"uuid_info",
+				vec![
+					Case {
+						desc: "A random UUID v4".to_string(),
+						input: vec!["550e8400-e29b-41d4-a716-446655440000"]
+							.into_iter()
+							.map(Into::into)
+							.collect(),
+						output: vec![
+							"Summary: random, not sortable, no timestamp",
+							"Version: 4 (Random)",
+							"Time-sortable: no",
+							"MAC address: no",
+							"Deterministic: no",
+							"Created: unknown (no timestamp)",
+						]
+						.into_iter()
+						.map(Into::into)
+						.collect(),
+						is_example: true,
+						is_test: true,
+						since: "0.17.0".to_string(),
+					},
+					Case {
+						desc: "A UUID v7 of RFC 9562, sortable by creation time".to_string(),
+						input: vec!["017f22e2-79b0-7cc3-98c4-dc0c0c07398f"]
+							.into_iter()
+							.map(Into::into)
+							.collect(),
+						output: vec![
+							"Summary: time-sortable, embeds creation time",
+							"Version: 7 (Unix timestamp)",
+							"Time-sortable: yes",
+							"MAC address: no",
+							"Deterministic: no",
+							"Created: 2022-02-22T19:22:22Z",
+						]
+						.into_iter()
+						.map(Into::into)
+						.collect(),
+						is_example: true,
+						is_test: true,
+						since: "0.17.0".to_string(),
+					},
+					Case {
+						desc: "A UUID v1 with the MAC address of its host".to_string(),
+						input: vec!["c232ab00-9414-11ec-b3c8-001122334455"]
+							.into_iter()
+							.map(Into::into)
+							.collect(),
+						output: vec![
+							"Summary: not sortable, embeds a MAC address, embeds creation time",
+							"Version: 1 (Timestamp and MAC)",
+							"Time-sortable: no",
+							"MAC address: 00:11:22:33:44:55",
+							"Deterministic: no",
+							"Created: 2022-02-22T19:22:22Z",
+						]
+						.into_iter()
+						.map(Into::into)
+						.collect(),
+						is_example: true,
+						is_test: true,
+						since: "0.17.0".to_string(),
+					},
+					Case {
+						desc: "A UUID v5, the same for the same namespace and name".to_string(),
+						input: vec!["cfbff0d1-9375-5685-968c-48ce8b15ae17"]
+							.into_iter()
+							.map(Into::into)
+							.collect(),
+						output: vec![
+							"Summary: deterministic (SHA-1 of a namespace and a name), not sortable, no timestamp",
+							"Version: 5 (SHA-1 hash)",
+							"Time-sortable: no",
+							"MAC address: no",
+							"Deterministic: yes (SHA-1 of a namespace and a name)",
+							"Created: unknown (no timestamp)",
+						]
+						.into_iter()
+						.map(Into::into)
+						.collect(),
+						is_example: false,
+						is_test: true,
+						since: "0.17.0".to_string(),
+					},
+				],
+			),
+		]
+		.into_iter()
+		.collect()
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::app;
+	use crate::modules::base::test::test_module;
+
+	#[test]
+	fn test_cases() {
+		test_module(module());
+	}
+
+	#[test]
+	fn test_gen_out_file() {
+		let path = std::env::temp_dir().join(format!("dtool_uuid_gen_{}.sql", std::process::id()));
+		let _ = std::fs::remove_file(&path);
+		let path = path.to_str().unwrap();
+
+		let matches = commands()[0].app.clone().get_matches_from(vec![
+			"uuid_gen",
+			"-c",
+			"3",
+			"--format-file",
+			"sql",
+			"--table",
+			"users",
+			"--out-file",
+			path,
+		]);
+		assert_eq!(
+			uuid_gen(&matches).unwrap().render(false).unwrap(),
+			vec![format!("3 UUIDs written to: {}", path)]
+		);
+		let content = std::fs::read_to_string(path).unwrap();
+		let lines = content.lines().collect::<Vec<_>>();
+		assert_eq!(lines.len(), 3);
+		assert!(lines
+			.iter()
+			.all(|x| x.starts_with("INSERT INTO users (id) VALUES ('") && x.ends_with("');")));
+		std::fs::remove_file(path).unwrap();
+
+		let uuids = generate_v7(2);
+		assert_eq!(
+			format_uuids(&uuids, "csv", ""),
+			vec!["id".to_string(), uuids[0].to_string(), uuids[1].to_string()]
+		);
+		assert!(check_table(Some("public.users")).is_ok());
+		assert!(check_table(Some("users; DROP TABLE users")).is_err());
+		assert!(check_table(None).is_err());
+	}
+
+	#[test]
+	fn test_v8_parse() {
+		let app = &commands()[0].app;
+		let matches = app.clone().get_matches_from(vec![
+			"uuid_gen",
+			"-v",
+			"8",
+			"-b",
+			"0xffffffffffffffffffffffffffffffff",
+		]);
+		let uuid = uuid_gen(&matches).unwrap().render(false).unwrap().remove(0);
+		assert_eq!(uuid, "ffffffff-ffff-8fff-bfff-ffffffffffff");
+
+		let app = &commands()[1].app;
+		let matches = app
+			.clone()
+			.get_matches_from(vec!["uuid_parse", uuid.as_str()]);
+		let result = uuid_parse(&matches).unwrap().render(false).unwrap();
+		assert!(result.contains(&"Version: 8 (Custom)".to_string()));
+		assert!(result.contains(&"Variant: RFC 4122".to_string()));
+
+		let app = &commands()[0].app;
+		let matches = app
+			.clone()
+			.get_matches_from(vec!["uuid_gen", "-v", "8", "-b", "0x0011"]);
+		assert!(uuid_gen(&matches).is_err());
+	}
+
+	#[test]
+	fn test_v1_random_node() {
+		let app = &commands()[0].app;
+		let matches = app
+			.clone()
+			.get_matches_from(vec!["uuid_gen", "-v", "1", "--node", "random", "-c", "2"]);
+		let uuids = uuid_gen(&matches).unwrap().render(false).unwrap();
+
+		let nodes = uuids
+			.iter()
+			.map(|x| {
+				let (uuid, _) = parse(x).unwrap();
+				assert_eq!(uuid.get_version_num(), 1);
+				uuid.as_bytes()[10..].to_vec()
+			})
+			.collect::<Vec<_>>();
+		assert_ne!(nodes[0], nodes[1]);
+		assert!(nodes.iter().all(|x| x[0] & 0x01 == 0x01));
+
+		let args = vec!["uuid_gen", "-v", "1", "--node", "0x0a0b0c0d0e0f"];
+		let matches = app.clone().get_matches_from(args);
+		let uuid = uuid_gen(&matches).unwrap().render(false).unwrap().remove(0);
+		assert!(uuid.ends_with("-0a0b0c0d0e0f"));
+	}
+
+	#[test]
+	fn test_age() {
+		let created = 1_700_000_000;
+		let ts = Timestamp::from_unix(uuid::timestamp::context::NoContext, created, 0);
+		let uuid = Uuid::new_v7(ts);
+		let (secs, _) = uuid.get_timestamp().unwrap().to_unix();
+
+		assert_eq!(
+			humanize_age(secs, created + 3 * 3600 + 12 * 60 + 7),
+			"3h 12m ago"
+		);
+		assert_eq!(humanize_age(secs, created + 2 * 86400 + 59), "2d ago");
+		assert_eq!(humanize_age(secs, created), "0s ago");
+		assert_eq!(humanize_age(secs, created - 5), "in 5s");
+	}
+
+	#[test]
+	fn test_parse_age() {
+		// A v7 UUID created at 1_700_000_000, 2023-11-14T22:13:20Z
+		let uuid = "018bcfe5-6800-7000-8000-000000000000";
+		let matches = commands()[1]
+			.app
+			.clone()
+			.get_matches_from(vec!["uuid_parse", uuid]);
+		let created = 1_700_000_000;
+
+		let age = |now| {
+			uuid_parse_at(&matches, now)
+				.unwrap()
+				.render(false)
+				.unwrap()
+				.into_iter()
+				.find(|line| line.starts_with("Age: "))
+		};
+		assert_eq!(
+			age(created + 3 * 3600 + 12 * 60 + 7),
+			Some("Age: 3h 12m ago".to_string())
+		);
+		assert_eq!(age(created - 5), Some("Age: in 5s".to_string()));
+	}
+
+	#[test]
+	fn test_crockford() {
+		for uuid in [
+			Uuid::nil(),
+			Uuid::max(),
+			Uuid::new_v4(),
+			Uuid::parse_str("550e8400-e29b-41d4-a716-446655440000").unwrap(),
+		] {
+			let encoded = to_crockford(&uuid);
+			assert_eq!(encoded.len(), 26);
+			assert_eq!(from_crockford(&encoded), Ok(uuid));
+			assert_eq!(from_crockford(&encoded.to_lowercase()), Ok(uuid));
+		}
+		assert_eq!(to_crockford(&Uuid::max()), "7ZZZZZZZZZZZZZZZZZZZZZZZZZ");
+
+		// O is 0, I and L are 1, hyphens are ignored
+		assert_eq!(
+			from_crockford("2NLT2O1RMV-87AAE5J4CS-AM8OOO"),
+			from_crockford("2N1T201RMV87AAE5J4CSAM8000")
+		);
+		assert_eq!(
+			from_crockford("0000000000000000000000000i"),
+			Ok(Uuid::from_u128(1))
+		);
+
+		for (input, reason) in [
+			("2N1T201RMV87AAE5J4CSAM800", "expected 26 chars, got 25"),
+			("2N1T201RMV87AAE5J4CSAM80000", "expected 26 chars, got 27"),
+			(
+				"8ZZZZZZZZZZZZZZZZZZZZZZZZZ",
+				"the first char must be 0 to 7",
+			),
+			("2N1T201RMV87AAE5J4CSAM800u", "unexpected char 'u'"),
+		] {
+			assert_eq!(
+				from_crockford(input),
+				Err(Error::invalid_input(format!(
+					"Invalid Crockford base32: {}",
+					reason
+				)))
+			);
+		}
+	}
+
+	#[test]
+	fn test_normalize() {
+		let app = &commands()[6].app;
+		let normalize = |input: &str| {
+			let matches = app.clone().get_matches_from(vec!["uuid_normalize", input]);
+			uuid_normalize(&matches).and_then(|x| x.render(false).map_err(Error::from))
+		};
+
+		assert_eq!(
+			normalize("{550E8400E29B41D4A716446655440000}"),
+			Ok(vec!["550e8400-e29b-41d4-a716-446655440000".to_string()])
+		);
+		assert_eq!(
+			normalize("550E8400E29B41D4A716446655440000\n\nURN:UUID:550e8400-e29b-41d4-a716-446655440000\n"),
+			Ok(vec![
+				"550e8400-e29b-41d4-a716-446655440000".to_string(),
+				"550e8400-e29b-41d4-a716-446655440000".to_string(),
+			])
+		);
+		assert_eq!(
+			normalize("550e8400-e29b-41d4-a716-446655440000\nnot a uuid"),
+			Err(Error::ValidationFailed(
+				"Invalid UUID on line 2: not a uuid".to_string()
+			))
+		);
+	}
+
+	#[test]
+	fn test_info() {
+		let summary = |uuid: &str| match info(&Uuid::parse_str(uuid).unwrap()) {
+			Ok(info) => info.summary(),
+			Err(summary) => summary,
+		};
+		assert_eq!(
+			summary("550e8400-e29b-41d4-a716-446655440000"),
+			"random, not sortable, no timestamp"
+		);
+		assert_eq!(
+			summary("017f22e2-79b0-7cc3-98c4-dc0c0c07398f"),
+			"time-sortable, embeds creation time"
+		);
+		// The node of the examples of RFC 9562 has its multicast bit set
+		assert_eq!(
+			summary("1ec9414c-232a-6b00-b3c8-9f6bdeced846"),
+			"time-sortable, random node, embeds creation time"
+		);
+		assert_eq!(
+			summary("9073926b-929f-31c2-abc9-fad77ae3e8eb"),
+			"deterministic (MD5 of a namespace and a name), not sortable, no timestamp"
+		);
+		assert_eq!(
+			summary("00000000-0000-0000-0000-000000000000"),
+			"nil UUID, all zeros"
+		);
+		// A Microsoft GUID variant
+		assert_eq!(
+			summary("550e8400-e29b-41d4-c716-446655440000"),
+			"Microsoft variant, the version bits mean nothing"
+		);
+
+		let app = commands()[7].app.clone().args(&app::global_args());
+		let matches = app.get_matches_from(vec![
+			"uuid_info",
+			"--json",
+			"017f22e2-79b0-7cc3-98c4-dc0c0c07398f",
+		]);
+		let result = match uuid_info(&matches) {
+			Ok(Output::Structured(result)) => result,
+			output => panic!("Unexpected output: {:?}", output),
+		};
+		assert_eq!(result["sortable"], true);
+		assert_eq!(result["mac_address"], json!(null));
+		assert_eq!(result["deterministic"], false);
+		assert_eq!(result["created"], "2022-02-22T19:22:22Z");
+	}
+
+	#[test]
+	fn test_parse_raw() {
+		let uuid = Uuid::parse_str("550e8400-e29b-41d4-a716-446655440000").unwrap();
+		let fields = fields(&uuid);
+		// Each field is its bytes in order, the version in the high nibble of
+		// time_hi_and_version and the variant in the high bits of clock_seq
+		let bytes = fields
+			.iter()
+			.flat_map(|(_, value)| hex::decode(value).unwrap())
+			.collect::<Vec<_>>();
+		assert_eq!(bytes, uuid.as_bytes().to_vec());
+		assert_eq!(
+			fields.iter().map(|(_, x)| x.len() / 2).collect::<Vec<_>>(),
+			vec![4, 2, 2, 2, 6]
+		);
+		assert_eq!(fields[2], ("time_hi_and_version", "41d4".to_string()));
+		assert!(fields[2].1.starts_with('4'));
+		assert_eq!(fields[3], ("clock_seq", "a716".to_string()));
+
+		let app = commands()[1].app.clone().args(&app::global_args());
+		let matches = app.get_matches_from(vec![
+			"uuid_parse",
+			"--raw-fields",
+			"--json",
+			"550e8400-e29b-41d4-a716-446655440000",
+		]);
+		let result = match uuid_parse(&matches) {
+			Ok(Output::Structured(result)) => result,
+			output => panic!("Unexpected output: {:?}", output),
+		};
+		assert_eq!(
+			result["integer"]["dec"],
+			"113059749145936325402354257176981405696"
+		);
+		assert_eq!(result["fields"]["node"], "446655440000");
+	}
+
+	#[test]
+	fn test_parse_date() {
+		let ts = Timestamp::from_unix(
+			uuid::timestamp::context::NoContext,
+			1_516_239_022,
+			123_000_000,
+		);
+		let uuid = Uuid::new_v7(ts).to_string();
+		let app = &commands()[1].app;
+		let matches = app
+			.clone()
+			.get_matches_from(vec!["uuid_parse", uuid.as_str()]);
+		let result = uuid_parse(&matches).unwrap().render(false).unwrap();
+		assert!(result.contains(&"Date: 2018-01-18T01:30:22.123Z".to_string()));
+	}
+
+	#[test]
+	fn test_generate() {
+		let options = GenerateOptions {
+			namespace: Some(Uuid::NAMESPACE_DNS),
+			name: Some("example.com".to_string()),
+			..Default::default()
+		};
+		assert_eq!(
+			generate(5, &options).unwrap().to_string(),
+			"cfbff0d1-9375-5685-968c-48ce8b15ae17"
+		);
+		assert_eq!(generate(4, &options).unwrap().get_version_num(), 4);
+		assert_eq!(
+			generate(3, &GenerateOptions::default()),
+			Err(
+				Error::Usage("Namespace (-n) is required for v3".to_string())
+					.with_hint("e.g. -n dns -s example.com")
+			)
+		);
+		assert_eq!(
+			generate(9, &options),
+			Err(Error::Unsupported(
+				"Unsupported UUID version: 9".to_string()
+			))
+		);
+
+		let options = GenerateOptions {
+			node: Some([0x0a, 0x0b, 0x0c, 0x0d, 0x0e, 0x0f]),
+			..Default::default()
+		};
+		let uuid = generate(1, &options).unwrap();
+		assert_eq!(
+			&uuid.as_bytes()[10..],
+			&[0x0a, 0x0b, 0x0c, 0x0d, 0x0e, 0x0f]
+		);
+
+		// The raw bytes of the DNS namespace give the same UUID, run after run
+		let matches = commands()[0].app.clone().get_matches_from(vec![
+			"uuid_gen",
+			"-v",
+			"5",
+			"--namespace-hex",
+			"6ba7b8109dad11d180b400c04fd430c8",
+			"-s",
+			"example.com",
+		]);
+		for _ in 0..2 {
+			assert_eq!(
+				gen_uuid(&matches, "5").unwrap().to_string(),
+				"cfbff0d1-9375-5685-968c-48ce8b15ae17"
+			);
+		}
+		assert_eq!(
+			namespace_hex("6ba7b810"),
+			Err(Error::invalid_input(
+				"Invalid namespace hex: exactly 16 bytes are required".to_string()
+			))
+		);
+
+		let (uuid, form) = parse("{CFBFF0D1-9375-5685-968C-48CE8B15AE17}").unwrap();
+		assert_eq!(uuid.to_string(), "cfbff0d1-9375-5685-968c-48ce8b15ae17");
+		assert_eq!(form, "braced");
+	}
+
+	#[test]
+	fn test_v7_monotonic() {
+		let uuids = generate_v7(100_000);
+		assert!(uuids.iter().all(|x| x.get_version_num() == 7));
+		assert!(uuids.windows(2).all(|x| x[0] < x[1]));
+
+		let app = &commands()[0].app;
+		let matches = app
+			.clone()
+			.get_matches_from(vec!["uuid_gen", "-v", "7", "-c", "100000"]);
+		let uuids = uuid_gen(&matches).unwrap().render(false).unwrap();
+		assert_eq!(uuids.len(), 100_000);
+		assert!(uuids.windows(2).all(|x| x[0] < x[1]));
+	}
+
+	#[test]
+	fn test_validate() {
+		let app = &commands()[3].app;
+		let validate = |args: Vec<&str>| {
+			let matches = app
+				.clone()
+				.get_matches_from(Some("uuid_validate").into_iter().chain(args));
+			let result = uuid_validate(&matches).unwrap().render(false).unwrap();
+			(result, base::exit_code())
+		};
+		let uuid = "550e8400-e29b-41d4-a716-446655440000";
+
+		assert_eq!(validate(vec![uuid]), (vec!["valid".to_string()], 0));
+		assert_eq!(
+			validate(vec!["--require-version", "4", uuid]),
+			(vec!["valid".to_string()], 0)
+		);
+		assert_eq!(
+			validate(vec!["--require-version", "7", uuid]),
+			(
+				vec!["invalid: wrong version: expected 7, got 4".to_string()],
+				exit_code::INVALID
+			)
+		);
+		let (result, code) = validate(vec!["550e8400-e29b-41d4-a716"]);
+		assert!(result[0].starts_with("invalid: "));
+		assert_eq!(code, exit_code::INVALID);
+	}
+
+	#[test]
+	fn test_compare_exit_code() {
+		let app = &commands()[2].app;
+		let a = "01890a5d-ac96-774b-bcce-b302099a8057";
+		let b = "01890a5d-ac97-774b-bcce-b302099a8057";
+
+		for (input, expected) in [
+			([a, a], 0),
+			([a, b], exit_code::LESS),
+			([b, a], exit_code::GREATER),
+		] {
+			let mut args = vec!["uuid_compare"];
+			args.extend(input);
+			let matches = app.clone().get_matches_from(args);
+			uuid_compare(&matches).unwrap();
+			assert_eq!(base::exit_code(), expected);
+		}
+	}
+
+	#[test]
+	fn test_empty_input() {
+		let matches = commands()[1]
+			.app
+			.clone()
+			.get_matches_from(vec!["uuid_parse", ""]);
+		assert_eq!(
+			uuid_parse(&matches),
+			Err(Error::Other(base::NO_INPUT.to_string()))
+		);
 	}
 }
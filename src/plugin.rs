@@ -0,0 +1,104 @@
+use crate::log;
+use std::env;
+use std::ffi::{OsStr, OsString};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// Executables named `dtool-<name>` in PATH run as the sub command `<name>`
+const PREFIX: &str = "dtool-";
+
+#[cfg(windows)]
+const SUFFIXES: &[&str] = &[".exe", ".bat", ".cmd"];
+#[cfg(not(windows))]
+const SUFFIXES: &[&str] = &[""];
+
+/// The first plugin in PATH for the sub command
+pub fn find(name: &str) -> Option<PathBuf> {
+	if name.is_empty() || name.contains(['/', '\\']) {
+		return None;
+	}
+	path_dirs().into_iter().find_map(|dir| {
+		SUFFIXES
+			.iter()
+			.map(|suffix| dir.join(format!("{}{}{}", PREFIX, name, suffix)))
+			.find(|path| is_executable(path))
+	})
+}
+
+/// The plugins in PATH sorted by name, a plugin shadowed by an earlier dir is skipped
+pub fn discover() -> Vec<(String, PathBuf)> {
+	let mut plugins = Vec::<(String, PathBuf)>::new();
+	for dir in path_dirs() {
+		let entries = match fs::read_dir(&dir) {
+			Ok(entries) => entries,
+			Err(_) => continue,
+		};
+		for entry in entries.filter_map(Result::ok) {
+			let path = entry.path();
+			let name = match plugin_name(&entry.file_name()) {
+				Some(name) if is_executable(&path) => name,
+				_ => continue,
+			};
+			if !plugins.iter().any(|(x, _)| *x == name) {
+				plugins.push((name, path));
+			}
+		}
+	}
+	plugins.sort();
+	plugins
+}
+
+/// Run the plugin with stdin and stdout passed through, and exit with its exit code
+pub fn exec(path: &Path, args: &[OsString]) -> ! {
+	match Command::new(path).args(args).status() {
+		Ok(status) => std::process::exit(status.code().unwrap_or(1)),
+		Err(e) => {
+			log::error(&format!("Failed to run plugin {}: {}", path.display(), e));
+			std::process::exit(1);
+		}
+	}
+}
+
+fn plugin_name(file_name: &OsStr) -> Option<String> {
+	let name = file_name.to_str()?.strip_prefix(PREFIX)?;
+	let name = SUFFIXES
+		.iter()
+		.find_map(|suffix| name.strip_suffix(suffix))?;
+	match name.is_empty() {
+		true => None,
+		false => Some(name.to_string()),
+	}
+}
+
+fn path_dirs() -> Vec<PathBuf> {
+	env::var_os("PATH")
+		.map(|x| env::split_paths(&x).collect())
+		.unwrap_or_default()
+}
+
+#[cfg(unix)]
+fn is_executable(path: &Path) -> bool {
+	use std::os::unix::fs::PermissionsExt;
+	fs::metadata(path)
+		.map(|x| x.is_file() && x.permissions().mode() & 0o111 != 0)
+		.unwrap_or(false)
+}
+
+#[cfg(not(unix))]
+fn is_executable(path: &Path) -> bool {
+	path.is_file()
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_plugin_name() {
+		let name = |x: &str| plugin_name(OsStr::new(&format!("{}{}", x, SUFFIXES[0])));
+		assert_eq!(name("dtool-hello"), Some("hello".to_string()));
+		assert_eq!(name("dtool-"), None);
+		assert_eq!(name("hello"), None);
+	}
+}
@@ -0,0 +1 @@
+alert('Hello, world.');
\ No newline at end of file